@@ -27,6 +27,19 @@ pub trait Binary {
     ///
     /// Returns `Ok(())` if the read operation is successful, or an `Err` if an error occurs.
     fn read_binary<R: BufRead>(&mut self, reader: R) -> Result<()>;
+
+    /// Reads exactly `word_count` 64-bit words of binary data into a struct from the given reader.
+    /// Any data following these words is left untouched in the reader.
+    ///
+    /// # Arguments
+    ///
+    /// * `reader` - The reader to read the binary data from.
+    /// * `word_count` - The number of 64-bit words to read.
+    ///
+    /// # Returns
+    ///
+    /// Returns `Ok(())` if the read operation is successful, or an `Err` if an error occurs.
+    fn read_binary_exact<R: BufRead>(&mut self, reader: R, word_count: usize) -> Result<()>;
 }
 
 /// Implementation of the `Binary` trait for the `BitArray` struct.
@@ -75,6 +88,31 @@ impl Binary for BitArray {
 
         Ok(())
     }
+
+    /// Reads exactly `word_count` words of the binary representation of the `BitArray` from the
+    /// given reader, so that the `BitArray` can be stored in front of other data.
+    ///
+    /// # Arguments
+    ///
+    /// * `reader` - The reader from which the binary data will be read.
+    /// * `word_count` - The number of 64-bit words to read.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if there was a problem reading from the reader, or if the reader does not
+    /// contain enough data.
+    fn read_binary_exact<R: BufRead>(&mut self, mut reader: R, word_count: usize) -> Result<()> {
+        self.data.clear();
+        self.data.reserve(word_count);
+
+        let mut buffer = [0; 8];
+        for _ in 0..word_count {
+            reader.read_exact(&mut buffer)?;
+            self.data.push(u64::from_le_bytes(buffer));
+        }
+
+        Ok(())
+    }
 }
 
 /// Fills the buffer with data read from the input.
@@ -188,4 +226,32 @@ mod tests {
         assert_eq!(bitarray.get(2), 0x4567890abc);
         assert_eq!(bitarray.get(3), 0xdef0123456);
     }
+
+    #[test]
+    fn test_read_binary_exact_with_trailing_data() {
+        let buffer = vec![
+            0xef, 0xcd, 0xab, 0x90, 0x78, 0x56, 0x34, 0x12, 0xde, 0xbc, 0x0a, 0x89, 0x67, 0x45, 0x23, 0x01, 0x00, 0x00,
+            0x00, 0x00, 0x56, 0x34, 0x12, 0xf0, 0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08, 0x09,
+        ];
+
+        let mut reader = &buffer[..];
+        let mut bitarray = BitArray::with_capacity(4, 40);
+        bitarray.read_binary_exact(&mut reader, 3).unwrap();
+
+        assert_eq!(bitarray.get(0), 0x1234567890);
+        assert_eq!(bitarray.get(1), 0xabcdef0123);
+        assert_eq!(bitarray.get(2), 0x4567890abc);
+        assert_eq!(bitarray.get(3), 0xdef0123456);
+
+        // The trailing data should still be available in the reader
+        assert_eq!(reader, [0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08, 0x09]);
+    }
+
+    #[test]
+    fn test_read_binary_exact_not_enough_data() {
+        let buffer = vec![0xef, 0xcd, 0xab, 0x90, 0x78, 0x56, 0x34, 0x12, 0xde, 0xbc];
+
+        let mut bitarray = BitArray::with_capacity(4, 40);
+        assert!(bitarray.read_binary_exact(&buffer[..], 3).is_err());
+    }
 }
@@ -7,10 +7,13 @@ use std::{
     io::{Result, Write}
 };
 
+use rayon::prelude::*;
+
 /// Re-export the `Binary` trait.
 pub use binary::Binary;
 
 /// A fixed-size bit array implementation.
+#[derive(Clone)]
 pub struct BitArray {
     /// The underlying data storage for the bit array.
     data: Vec<u64>,
@@ -43,6 +46,84 @@ impl BitArray {
         }
     }
 
+    /// Creates a new `BitArray` containing the given values.
+    ///
+    /// # Arguments
+    ///
+    /// * `values` - The values to store in the `BitArray`.
+    /// * `bits_per_value` - The number of bits in a single value.
+    ///
+    /// # Returns
+    ///
+    /// A new `BitArray` containing the given values.
+    pub fn from_values(values: &[u64], bits_per_value: usize) -> Self {
+        let mut bitarray = Self::with_capacity(values.len(), bits_per_value);
+        for (index, &value) in values.iter().enumerate() {
+            bitarray.set(index, value);
+        }
+        bitarray
+    }
+
+    /// Appends a value to the end of the `BitArray`, growing the underlying data storage when the
+    /// value does not fit in the last block.
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - The value to append.
+    pub fn push(&mut self, value: u64) {
+        // The value can straddle the last block, so all the blocks containing its bits are added
+        let required_blocks = ((self.len + 1) * self.bits_per_value).div_ceil(64);
+        if required_blocks > self.data.len() {
+            self.data.resize(required_blocks, 0);
+        }
+
+        self.len += 1;
+        self.set(self.len - 1, value);
+    }
+
+    /// Shortens the `BitArray` to the specified length. The bits of the removed values are cleared,
+    /// so they do not show up again when the `BitArray` grows. Nothing happens if the `BitArray` is
+    /// not longer than `new_len`.
+    ///
+    /// # Arguments
+    ///
+    /// * `new_len` - The new length of the `BitArray`.
+    pub fn truncate(&mut self, new_len: usize) {
+        if new_len >= self.len {
+            return;
+        }
+
+        self.len = new_len;
+        self.data.truncate((new_len * self.bits_per_value).div_ceil(64));
+
+        // Clear the bits of the removed values in the last, partially used block
+        let used_bits = new_len * self.bits_per_value % 64;
+        if used_bits != 0 {
+            if let Some(last_block) = self.data.last_mut() {
+                *last_block &= !(u64::MAX >> used_bits);
+            }
+        }
+    }
+
+    /// Resizes the `BitArray` to the specified length. If the `BitArray` grows, the new positions
+    /// are filled with `value`, otherwise the `BitArray` is truncated.
+    ///
+    /// # Arguments
+    ///
+    /// * `new_len` - The new length of the `BitArray`.
+    /// * `value` - The value used to fill the new positions.
+    pub fn resize(&mut self, new_len: usize, value: u64) {
+        if new_len <= self.len {
+            self.truncate(new_len);
+            return;
+        }
+
+        self.data.reserve((new_len * self.bits_per_value).div_ceil(64) - self.data.len());
+        for _ in self.len..new_len {
+            self.push(value);
+        }
+    }
+
     /// Retrieves the value at the specified index in the `BitArray`.
     ///
     /// # Arguments
@@ -112,6 +193,69 @@ impl BitArray {
         self.data[end_block] |= value << (64 - end_block_offset);
     }
 
+    /// Checks if the values starting at the specified index are equal to the given values.
+    /// Multiple values are compared at once, instead of retrieving them one by one.
+    ///
+    /// # Arguments
+    ///
+    /// * `start` - The index of the first value to compare.
+    /// * `values` - The values to compare with.
+    ///
+    /// # Returns
+    ///
+    /// `true` if all the values are equal, `false` otherwise or if the range exceeds the length of
+    /// the `BitArray`.
+    pub fn range_equals(&self, start: usize, values: &[u64]) -> bool {
+        if start + values.len() > self.len {
+            return false;
+        }
+
+        let values_per_chunk = 64 / self.bits_per_value;
+        for (chunk_index, chunk) in values.chunks(values_per_chunk).enumerate() {
+            // Pack the values in the same order as they are stored in the bit array
+            let packed = chunk.iter().fold(0, |packed, &value| packed << self.bits_per_value | (value & self.mask));
+
+            let start_bit = (start + chunk_index * values_per_chunk) * self.bits_per_value;
+            if self.get_bits(start_bit, chunk.len() * self.bits_per_value) != packed {
+                return false;
+            }
+        }
+
+        true
+    }
+
+    /// Retrieves `bit_count` consecutive bits, starting at the specified bit.
+    ///
+    /// # Arguments
+    ///
+    /// * `start_bit` - The index of the first bit to retrieve.
+    /// * `bit_count` - The number of bits to retrieve, at most 64.
+    ///
+    /// # Returns
+    ///
+    /// The bits in the least significant positions of a `u64`.
+    fn get_bits(&self, start_bit: usize, bit_count: usize) -> u64 {
+        if bit_count == 0 {
+            return 0;
+        }
+
+        let mask = if bit_count == 64 { u64::MAX } else { (1 << bit_count) - 1 };
+        let start_block = start_bit / 64;
+        let start_block_offset = start_bit % 64;
+
+        // If the bits are contained within a single block
+        if start_block_offset + bit_count <= 64 {
+            return self.data[start_block] >> (64 - start_block_offset - bit_count) & mask;
+        }
+
+        // The bits are spread over two blocks
+        let end_block_bit_count = start_block_offset + bit_count - 64;
+        let a = self.data[start_block] << end_block_bit_count;
+        let b = self.data[start_block + 1] >> (64 - end_block_bit_count);
+
+        (a | b) & mask
+    }
+
     /// Returns the number of bits in a single value.
     ///
     /// # Returns
@@ -121,6 +265,24 @@ impl BitArray {
         self.bits_per_value
     }
 
+    /// Returns the mask used to extract a single value from the underlying data.
+    ///
+    /// # Returns
+    ///
+    /// The mask with the lowest `bits_per_value` bits set.
+    pub fn mask(&self) -> u64 {
+        self.mask
+    }
+
+    /// Returns the number of 64-bit words used to store the values.
+    ///
+    /// # Returns
+    ///
+    /// The number of 64-bit words in the underlying data.
+    pub fn capacity_words(&self) -> usize {
+        self.data.len()
+    }
+
     /// Returns the length of the `BitArray`.
     ///
     /// # Returns
@@ -149,6 +311,40 @@ impl BitArray {
     }
 }
 
+/// Two `BitArray`s are equal if they contain the same values with the same amount of bits. The
+/// unused bits at the end of the last block are ignored, since they are not part of any value.
+impl PartialEq for BitArray {
+    fn eq(&self, other: &Self) -> bool {
+        if self.len != other.len || self.bits_per_value != other.bits_per_value {
+            return false;
+        }
+
+        let used_bits = self.len * self.bits_per_value;
+        let full_blocks = used_bits / 64;
+        if self.data[..full_blocks] != other.data[..full_blocks] {
+            return false;
+        }
+
+        let remaining_bits = used_bits % 64;
+        if remaining_bits == 0 {
+            return true;
+        }
+
+        let mask = !(u64::MAX >> remaining_bits);
+        self.data[full_blocks] & mask == other.data[full_blocks] & mask
+    }
+}
+
+impl Eq for BitArray {}
+
+impl Extend<u64> for BitArray {
+    fn extend<T: IntoIterator<Item = u64>>(&mut self, iter: T) {
+        for value in iter {
+            self.push(value);
+        }
+    }
+}
+
 /// Writes the data to a writer in a binary format using a bit array. The data is written
 /// in chunks of the specified capacity, so memory usage is minimized.
 ///
@@ -214,6 +410,52 @@ pub fn data_to_writer(
     Ok(())
 }
 
+/// Writes the data to a writer in the same binary format as `data_to_writer`, but packs the chunks
+/// into bit arrays on multiple threads. The chunks are packed in batches of one chunk per thread,
+/// and every batch is written in order before the next batch is packed, so the output is identical
+/// to `data_to_writer` and at most one batch of chunks is kept in memory.
+///
+/// # Arguments
+///
+/// * `data` - The data to write.
+/// * `bits_per_value` - The number of bits in a single value.
+/// * `max_capacity` - The maximum amount of elements that may be stored in a single chunk.
+/// * `writer` - The writer to write the data to.
+///
+/// # Returns
+///
+/// A `Result` indicating whether the write operation was successful or not.
+pub fn data_to_writer_parallel(
+    data: Vec<i64>,
+    bits_per_value: usize,
+    max_capacity: usize,
+    writer: &mut impl Write
+) -> Result<()> {
+    // Use the same chunk capacity as `data_to_writer`, so the chunks end at the same values
+    let greates_common_divisor = gcd(bits_per_value, 64);
+    let capacity = max(greates_common_divisor, max_capacity / greates_common_divisor * greates_common_divisor);
+
+    let chunks: Vec<&[i64]> = data.chunks(capacity).collect();
+    for batch in chunks.chunks(rayon::current_num_threads()) {
+        let bitarrays: Vec<BitArray> = batch
+            .par_iter()
+            .map(|chunk| {
+                let mut bitarray = BitArray::with_capacity(chunk.len(), bits_per_value);
+                for (i, &value) in chunk.iter().enumerate() {
+                    bitarray.set(i, value as u64);
+                }
+                bitarray
+            })
+            .collect();
+
+        for bitarray in bitarrays {
+            bitarray.write_binary(writer)?;
+        }
+    }
+
+    Ok(())
+}
+
 /// Calculates the greatest common divisor of two numbers.
 ///
 /// # Arguments
@@ -269,12 +511,219 @@ mod tests {
         assert_eq!(bitarray.data, vec![0x1cfac47f32c25261, 0x4dc9f34db6ba5108, 0x9144EB9C00000000]);
     }
 
+    #[test]
+    fn test_bitarray_from_values() {
+        let values = [0x1234567890, 0xabcdef0123, 0x4567890abc, 0xdef0123456];
+        let bitarray = BitArray::from_values(&values, 40);
+
+        let mut expected = BitArray::with_capacity(4, 40);
+        for (index, &value) in values.iter().enumerate() {
+            expected.set(index, value);
+        }
+
+        assert_eq!(bitarray.len(), 4);
+        assert_eq!(bitarray.data, expected.data);
+    }
+
+    #[test]
+    fn test_bitarray_push() {
+        let values = [0x1234567890, 0xabcdef0123, 0x4567890abc, 0xdef0123456, 0x1, 0xffffffffff];
+
+        let mut expected = BitArray::with_capacity(values.len(), 40);
+        for (index, &value) in values.iter().enumerate() {
+            expected.set(index, value);
+        }
+
+        // the second value straddles the first and the second block
+        let mut bitarray = BitArray::with_capacity(0, 40);
+        for &value in values.iter() {
+            bitarray.push(value);
+        }
+
+        assert_eq!(bitarray.len(), values.len());
+        assert_eq!(bitarray.data, expected.data);
+        for (index, &value) in values.iter().enumerate() {
+            assert_eq!(bitarray.get(index), value);
+        }
+    }
+
+    #[test]
+    fn test_bitarray_push_after_with_capacity() {
+        let mut bitarray = BitArray::with_capacity(2, 5);
+        bitarray.set(0, 3);
+        bitarray.set(1, 17);
+        bitarray.push(31);
+
+        assert_eq!(bitarray.len(), 3);
+        assert_eq!(bitarray.get(0), 3);
+        assert_eq!(bitarray.get(1), 17);
+        assert_eq!(bitarray.get(2), 31);
+    }
+
+    #[test]
+    fn test_bitarray_extend() {
+        let mut bitarray = BitArray::with_capacity(0, 11);
+        bitarray.extend((0..100).map(|value| value * 17));
+
+        let values: Vec<u64> = (0..100).map(|value| value * 17).collect();
+        assert_eq!(bitarray.len(), 100);
+        assert_eq!(bitarray.data, BitArray::from_values(&values, 11).data);
+    }
+
+    #[test]
+    fn test_bitarray_truncate() {
+        let values = [0x1234567890, 0xabcdef0123, 0x4567890abc, 0xdef0123456];
+        let mut bitarray = BitArray::from_values(&values, 40);
+
+        // the second value straddles the first and the second block
+        bitarray.truncate(2);
+        assert_eq!(bitarray.len(), 2);
+        assert_eq!(bitarray.data, vec![0x1234567890abcdef, 0x0123000000000000]);
+        assert_eq!(bitarray.get(0), 0x1234567890);
+        assert_eq!(bitarray.get(1), 0xabcdef0123);
+
+        // the removed values are cleared, so they do not reappear
+        bitarray.push(0);
+        assert_eq!(bitarray.get(2), 0);
+
+        bitarray.truncate(10);
+        assert_eq!(bitarray.len(), 3);
+
+        bitarray.truncate(0);
+        assert!(bitarray.is_empty());
+        assert!(bitarray.data.is_empty());
+    }
+
+    #[test]
+    fn test_bitarray_truncate_middle_of_block() {
+        let mut bitarray = BitArray::from_values(&[1, 2, 3, 4, 5, 6, 7], 8);
+        bitarray.truncate(3);
+
+        assert_eq!(bitarray.data, vec![0x0102030000000000]);
+        assert_eq!(bitarray.get(2), 3);
+    }
+
+    #[test]
+    fn test_bitarray_resize() {
+        let mut bitarray = BitArray::from_values(&[0x1234567890, 0xabcdef0123], 40);
+
+        bitarray.resize(5, 0x4567890abc);
+        assert_eq!(bitarray.len(), 5);
+        assert_eq!(bitarray.get(0), 0x1234567890);
+        assert_eq!(bitarray.get(1), 0xabcdef0123);
+        for index in 2..5 {
+            assert_eq!(bitarray.get(index), 0x4567890abc);
+        }
+        assert_eq!(bitarray.data.len(), 4);
+
+        bitarray.resize(1, 0);
+        assert_eq!(bitarray.len(), 1);
+        assert_eq!(bitarray.get(0), 0x1234567890);
+
+        bitarray.resize(3, 7);
+        assert_eq!(bitarray.get(1), 7);
+        assert_eq!(bitarray.get(2), 7);
+    }
+
+    #[test]
+    fn test_bitarray_eq() {
+        let bitarray = BitArray::from_values(&[0x1234567890, 0xabcdef0123, 0x4567890abc], 40);
+        assert!(bitarray == bitarray.clone());
+
+        // the unused bits at the end of the last block are ignored
+        let mut other = bitarray.clone();
+        other.data[1] |= 0xff;
+        assert!(bitarray == other);
+
+        other.data[1] |= 0x100;
+        assert!(bitarray != other);
+    }
+
+    #[test]
+    fn test_bitarray_eq_built_differently() {
+        let mut pushed = BitArray::with_capacity(0, 40);
+        pushed.extend([0x1234567890, 0xabcdef0123]);
+        assert!(pushed == BitArray::from_values(&[0x1234567890, 0xabcdef0123], 40));
+
+        let mut truncated = BitArray::from_values(&[0x1234567890, 0xabcdef0123, 0x4567890abc], 40);
+        truncated.truncate(2);
+        assert!(pushed == truncated);
+    }
+
+    #[test]
+    fn test_bitarray_ne() {
+        let bitarray = BitArray::from_values(&[1, 2, 3], 8);
+        assert!(bitarray != BitArray::from_values(&[1, 2], 8));
+        assert!(bitarray != BitArray::from_values(&[1, 2, 3], 9));
+        assert!(bitarray != BitArray::from_values(&[1, 2, 4], 8));
+    }
+
+    #[test]
+    fn test_bitarray_range_equals() {
+        let mut bitarray = BitArray::with_capacity(4, 40);
+        bitarray.set(0, 0x1234567890);
+        bitarray.set(1, 0xabcdef0123);
+        bitarray.set(2, 0x4567890abc);
+        bitarray.set(3, 0xdef0123456);
+
+        assert!(bitarray.range_equals(1, &[0xabcdef0123, 0x4567890abc]));
+        assert!(bitarray.range_equals(3, &[0xdef0123456]));
+        assert!(bitarray.range_equals(2, &[]));
+        assert!(!bitarray.range_equals(1, &[0xabcdef0123, 0x4567890abd]));
+        assert!(!bitarray.range_equals(3, &[0xdef0123456, 0]));
+    }
+
+    #[test]
+    fn test_bitarray_range_equals_randomized() {
+        // simple linear congruential generator, so the test is deterministic
+        let mut seed: u64 = 42;
+        let mut next_random = move || {
+            seed = seed.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+            seed >> 33
+        };
+
+        let mut bitarray = BitArray::with_capacity(500, 5);
+        for i in 0..500 {
+            bitarray.set(i, next_random() % 27);
+        }
+
+        for _ in 0..1000 {
+            let start = (next_random() % 500) as usize;
+            let length = (next_random() % 40) as usize;
+            let end = (start + length).min(500);
+
+            let mut values: Vec<u64> = (start..end).map(|i| bitarray.get(i)).collect();
+            assert!(bitarray.range_equals(start, &values));
+
+            if !values.is_empty() {
+                let changed_index = (next_random() as usize) % values.len();
+                values[changed_index] = (values[changed_index] + 1) % 27;
+                let expected = (start..end).zip(values.iter()).all(|(i, &value)| bitarray.get(i) == value);
+                assert_eq!(bitarray.range_equals(start, &values), expected);
+            }
+        }
+    }
+
     #[test]
     fn test_bitarray_bits_per_value() {
         let bitarray = BitArray::with_capacity(4, 40);
         assert_eq!(bitarray.bits_per_value(), 40);
     }
 
+    #[test]
+    fn test_bitarray_mask() {
+        assert_eq!(BitArray::with_capacity(4, 40).mask(), 0xff_ffff_ffff);
+        assert_eq!(BitArray::with_capacity(4, 3).mask(), 0b111);
+    }
+
+    #[test]
+    fn test_bitarray_capacity_words() {
+        // 4 values of 40 bits need 160 bits, which are stored in 3 words
+        assert_eq!(BitArray::with_capacity(4, 40).capacity_words(), 3);
+        assert_eq!(BitArray::with_capacity(8, 8).capacity_words(), 1);
+        assert_eq!(BitArray::with_capacity(0, 40).capacity_words(), 0);
+    }
+
     #[test]
     fn test_bitarray_len() {
         let bitarray = BitArray::with_capacity(4, 40);
@@ -375,6 +824,23 @@ mod tests {
         ]);
     }
 
+    #[test]
+    fn test_data_to_writer_parallel() {
+        let data: Vec<i64> = (0..1000).map(|i| (i * 7919) % 1024).collect();
+
+        for bits_per_value in [10, 11, 32, 40] {
+            for max_capacity in [1, 8, 100, 2000] {
+                let mut expected = Vec::new();
+                data_to_writer(data.clone(), bits_per_value, max_capacity, &mut expected).unwrap();
+
+                let mut writer = Vec::new();
+                data_to_writer_parallel(data.clone(), bits_per_value, max_capacity, &mut writer).unwrap();
+
+                assert_eq!(writer, expected);
+            }
+        }
+    }
+
     #[test]
     fn test_gcd() {
         assert_eq!(gcd(40, 64), 8);
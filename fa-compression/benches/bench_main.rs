@@ -2,6 +2,7 @@ use criterion::criterion_main;
 
 mod algorithm1;
 mod algorithm2;
+mod comparison;
 mod util;
 
-criterion_main!(algorithm1::benches, algorithm2::benches);
+criterion_main!(algorithm1::benches, algorithm2::benches, comparison::benches);
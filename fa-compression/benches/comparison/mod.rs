@@ -0,0 +1,85 @@
+use std::collections::HashSet;
+
+use criterion::{black_box, criterion_group, BatchSize, Criterion, Throughput};
+use fa_compression::{algorithm1, algorithm2};
+use rand::Rng;
+
+use super::util::generate_annotation;
+
+/// Generate a corpus of annotation records, where the annotations are drawn from a limited pool so
+/// they occur in multiple records, like in a real database.
+fn generate_corpus(record_count: usize, pool_size: usize) -> Vec<String> {
+    let mut random = rand::thread_rng();
+
+    let pool: Vec<String> = (0..pool_size).map(|_| generate_annotation(&mut random)).collect();
+
+    (0..record_count)
+        .map(|_| {
+            let annotation_count = random.gen_range(1..10);
+            (0..annotation_count)
+                .map(|_| pool[random.gen_range(0..pool_size)].as_str())
+                .collect::<Vec<_>>()
+                .join(";")
+        })
+        .collect()
+}
+
+/// Encode every record of the corpus with algorithm2, building the compression table on the fly.
+fn encode_corpus_algorithm2(corpus: &[String]) -> (Vec<Vec<u8>>, algorithm2::CompressionTable) {
+    let mut compression_table = algorithm2::CompressionTable::new();
+    let encoded =
+        corpus.iter().map(|record| algorithm2::encode_adaptive(record, &mut compression_table).unwrap()).collect();
+
+    (encoded, compression_table)
+}
+
+/// Report the encoded sizes of the corpus for both algorithms.
+pub fn report_encoded_sizes(corpus: &[String]) {
+    let decoded_size: usize = corpus.iter().map(|record| record.len()).sum();
+    let algorithm1_size: usize = corpus.iter().map(|record| algorithm1::encode(record).len()).sum();
+    let (encoded, _) = encode_corpus_algorithm2(corpus);
+    let algorithm2_size: usize = encoded.iter().map(|record| record.len()).sum();
+
+    // Algorithm2 also needs its compression table, which holds every distinct annotation once
+    let distinct_annotations: HashSet<&str> = corpus.iter().flat_map(|record| record.split(';')).collect();
+    let table_size: usize = distinct_annotations.iter().map(|annotation| annotation.len() + 1).sum();
+
+    eprintln!("Encoded sizes of {} records ({} bytes decoded):", corpus.len(), decoded_size);
+    eprintln!("\talgorithm1: {} bytes", algorithm1_size);
+    eprintln!("\talgorithm2: {} bytes (+ {} bytes for the compression table)", algorithm2_size, table_size);
+}
+
+pub fn comparison_benchmark(c: &mut Criterion) {
+    let corpus = generate_corpus(1000, 200);
+    report_encoded_sizes(&corpus);
+
+    let mut group = c.benchmark_group("compare_algorithms");
+    group.throughput(Throughput::Bytes(corpus.iter().map(|record| record.len() as u64).sum()));
+
+    group.bench_function("encode_algorithm1", |b| {
+        b.iter(|| corpus.iter().for_each(|record| drop(black_box(algorithm1::encode(record)))))
+    });
+
+    group.bench_function("encode_algorithm2", |b| b.iter(|| black_box(encode_corpus_algorithm2(&corpus))));
+
+    let algorithm1_encoded: Vec<Vec<u8>> = corpus.iter().map(|record| algorithm1::encode(record)).collect();
+    group.bench_function("decode_algorithm1", |b| {
+        b.iter(|| algorithm1_encoded.iter().for_each(|record| drop(black_box(algorithm1::decode(record)))))
+    });
+
+    // decoding with algorithm2 consumes the compression table, so decode the whole corpus at once
+    group.bench_function("decode_algorithm2", |b| {
+        b.iter_batched(
+            || {
+                let (encoded, compression_table) = encode_corpus_algorithm2(&corpus);
+                (encoded.concat(), compression_table)
+            },
+            |(encoded, compression_table)| black_box(algorithm2::decode(&encoded, compression_table)),
+            BatchSize::SmallInput
+        )
+    });
+
+    group.finish();
+}
+
+criterion_group!(benches, comparison_benchmark);
@@ -1,10 +1,13 @@
 //! This module provides a function to decode a byte array into a string representation of
 //! annotations.
 
-use super::{CharacterSet, Decode};
+use std::{
+    error::Error,
+    fmt,
+    io::{self, Write}
+};
 
-/// The prefixes for the different types of annotations.
-static PREFIXES: [&str; 3] = ["EC:", "GO:", "IPR:IPR"];
+use super::{CharacterSet, Decode, DEFAULT_PREFIXES};
 
 /// Decodes a byte array into a string representation of annotations.
 ///
@@ -30,6 +33,21 @@ static PREFIXES: [&str; 3] = ["EC:", "GO:", "IPR:IPR"];
 /// assert_eq!(result, "EC:1.1.1.-;GO:0009279;IPR:IPR016364");
 /// ```
 pub fn decode(input: &[u8]) -> String {
+    decode_with_prefixes(input, &DEFAULT_PREFIXES)
+}
+
+/// Decodes a byte array into a string representation of annotations, using the given annotation
+/// types. The prefixes should be the same as the ones used to encode the byte array.
+///
+/// # Arguments
+///
+/// * `input` - The byte array to decode.
+/// * `prefixes` - The prefixes of the annotation types, in the order of the sections.
+///
+/// # Returns
+///
+/// A string representation of the decoded annotations.
+pub fn decode_with_prefixes(input: &[u8], prefixes: &[&str]) -> String {
     if input.is_empty() {
         return String::new();
     }
@@ -50,7 +68,7 @@ pub fn decode(input: &[u8]) -> String {
     //       Given the additional prefixes, we can safely triple the space. This might
     //       allocate more than necessary, but it's a simple and fast solution.
     let mut result = String::with_capacity(input.len() * 3);
-    for (annotations, prefix) in decoded.split(',').zip(PREFIXES).filter(|(s, _)| !s.is_empty()) {
+    for (annotations, prefix) in decoded.split(',').zip(prefixes).filter(|(s, _)| !s.is_empty()) {
         for annotation in annotations.split(';') {
             result.push_str(prefix);
             result.push_str(annotation);
@@ -64,9 +82,293 @@ pub fn decode(input: &[u8]) -> String {
     result
 }
 
+/// Decodes a byte array into a string representation of annotations, and writes the result to
+/// `writer` instead of building it in memory.
+///
+/// The written output is the same as the result of `decode`, but every annotation is written as
+/// soon as it is decoded, so decoding a large input does not require a large allocation. Every
+/// annotation results in a few small writes, so a buffered writer should be used.
+///
+/// # Arguments
+///
+/// * `input` - The byte array to decode.
+/// * `writer` - The writer to which the decoded annotations are written.
+///
+/// # Returns
+///
+/// An empty `Ok` if all the annotations were written.
+///
+/// # Errors
+///
+/// Returns the `io::Error` of the writer if writing the annotations failed.
+///
+/// # Examples
+///
+/// ```
+/// use fa_compression::algorithm1::decode_to_writer;
+///
+/// let input = &[ 44, 44, 44, 190, 17, 26, 56, 174, 18, 116, 117 ];
+/// let mut output = Vec::new();
+/// decode_to_writer(input, &mut output).unwrap();
+/// assert_eq!(output, b"EC:1.1.1.-;GO:0009279;IPR:IPR016364");
+/// ```
+pub fn decode_to_writer(input: &[u8], writer: &mut impl Write) -> io::Result<()> {
+    decode_to_writer_with_prefixes(input, &DEFAULT_PREFIXES, writer)
+}
+
+/// Decodes a byte array into a string representation of annotations using the given annotation
+/// types, and writes the result to `writer` instead of building it in memory.
+///
+/// # Arguments
+///
+/// * `input` - The byte array to decode.
+/// * `prefixes` - The prefixes of the annotation types, in the order of the sections.
+/// * `writer` - The writer to which the decoded annotations are written.
+///
+/// # Returns
+///
+/// An empty `Ok` if all the annotations were written.
+///
+/// # Errors
+///
+/// Returns the `io::Error` of the writer if writing the annotations failed.
+pub fn decode_to_writer_with_prefixes(input: &[u8], prefixes: &[&str], writer: &mut impl Write) -> io::Result<()> {
+    let mut section = 0;
+    let mut section_is_empty = true;
+    let mut is_first_annotation = true;
+
+    // Only the current annotation is kept in memory, since the annotations are written one by one
+    let mut annotation = String::new();
+    let mut write_annotation = |section: usize, annotation: &mut String| -> io::Result<()> {
+        // Sections without a prefix are not decoded, like the sections that `zip` drops in `decode`
+        if let Some(prefix) = prefixes.get(section) {
+            if !is_first_annotation {
+                writer.write_all(b";")?;
+            }
+            writer.write_all(prefix.as_bytes())?;
+            writer.write_all(annotation.as_bytes())?;
+            is_first_annotation = false;
+        }
+        annotation.clear();
+        Ok(())
+    };
+
+    for &byte in input {
+        let (c1, c2) = CharacterSet::decode_pair(byte);
+
+        for (character, is_low_nibble) in [(c1, false), (c2, true)] {
+            match character {
+                '$' if is_low_nibble => continue,
+                ',' => {
+                    if !section_is_empty {
+                        write_annotation(section, &mut annotation)?;
+                    }
+                    section += 1;
+                    section_is_empty = true;
+                }
+                ';' => {
+                    write_annotation(section, &mut annotation)?;
+                    section_is_empty = false;
+                }
+                _ => {
+                    annotation.push(character);
+                    section_is_empty = false;
+                }
+            }
+        }
+    }
+
+    if !section_is_empty {
+        write_annotation(section, &mut annotation)?;
+    }
+
+    Ok(())
+}
+
+/// Error returned by `try_decode` when the input is not a valid encoded byte array.
+#[derive(Debug, PartialEq, Eq)]
+pub enum DecodeError {
+    /// The empty placeholder character is only allowed as the second character of the last byte.
+    UnexpectedPadding {
+        /// The position of the offending byte in the input.
+        position: usize,
+        /// The offending byte.
+        byte: u8
+    },
+    /// The input contains more than three annotation sections (EC, GO and InterPro).
+    TooManySections {
+        /// The position of the offending byte in the input.
+        position: usize,
+        /// The offending byte.
+        byte: u8
+    },
+    /// The input contains an annotation without any characters.
+    EmptyAnnotation {
+        /// The position of the offending byte in the input.
+        position: usize,
+        /// The offending byte.
+        byte: u8
+    }
+}
+
+impl fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DecodeError::UnexpectedPadding { position, byte } => {
+                write!(f, "Unexpected padding in byte {} at position {}", byte, position)
+            }
+            DecodeError::TooManySections { position, byte } => {
+                write!(f, "Too many annotation sections in byte {} at position {}", byte, position)
+            }
+            DecodeError::EmptyAnnotation { position, byte } => {
+                write!(f, "Empty annotation in byte {} at position {}", byte, position)
+            }
+        }
+    }
+}
+
+impl Error for DecodeError {}
+
+/// Decodes a byte array into a string representation of annotations, after validating that the
+/// byte array has a valid structure.
+///
+/// In contrast to `decode`, malformed input results in an error instead of a nonsensical result.
+///
+/// # Arguments
+///
+/// * `input` - The byte array to decode.
+///
+/// # Returns
+///
+/// A string representation of the decoded annotations.
+///
+/// # Errors
+///
+/// Returns a `DecodeError` naming the offending byte and its position if the input contains
+/// padding in the wrong place, more than three annotation sections or an empty annotation.
+///
+/// # Examples
+///
+/// ```
+/// use fa_compression::algorithm1::try_decode;
+///
+/// let input = &[ 44, 44, 44, 190, 17, 26, 56, 174, 18, 116, 117 ];
+/// let result = try_decode(input).unwrap();
+/// assert_eq!(result, "EC:1.1.1.-;GO:0009279;IPR:IPR016364");
+///
+/// assert!(try_decode(&[ 255, 238 ]).is_err());
+/// ```
+pub fn try_decode(input: &[u8]) -> Result<String, DecodeError> {
+    try_decode_with_prefixes(input, &DEFAULT_PREFIXES)
+}
+
+/// Decodes a byte array into a string representation of annotations using the given annotation
+/// types, after validating that the byte array has a valid structure.
+///
+/// # Arguments
+///
+/// * `input` - The byte array to decode.
+/// * `prefixes` - The prefixes of the annotation types, in the order of the sections.
+///
+/// # Returns
+///
+/// A string representation of the decoded annotations.
+///
+/// # Errors
+///
+/// Returns a `DecodeError` naming the offending byte and its position if the input contains
+/// padding in the wrong place, more sections than prefixes or an empty annotation.
+pub fn try_decode_with_prefixes(input: &[u8], prefixes: &[&str]) -> Result<String, DecodeError> {
+    let mut section_count = 1;
+    let mut annotation_length = 0;
+    let mut last_character = ',';
+
+    for (position, &byte) in input.iter().enumerate() {
+        let (c1, c2) = CharacterSet::decode_pair(byte);
+        let is_last_byte = position == input.len() - 1;
+
+        for (character, is_low_nibble) in [(c1, false), (c2, true)] {
+            match character {
+                '$' if is_low_nibble && is_last_byte => continue,
+                '$' => return Err(DecodeError::UnexpectedPadding { position, byte }),
+                ',' => {
+                    if last_character == ';' {
+                        return Err(DecodeError::EmptyAnnotation { position, byte });
+                    }
+                    section_count += 1;
+                    if section_count > prefixes.len() {
+                        return Err(DecodeError::TooManySections { position, byte });
+                    }
+                    annotation_length = 0;
+                }
+                ';' => {
+                    if annotation_length == 0 {
+                        return Err(DecodeError::EmptyAnnotation { position, byte });
+                    }
+                    annotation_length = 0;
+                }
+                _ => annotation_length += 1
+            }
+            last_character = character;
+        }
+    }
+
+    if last_character == ';' {
+        let position = input.len() - 1;
+        return Err(DecodeError::EmptyAnnotation { position, byte: input[position] });
+    }
+
+    Ok(decode_with_prefixes(input, prefixes))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::algorithm1::encode_with_prefixes;
+
+    fn decode_to_string(input: &[u8], prefixes: &[&str]) -> String {
+        let mut output = Vec::new();
+        decode_to_writer_with_prefixes(input, prefixes, &mut output).unwrap();
+        String::from_utf8(output).unwrap()
+    }
+
+    #[test]
+    fn test_decode_to_writer() {
+        let inputs: [&[u8]; 8] = [
+            &[],
+            &[44, 44, 44, 190, 224],
+            &[225, 17, 163, 138, 224],
+            &[238, 18, 116, 117],
+            &[225, 17, 163, 138, 225, 39, 71, 95, 17, 153, 39],
+            &[44, 44, 44, 191, 44, 60, 44, 142, 225, 39, 71, 80],
+            &[44, 44, 44, 190, 17, 26, 56, 175, 17, 26, 56, 174],
+            &[44, 44, 44, 190, 17, 26, 56, 174, 18, 116, 117, 241, 67, 116, 111, 17, 153, 39]
+        ];
+
+        for input in inputs {
+            let mut output = Vec::new();
+            decode_to_writer(input, &mut output).unwrap();
+            assert_eq!(String::from_utf8(output).unwrap(), decode(input));
+        }
+    }
+
+    #[test]
+    fn test_decode_to_writer_with_prefixes() {
+        let prefixes = ["EC:", "GO:", "IPR:IPR", "KEGG:K"];
+        let encoded = encode_with_prefixes("KEGG:K00001;IPR:IPR016364;EC:1.1.1.-;KEGG:K12345", &prefixes);
+
+        assert_eq!(decode_to_string(&encoded, &prefixes), decode_with_prefixes(&encoded, &prefixes));
+        // the fourth section is dropped with the default prefixes
+        assert_eq!(decode_to_string(&encoded, &DEFAULT_PREFIXES), decode(&encoded));
+    }
+
+    #[test]
+    fn test_decode_to_writer_malformed() {
+        // malformed input is decoded in the same way as by `decode`
+        for input in [&[255, 238][..], &[208, 224][..], &[46, 17][..], &[227, 17][..]] {
+            assert_eq!(decode_to_string(input, &DEFAULT_PREFIXES), decode(input));
+        }
+    }
 
     #[test]
     fn test_decode_empty() {
@@ -110,4 +412,71 @@ mod tests {
             "EC:1.1.1.-;GO:0009279;IPR:IPR016364;IPR:IPR032635;IPR:IPR008816"
         )
     }
+
+    #[test]
+    fn test_try_decode() {
+        assert_eq!(try_decode(&[]), Ok(String::new()));
+        assert_eq!(try_decode(&[44, 44, 44, 190, 224]), Ok("EC:1.1.1.-".to_string()));
+        assert_eq!(
+            try_decode(&[44, 44, 44, 190, 17, 26, 56, 174, 18, 116, 117, 241, 67, 116, 111, 17, 153, 39]),
+            Ok("EC:1.1.1.-;GO:0009279;IPR:IPR016364;IPR:IPR032635;IPR:IPR008816".to_string())
+        );
+    }
+
+    #[test]
+    fn test_round_trip_four_prefixes() {
+        let prefixes = ["EC:", "GO:", "IPR:IPR", "KEGG:K"];
+        let input = "KEGG:K00001;IPR:IPR016364;EC:1.1.1.-;GO:0009279;KEGG:K12345;IPR:IPR008816";
+
+        let encoded = encode_with_prefixes(input, &prefixes);
+        assert_eq!(
+            decode_with_prefixes(&encoded, &prefixes),
+            "EC:1.1.1.-;GO:0009279;IPR:IPR016364;IPR:IPR008816;KEGG:K00001;KEGG:K12345"
+        );
+        assert_eq!(
+            try_decode_with_prefixes(&encoded, &prefixes),
+            Ok("EC:1.1.1.-;GO:0009279;IPR:IPR016364;IPR:IPR008816;KEGG:K00001;KEGG:K12345".to_string())
+        );
+
+        // the fourth section is not allowed with the default prefixes
+        assert!(matches!(try_decode(&encoded), Err(DecodeError::TooManySections { .. })));
+    }
+
+    #[test]
+    fn test_round_trip_four_prefixes_only_last() {
+        let prefixes = ["EC:", "GO:", "IPR:IPR", "KEGG:K"];
+        let encoded = encode_with_prefixes("KEGG:K00001", &prefixes);
+        assert_eq!(decode_with_prefixes(&encoded, &prefixes), "KEGG:K00001");
+    }
+
+    #[test]
+    fn test_try_decode_unexpected_padding() {
+        // padding as the first character of a byte
+        assert_eq!(try_decode(&[12]), Err(DecodeError::UnexpectedPadding { position: 0, byte: 12 }));
+        // padding in a byte that is not the last one
+        assert_eq!(try_decode(&[32, 238]), Err(DecodeError::UnexpectedPadding { position: 0, byte: 32 }));
+    }
+
+    #[test]
+    fn test_try_decode_too_many_sections() {
+        assert_eq!(try_decode(&[238, 226]), Err(DecodeError::TooManySections { position: 1, byte: 226 }));
+    }
+
+    #[test]
+    fn test_try_decode_empty_annotation() {
+        // a semicolon (nibble 15) at the start of a section
+        assert_eq!(try_decode(&[255, 238]), Err(DecodeError::EmptyAnnotation { position: 0, byte: 255 }));
+        // a semicolon at the end of a section
+        assert_eq!(try_decode(&[47, 238]), Err(DecodeError::EmptyAnnotation { position: 1, byte: 238 }));
+        // a semicolon at the end of the input
+        assert_eq!(try_decode(&[238, 47]), Err(DecodeError::EmptyAnnotation { position: 1, byte: 47 }));
+    }
+
+    #[test]
+    fn test_decode_error_display() {
+        assert_eq!(
+            DecodeError::EmptyAnnotation { position: 3, byte: 255 }.to_string(),
+            "Empty annotation in byte 255 at position 3"
+        );
+    }
 }
@@ -1,6 +1,6 @@
 //! This module contains the function to encode the input string into a compressed byte vector.
 
-use super::{CharacterSet, Encode};
+use super::{CharacterSet, Encode, DEFAULT_PREFIXES};
 
 /// Encodes the input string into a compressed byte vector.
 ///
@@ -28,6 +28,36 @@ use super::{CharacterSet, Encode};
 /// assert_eq!(encoded, vec![ 44, 44, 44, 190, 17, 26, 56, 174, 18, 116, 117 ]);
 /// ```
 pub fn encode(input: &str) -> Vec<u8> {
+    encode_with_prefixes(input, &DEFAULT_PREFIXES)
+}
+
+/// Encodes the input string into a compressed byte vector, using the given annotation types.
+///
+/// Every annotation is assigned to the first type of which it starts with the prefix, and annotations
+/// without a known prefix are skipped. The encoded byte vector contains one section per prefix, in
+/// the order of `prefixes`, so it should be decoded with the same prefixes. The part of the
+/// annotations after their prefix can only contain digits, `-`, `.` and `n`.
+///
+/// # Arguments
+///
+/// * `input` - The input string containing annotations.
+/// * `prefixes` - The prefixes of the annotation types.
+///
+/// # Returns
+///
+/// A compressed byte vector representing the encoded annotations.
+///
+/// # Examples
+///
+/// ```
+/// use fa_compression::algorithm1::{decode_with_prefixes, encode_with_prefixes};
+///
+/// let prefixes = ["EC:", "GO:", "IPR:IPR", "KEGG:K"];
+/// let encoded = encode_with_prefixes("KEGG:K00001;EC:1.1.1.-", &prefixes);
+///
+/// assert_eq!(decode_with_prefixes(&encoded, &prefixes), "EC:1.1.1.-;KEGG:K00001");
+/// ```
+pub fn encode_with_prefixes(input: &str, prefixes: &[&str]) -> Vec<u8> {
     if input.is_empty() {
         return Vec::new();
     }
@@ -38,20 +68,14 @@ pub fn encode(input: &str) -> Vec<u8> {
 
     // Create vectors to store the different types of annotations
     // Note: We assume an average of 12 characters per annotation
-    //       We also know that we have 3 types of annotations
-    //       So we can pre-allocate a vector with a capacity of input.len() / 12 / 3
-    let mut interpros: Vec<&str> = Vec::with_capacity(input.len() / 36);
-    let mut gos: Vec<&str> = Vec::with_capacity(input.len() / 36);
-    let mut ecs: Vec<&str> = Vec::with_capacity(input.len() / 36);
+    //       So we can pre-allocate a vector with a capacity of input.len() / 12 / prefixes.len()
+    let capacity = input.len() / 12 / prefixes.len().max(1);
+    let mut sections: Vec<Vec<&str>> = prefixes.iter().map(|_| Vec::with_capacity(capacity)).collect();
 
     // Read the input and split the annotations into the corresponding vectors
     for annotation in input.split(';') {
-        if annotation.starts_with("IPR") {
-            interpros.push(&annotation[7..]);
-        } else if annotation.starts_with("GO") {
-            gos.push(&annotation[3..]);
-        } else if annotation.starts_with("EC") {
-            ecs.push(&annotation[3..]);
+        if let Some(index) = prefixes.iter().position(|prefix| annotation.starts_with(prefix)) {
+            sections[index].push(&annotation[prefixes[index].len()..]);
         }
     }
 
@@ -59,13 +83,14 @@ pub fn encode(input: &str) -> Vec<u8> {
     // ==========================================================================================
     // ==========================================================================================
 
-    // Create a string without any unnecessary characters
+    // Create a string without any unnecessary characters, with a section for every prefix
     let mut result = String::with_capacity(input.len());
-    result.push_str(&ecs.join(";"));
-    result.push(',');
-    result.push_str(&gos.join(";"));
-    result.push(',');
-    result.push_str(&interpros.join(";"));
+    for (index, section) in sections.iter().enumerate() {
+        if index > 0 {
+            result.push(',');
+        }
+        result.push_str(&section.join(";"));
+    }
 
     // Take two characters at a time and encode them into a single byte
     let mut encoded: Vec<u8> = Vec::with_capacity(result.len() / 2);
@@ -123,6 +148,12 @@ mod tests {
         assert_eq!(encode("EC:1.1.1.-;GO:0009279;GO:0009279"), vec![44, 44, 44, 190, 17, 26, 56, 175, 17, 26, 56, 174])
     }
 
+    #[test]
+    fn test_encode_with_default_prefixes() {
+        let input = "IPR:IPR016364;EC:1.1.1.-;IPR:IPR032635;GO:0009279;IPR:IPR008816";
+        assert_eq!(encode_with_prefixes(input, &DEFAULT_PREFIXES), encode(input));
+    }
+
     #[test]
     fn test_encode_all() {
         assert_eq!(encode("IPR:IPR016364;EC:1.1.1.-;IPR:IPR032635;GO:0009279;IPR:IPR008816"), vec![
@@ -0,0 +1,87 @@
+//! This module contains the function to merge two encoded annotation byte vectors.
+
+use std::collections::HashSet;
+
+use super::{decode, encode};
+
+/// Merges two encoded annotation byte vectors into a single encoded byte vector.
+///
+/// Both inputs are decoded, after which the union of their annotations is encoded again. The
+/// annotations of `a` come before the new annotations of `b` within each annotation type, and
+/// duplicate annotations are only kept once.
+///
+/// # Arguments
+///
+/// * `a` - The first encoded byte vector.
+/// * `b` - The second encoded byte vector.
+///
+/// # Returns
+///
+/// A compressed byte vector representing the union of the annotations.
+///
+/// # Examples
+///
+/// ```
+/// use fa_compression::algorithm1::{decode, encode, merge};
+///
+/// let a = encode("EC:1.1.1.-;GO:0009279");
+/// let b = encode("GO:0009279;IPR:IPR016364");
+///
+/// assert_eq!(decode(&merge(&a, &b)), "EC:1.1.1.-;GO:0009279;IPR:IPR016364");
+/// ```
+pub fn merge(a: &[u8], b: &[u8]) -> Vec<u8> {
+    if a.is_empty() {
+        return b.to_vec();
+    }
+    if b.is_empty() {
+        return a.to_vec();
+    }
+
+    let decoded_a = decode(a);
+    let decoded_b = decode(b);
+
+    let mut seen = HashSet::new();
+    let annotations: Vec<&str> = decoded_a
+        .split(';')
+        .chain(decoded_b.split(';'))
+        .filter(|annotation| !annotation.is_empty() && seen.insert(*annotation))
+        .collect();
+
+    encode(&annotations.join(";"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_merge_empty() {
+        let a = encode("EC:1.1.1.-;GO:0009279");
+        assert_eq!(merge(&a, &[]), a);
+        assert_eq!(merge(&[], &a), a);
+        assert_eq!(merge(&[], &[]), Vec::<u8>::new());
+    }
+
+    #[test]
+    fn test_merge_disjoint() {
+        let a = encode("EC:1.1.1.-;IPR:IPR016364");
+        let b = encode("GO:0009279;EC:1.2.1.7");
+
+        assert_eq!(merge(&a, &b), encode("EC:1.1.1.-;EC:1.2.1.7;GO:0009279;IPR:IPR016364"));
+        assert_eq!(decode(&merge(&a, &b)), "EC:1.1.1.-;EC:1.2.1.7;GO:0009279;IPR:IPR016364");
+    }
+
+    #[test]
+    fn test_merge_overlapping() {
+        let a = encode("EC:1.1.1.-;GO:0009279;IPR:IPR016364");
+        let b = encode("GO:0009279;IPR:IPR008816;IPR:IPR016364");
+
+        assert_eq!(decode(&merge(&a, &b)), "EC:1.1.1.-;GO:0009279;IPR:IPR016364;IPR:IPR008816");
+    }
+
+    #[test]
+    fn test_merge_identical() {
+        let a = encode("EC:1.1.1.-;GO:0009279;IPR:IPR016364");
+        assert_eq!(merge(&a, &a), a);
+    }
+}
@@ -5,9 +5,18 @@ use std::ops::BitOr;
 
 mod decode;
 mod encode;
+mod merge;
 
-pub use decode::decode;
-pub use encode::encode;
+pub use decode::{
+    decode, decode_to_writer, decode_to_writer_with_prefixes, decode_with_prefixes, try_decode,
+    try_decode_with_prefixes, DecodeError
+};
+pub use encode::{encode, encode_with_prefixes};
+pub use merge::merge;
+
+/// The prefixes of the default annotation types: Enzyme Commission (EC), Gene Ontology (GO) and
+/// InterPro (IPR). The encoded annotations contain one section per prefix, in this order.
+pub static DEFAULT_PREFIXES: [&str; 3] = ["EC:", "GO:", "IPR:IPR"];
 
 /// Trait for encoding a value into a character set.
 trait Encode {
@@ -1,7 +1,12 @@
 //! This module contains the function to encode the input string into a compressed byte vector.
 
+use std::{error::Error, fmt};
+
 use super::CompressionTable;
 
+/// The maximum amount of entries in a compression table, since every index is encoded in 3 bytes.
+const MAX_ENTRIES: usize = 1 << 24;
+
 /// Encodes the input string using the provided compression table.
 ///
 /// # Arguments
@@ -41,9 +46,92 @@ pub fn encode(input: &str, compression_table: CompressionTable) -> Vec<u8> {
     encoded
 }
 
+/// Encodes the input string while growing the compression table, so that the annotations of a
+/// database can be encoded in a single pass without building the table up front.
+///
+/// Annotations that are not yet part of the compression table are added to it, and receive the next
+/// free index. As a consequence, the entries of the table are ordered by first appearance instead
+/// of by frequency. The grown table is needed to decode the encoded annotations afterwards.
+///
+/// # Arguments
+///
+/// * `input` - The input string to encode.
+/// * `compression_table` - The compression table used for encoding, which is extended with the new
+///   annotations in the input.
+///
+/// # Returns
+///
+/// A compressed byte vector representing the encoded annotations.
+///
+/// # Examples
+///
+/// ```
+/// use fa_compression::algorithm2::encode_adaptive;
+/// use fa_compression::algorithm2::CompressionTable;
+///
+/// let mut compression_table = CompressionTable::new();
+///
+/// let encoded = encode_adaptive("IPR:IPR000001;GO:0000001", &mut compression_table).unwrap();
+/// assert_eq!(encoded, vec![0, 0, 0, 1, 0, 0]);
+///
+/// let encoded = encode_adaptive("GO:0000001;EC:1.1.1.-", &mut compression_table).unwrap();
+/// assert_eq!(encoded, vec![1, 0, 0, 2, 0, 0]);
+/// ```
+///
+/// # Errors
+///
+/// Returns a `TableFullError` if a new annotation has to be added to a compression table that
+/// already contains 2^24 entries, since its index can no longer be encoded in 3 bytes.
+pub fn encode_adaptive(input: &str, compression_table: &mut CompressionTable) -> Result<Vec<u8>, TableFullError> {
+    encode_adaptive_with_limit(input, compression_table, MAX_ENTRIES)
+}
+
+/// Encodes the input string while growing the compression table up to `max_entries` entries.
+fn encode_adaptive_with_limit(
+    input: &str,
+    compression_table: &mut CompressionTable,
+    max_entries: usize
+) -> Result<Vec<u8>, TableFullError> {
+    if input.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let mut encoded: Vec<u8> = Vec::with_capacity(input.len() / 3);
+    for annotation in input.split(';') {
+        let index = match compression_table.index_of(annotation) {
+            Some(index) => index,
+            None if compression_table.entries.len() < max_entries => {
+                compression_table.add_entry(annotation.to_string());
+                compression_table.entries.len() - 1
+            }
+            None => return Err(TableFullError { annotation: annotation.to_string() })
+        };
+        encoded.extend_from_slice(&index.to_le_bytes()[0..3])
+    }
+
+    Ok(encoded)
+}
+
+/// Error returned by `encode_adaptive` when a new annotation no longer fits in the compression
+/// table.
+#[derive(Debug, PartialEq, Eq)]
+pub struct TableFullError {
+    /// The annotation that could not be added to the compression table.
+    pub annotation: String
+}
+
+impl fmt::Display for TableFullError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Compression table is full (2^24 entries), cannot add annotation {}", self.annotation)
+    }
+}
+
+impl Error for TableFullError {}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::algorithm2::decode;
 
     fn create_compresion_table() -> CompressionTable {
         let mut table = CompressionTable::new();
@@ -93,4 +181,39 @@ mod tests {
             0, 0, 0, 7, 0, 0, 2, 0, 0, 5, 0, 0
         ])
     }
+
+    #[test]
+    fn test_encode_adaptive_empty() {
+        let mut table = CompressionTable::new();
+        assert_eq!(encode_adaptive("", &mut table), Ok(vec![]));
+        assert_eq!(table.entries.len(), 0);
+    }
+
+    #[test]
+    fn test_encode_adaptive_round_trip() {
+        let records = ["IPR:IPR000001;GO:0000002", "GO:0000002;EC:1.1.1.-", "IPR:IPR000003;IPR:IPR000001"];
+
+        let mut table = CompressionTable::new();
+        let encoded: Vec<u8> = records.iter().flat_map(|record| encode_adaptive(record, &mut table).unwrap()).collect();
+
+        // every distinct annotation is added exactly once, in order of first appearance
+        assert_eq!(table.entries.len(), 4);
+        assert_eq!(table.index_of("EC:1.1.1.-"), Some(2));
+
+        assert_eq!(decode(&encoded, table), records.join(";"));
+    }
+
+    #[test]
+    fn test_encode_adaptive_table_full() {
+        let mut table = CompressionTable::new();
+
+        assert_eq!(encode_adaptive_with_limit("IPR:IPR000001;GO:0000002", &mut table, 2), Ok(vec![0, 0, 0, 1, 0, 0]));
+        // annotations that are already in the full table can still be encoded
+        assert_eq!(encode_adaptive_with_limit("GO:0000002", &mut table, 2), Ok(vec![1, 0, 0]));
+        assert_eq!(
+            encode_adaptive_with_limit("GO:0000002;EC:1.1.1.-", &mut table, 2),
+            Err(TableFullError { annotation: "EC:1.1.1.-".to_string() })
+        );
+        assert_eq!(table.entries.len(), 2);
+    }
 }
@@ -4,10 +4,10 @@
 mod decode;
 mod encode;
 
-use std::ops::Index;
+use std::{collections::HashMap, ops::Index};
 
 pub use decode::decode;
-pub use encode::encode;
+pub use encode::{encode, encode_adaptive, TableFullError};
 
 /// Represents an entry in the compression table.
 #[doc(hidden)]
@@ -18,7 +18,9 @@ pub struct CompressionTableEntry {
 /// Represents a compression table.
 pub struct CompressionTable {
     /// List of annotations in the compression table.
-    entries: Vec<CompressionTableEntry>
+    entries: Vec<CompressionTableEntry>,
+    /// Maps every annotation to its (first) index in the list of entries.
+    indices: HashMap<String, usize>
 }
 
 impl CompressionTable {
@@ -36,7 +38,7 @@ impl CompressionTable {
     /// let table = CompressionTable::new();
     /// ```
     pub fn new() -> CompressionTable {
-        CompressionTable { entries: Vec::new() }
+        CompressionTable { entries: Vec::new(), indices: HashMap::new() }
     }
 
     /// Adds a new entry to the compression table.
@@ -55,12 +57,13 @@ impl CompressionTable {
     /// table.add_entry("IPR:IPR000002".to_string());
     /// ```
     pub fn add_entry(&mut self, annotation: String) {
+        self.indices.entry(annotation.clone()).or_insert(self.entries.len());
         self.entries.push(CompressionTableEntry { annotation });
     }
 
     /// Returns the index of the given annotation in the compression table, if it exists.
     fn index_of(&self, annotation: &str) -> Option<usize> {
-        self.entries.iter().position(|entry| entry.annotation == annotation)
+        self.indices.get(annotation).copied()
     }
 }
 
@@ -127,6 +130,15 @@ mod tests {
         assert_eq!(table.index_of("EC:2.2.2.-"), None);
     }
 
+    #[test]
+    fn test_index_of_duplicate_entry() {
+        let mut table = create_compresion_table();
+        table.add_entry("IPR:IPR000002".to_string());
+
+        assert_eq!(table.entries.len(), 6);
+        assert_eq!(table.index_of("IPR:IPR000002"), Some(1));
+    }
+
     #[test]
     fn test_index() {
         let table = create_compresion_table();
@@ -0,0 +1,118 @@
+//! This module contains the error that is returned when a binary index file can not be loaded, so
+//! callers can distinguish a truncated file from a file in the wrong format.
+
+use std::{error::Error, fmt, io};
+
+use crate::FileKind;
+
+/// The errors that can occur while loading a binary index file.
+#[derive(Debug)]
+pub enum IndexError {
+    /// Reading a part of the file failed.
+    Io {
+        /// The part of the file that was being read.
+        part: &'static str,
+        /// The error returned by the reader.
+        source: io::Error
+    },
+    /// The file ended before a part of it could be read.
+    Truncated {
+        /// The part of the file that was being read.
+        part: &'static str
+    },
+    /// A part of the file contains a value that is not supported by this version.
+    Unsupported {
+        /// The part of the file that was being read.
+        part: &'static str
+    },
+    /// The file does not start with the magic bytes, so it is not a binary index file or it was
+    /// written before the header was introduced.
+    BadMagic {
+        /// The kind of file that was expected.
+        expected: FileKind
+    },
+    /// The header contains a file kind that is not known by this version.
+    UnsupportedKind {
+        /// The discriminant of the file kind in the header.
+        found: u8,
+        /// The kind of file that was expected.
+        expected: FileKind
+    },
+    /// The file is a binary index file of a different kind than expected.
+    WrongKind {
+        /// The kind of file in the header.
+        found: FileKind,
+        /// The kind of file that was expected.
+        expected: FileKind
+    }
+}
+
+impl IndexError {
+    /// Creates the error for a failed read of a part of the file. A read that failed because the
+    /// end of the file was reached is reported as `Truncated`.
+    ///
+    /// # Arguments
+    ///
+    /// * `part` - The part of the file that was being read.
+    /// * `source` - The error returned by the reader.
+    ///
+    /// # Returns
+    ///
+    /// The `IndexError` describing the failed read.
+    pub fn read(part: &'static str, source: io::Error) -> Self {
+        if source.kind() == io::ErrorKind::UnexpectedEof {
+            IndexError::Truncated { part }
+        } else {
+            IndexError::Io { part, source }
+        }
+    }
+}
+
+impl fmt::Display for IndexError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            IndexError::Io { part, source } => write!(f, "Could not read {} from the binary file: {}", part, source),
+            IndexError::Truncated { part } => {
+                write!(f, "Could not read {} from the binary file: the file is truncated", part)
+            }
+            IndexError::Unsupported { part } => {
+                write!(f, "Could not read {} from the binary file: the value is not supported", part)
+            }
+            IndexError::BadMagic { expected } => {
+                write!(f, "Unexpected file format: missing header, expected {}", expected)
+            }
+            IndexError::UnsupportedKind { found, expected } => {
+                write!(f, "Unexpected file format: found unknown kind {}, expected {}", found, expected)
+            }
+            IndexError::WrongKind { found, expected } => {
+                write!(f, "Unexpected file format: found {}, expected {}", found, expected)
+            }
+        }
+    }
+}
+
+impl Error for IndexError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            IndexError::Io { source, .. } => Some(source),
+            _ => None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_read_error() {
+        let err = IndexError::read("the sample rate", io::Error::from(io::ErrorKind::UnexpectedEof));
+        assert!(matches!(err, IndexError::Truncated { part: "the sample rate" }));
+        assert_eq!(err.to_string(), "Could not read the sample rate from the binary file: the file is truncated");
+
+        let err = IndexError::read("the sample rate", io::Error::other("Read failed"));
+        assert!(matches!(err, IndexError::Io { part: "the sample rate", .. }));
+        assert_eq!(err.to_string(), "Could not read the sample rate from the binary file: Read failed");
+        assert!(err.source().is_some());
+    }
+}
@@ -0,0 +1,258 @@
+//! This module contains the header that is written at the start of every binary index file, so a
+//! file of the wrong kind is rejected when it is loaded.
+
+use std::{
+    error::Error,
+    fmt,
+    io::{BufRead, Write}
+};
+
+use crate::IndexError;
+
+/// The magic bytes at the start of every binary index file.
+pub const MAGIC: [u8; 4] = *b"UPI1";
+
+/// The flag in the header of a suffix array that was built with every L in the text translated to
+/// an I. Files without a header are read with this flag set, since the translation could not be
+/// skipped before the header was introduced.
+pub const EQUATE_IL_FLAG: u8 = 0x01;
+
+/// The flag in the header of the proteins if the text does not use the default delimiters, which
+/// are then written after the header.
+pub const CUSTOM_DELIMITERS_FLAG: u8 = 0x02;
+
+/// All the flags that are known by this version.
+const KNOWN_FLAGS: u8 = EQUATE_IL_FLAG | CUSTOM_DELIMITERS_FLAG;
+
+/// The kinds of binary index files, written after the magic bytes.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FileKind {
+    /// A (compressed) suffix array.
+    SuffixArray = 1,
+    /// A compressed protein text.
+    Text = 2,
+    /// The proteins with their compressed text.
+    Proteins = 3
+}
+
+impl FileKind {
+    /// Converts a discriminant read from a file into a `FileKind`.
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - The discriminant of the file kind.
+    ///
+    /// # Returns
+    ///
+    /// The `FileKind` with the given discriminant, or `None` if the discriminant is unknown.
+    fn from_u8(value: u8) -> Option<FileKind> {
+        match value {
+            1 => Some(FileKind::SuffixArray),
+            2 => Some(FileKind::Text),
+            3 => Some(FileKind::Proteins),
+            _ => None
+        }
+    }
+}
+
+impl fmt::Display for FileKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FileKind::SuffixArray => write!(f, "suffix array"),
+            FileKind::Text => write!(f, "text"),
+            FileKind::Proteins => write!(f, "proteins")
+        }
+    }
+}
+
+/// Writes the header of a binary index file to a writer.
+///
+/// # Arguments
+///
+/// * `kind` - The kind of the file that is written.
+/// * `flags` - The flags of the file, such as `EQUATE_IL_FLAG` or `CUSTOM_DELIMITERS_FLAG`.
+/// * `writer` - The writer to which the header will be written.
+///
+/// # Errors
+///
+/// Returns an error if writing to the writer fails.
+pub fn write_header(kind: FileKind, flags: u8, writer: &mut impl Write) -> Result<(), Box<dyn Error>> {
+    writer.write_all(&MAGIC).map_err(|_| "Could not write the header to the writer")?;
+    writer.write_all(&[kind as u8, flags]).map_err(|_| "Could not write the header to the writer")?;
+
+    Ok(())
+}
+
+/// Reads the header of a binary index file from a reader and checks that the file has the expected
+/// kind.
+///
+/// # Arguments
+///
+/// * `reader` - The reader from which the header will be read.
+/// * `expected_kind` - The kind of file that the reader should contain.
+/// * `allow_legacy` - If files without a header, written before the header was introduced, are
+///   accepted. Nothing is read from such files.
+///
+/// # Returns
+///
+/// The flags in the header, or `EQUATE_IL_FLAG` for a file without a header.
+///
+/// # Errors
+///
+/// Returns an error if reading from the reader fails, if the header is missing and legacy files are
+/// not allowed, if the file has a different kind than expected, or if the header contains flags
+/// that are not known by this version.
+pub fn read_header(reader: &mut impl BufRead, expected_kind: FileKind, allow_legacy: bool) -> Result<u8, IndexError> {
+    let matched = consume_magic(reader).map_err(|err| IndexError::read("the header", err))?;
+
+    // Nothing is consumed from legacy files, since they do not start with the magic bytes
+    if matched == 0 && allow_legacy {
+        return Ok(EQUATE_IL_FLAG);
+    }
+    if matched < MAGIC.len() {
+        return Err(IndexError::BadMagic { expected: expected_kind });
+    }
+
+    let mut header_buffer = [0_u8; 2];
+    reader.read_exact(&mut header_buffer).map_err(|err| IndexError::read("the header", err))?;
+    let [kind, flags] = header_buffer;
+
+    match FileKind::from_u8(kind) {
+        Some(kind) if kind == expected_kind => {}
+        Some(kind) => return Err(IndexError::WrongKind { found: kind, expected: expected_kind }),
+        None => return Err(IndexError::UnsupportedKind { found: kind, expected: expected_kind })
+    }
+
+    if flags & !KNOWN_FLAGS != 0 {
+        return Err(IndexError::Unsupported { part: "the header flags" });
+    }
+
+    Ok(flags)
+}
+
+/// Consumes the magic bytes from the start of a reader. The magic bytes can be spread over multiple
+/// fills of the buffer of the reader, so they are matched piece by piece.
+///
+/// # Arguments
+///
+/// * `reader` - The reader from which the magic bytes will be consumed.
+///
+/// # Returns
+///
+/// The amount of magic bytes that were consumed. This is 0 if the buffered bytes at the start of the
+/// reader do not match the magic bytes, in which case nothing is consumed.
+///
+/// # Errors
+///
+/// Returns an error if reading from the reader fails.
+fn consume_magic(reader: &mut impl BufRead) -> std::io::Result<usize> {
+    let mut matched = 0;
+    while matched < MAGIC.len() {
+        let buffer = reader.fill_buf()?;
+        if buffer.is_empty() {
+            break;
+        }
+
+        let length = buffer.len().min(MAGIC.len() - matched);
+        if buffer[..length] != MAGIC[matched..matched + length] {
+            break;
+        }
+        reader.consume(length);
+        matched += length;
+    }
+
+    Ok(matched)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{BufReader, Read};
+
+    use super::*;
+
+    #[test]
+    fn test_write_header() {
+        let mut buffer = Vec::new();
+        write_header(FileKind::Text, 0, &mut buffer).unwrap();
+
+        assert_eq!(buffer, vec![b'U', b'P', b'I', b'1', 2, 0]);
+    }
+
+    #[test]
+    fn test_read_header() {
+        let mut buffer = Vec::new();
+        write_header(FileKind::SuffixArray, EQUATE_IL_FLAG, &mut buffer).unwrap();
+        buffer.push(64);
+
+        let mut reader = buffer.as_slice();
+        assert_eq!(read_header(&mut reader, FileKind::SuffixArray, false).unwrap(), EQUATE_IL_FLAG);
+
+        // only the header is consumed
+        assert_eq!(reader, &[64]);
+
+        let mut buffer = Vec::new();
+        write_header(FileKind::SuffixArray, 0, &mut buffer).unwrap();
+        assert_eq!(read_header(&mut buffer.as_slice(), FileKind::SuffixArray, false).unwrap(), 0);
+    }
+
+    #[test]
+    fn test_read_header_across_buffer_boundary() {
+        let mut buffer = vec![0_u8; 6];
+        write_header(FileKind::Text, 0, &mut buffer).unwrap();
+        buffer.push(64);
+
+        // the header starts at offset 6, so it is split over two fills of the buffer
+        for allow_legacy in [false, true] {
+            let mut reader = BufReader::with_capacity(8, buffer.as_slice());
+            reader.read_exact(&mut [0_u8; 6]).unwrap();
+
+            assert_eq!(read_header(&mut reader, FileKind::Text, allow_legacy).unwrap(), 0);
+
+            // only the header is consumed
+            let mut rest = Vec::new();
+            reader.read_to_end(&mut rest).unwrap();
+            assert_eq!(rest, vec![64]);
+        }
+    }
+
+    #[test]
+    fn test_read_header_unknown_flags() {
+        let buffer = vec![b'U', b'P', b'I', b'1', 1, 0x82];
+
+        let err = read_header(&mut buffer.as_slice(), FileKind::SuffixArray, false).unwrap_err();
+        assert!(matches!(err, IndexError::Unsupported { part: "the header flags" }));
+    }
+
+    #[test]
+    fn test_read_header_wrong_kind() {
+        let mut buffer = Vec::new();
+        write_header(FileKind::Text, 0, &mut buffer).unwrap();
+
+        let err = read_header(&mut buffer.as_slice(), FileKind::SuffixArray, true).unwrap_err();
+        assert!(matches!(err, IndexError::WrongKind { found: FileKind::Text, expected: FileKind::SuffixArray }));
+        assert_eq!(err.to_string(), "Unexpected file format: found text, expected suffix array");
+    }
+
+    #[test]
+    fn test_read_header_unknown_kind() {
+        let buffer = vec![b'U', b'P', b'I', b'1', 42, 0];
+
+        let err = read_header(&mut buffer.as_slice(), FileKind::Proteins, false).unwrap_err();
+        assert!(matches!(err, IndexError::UnsupportedKind { found: 42, expected: FileKind::Proteins }));
+        assert_eq!(err.to_string(), "Unexpected file format: found unknown kind 42, expected proteins");
+    }
+
+    #[test]
+    fn test_read_header_legacy() {
+        let buffer = vec![64, 1, 0, 0];
+
+        let err = read_header(&mut buffer.as_slice(), FileKind::SuffixArray, false).unwrap_err();
+        assert!(matches!(err, IndexError::BadMagic { expected: FileKind::SuffixArray }));
+        assert_eq!(err.to_string(), "Unexpected file format: missing header, expected suffix array");
+
+        // legacy files are accepted without consuming anything
+        let mut reader = buffer.as_slice();
+        assert_eq!(read_header(&mut reader, FileKind::SuffixArray, true).unwrap(), EQUATE_IL_FLAG);
+        assert_eq!(reader, &[64, 1, 0, 0]);
+    }
+}
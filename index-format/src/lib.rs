@@ -0,0 +1,10 @@
+//! This crate contains the format shared by the binary index files: the header that is written at
+//! the start of every file and the error that is returned when a file can not be loaded.
+
+mod error;
+mod header;
+
+/// Re-export the error returned when loading the binary index files.
+pub use error::IndexError;
+/// Re-export the header of the binary index files.
+pub use header::{read_header, write_header, FileKind, CUSTOM_DELIMITERS_FLAG, EQUATE_IL_FLAG, MAGIC};
@@ -57,11 +57,21 @@ fn main() -> Result<(), Box<dyn Error>> {
         .status()
         .expect("Failed to clone the libsais repository");
 
+    // the OpenMP support of libsais is only compiled and linked if the openmp feature is enabled
+    let openmp = env::var("CARGO_FEATURE_OPENMP").is_ok();
+
     // compile the c library
     Command::new("rm").args(["libsais/CMakeCache.txt"]).status().unwrap_or_default(); // if removing fails, it is since the cmake cache did not exist, we just can ignore it
     exit_status_to_result(
         "cmake",
-        Command::new("cmake").args(["-DCMAKE_BUILD_TYPE=\"Release\"", "libsais", "-Blibsais"]).status()?
+        Command::new("cmake")
+            .args([
+                "-DCMAKE_BUILD_TYPE=\"Release\"",
+                if openmp { "-DLIBSAIS_USE_OPENMP=ON" } else { "-DLIBSAIS_USE_OPENMP=OFF" },
+                "libsais",
+                "-Blibsais"
+            ])
+            .status()?
     )?;
     exit_status_to_result("make", Command::new("make").args(["-C", "libsais"]).status()?)?;
 
@@ -70,6 +80,12 @@ fn main() -> Result<(), Box<dyn Error>> {
     println!("cargo:rustc-link-search=native={}", Path::new(&dir).join("libsais").display());
     println!("cargo:rustc-link-lib=static=libsais");
 
+    // if libsais is built with OpenMP support, we also need to link the OpenMP runtime
+    if openmp {
+        let openmp_library = if env::var("CARGO_CFG_TARGET_OS")? == "macos" { "omp" } else { "gomp" };
+        println!("cargo:rustc-link-lib={}", openmp_library);
+    }
+
     // The bindgen::Builder is the main entry point
     // to bindgen, and lets you build up options for
     // the resulting bindings.
@@ -77,6 +93,8 @@ fn main() -> Result<(), Box<dyn Error>> {
         // The input header we would like to generate
         // bindings for.
         .header("libsais-wrapper.h")
+        // Only generate the bindings of the OpenMP functions if libsais is built with OpenMP support
+        .clang_args(if openmp { vec!["-DLIBSAIS_OPENMP"] } else { vec![] })
         // Tell cargo to invalidate the built crate whenever any of the
         // included header files changed.
         .parse_callbacks(Box::new(bindgen::CargoCallbacks::new()))
@@ -19,9 +19,110 @@ pub fn sais64(text: &[u8]) -> Option<Vec<i64>> {
     if exit_code == 0 { Some(sa) } else { None }
 }
 
+/// Builds the suffix array over the `text` using the libsais64 algorithm with multiple threads
+///
+/// # Arguments
+/// * `text` - The text used for suffix array construction
+/// * `threads` - The number of threads used during construction, the serial algorithm is used if
+///   this is at most 1
+///
+/// # Returns
+///
+/// Returns Some with the suffix array build over the text if construction succeeds
+/// Returns None if construction of the suffix array failed
+#[cfg(feature = "openmp")]
+pub fn sais64_parallel(text: &[u8], threads: usize) -> Option<Vec<i64>> {
+    if threads <= 1 {
+        return sais64(text);
+    }
+
+    let mut sa = vec![0; text.len()];
+    let exit_code = unsafe {
+        libsais64_omp(text.as_ptr(), sa.as_mut_ptr(), text.len() as i64, 0, std::ptr::null_mut(), threads as i64)
+    };
+    if exit_code == 0 { Some(sa) } else { None }
+}
+
+/// Builds the suffix array over the `text` using the serial libsais64 algorithm, since the library
+/// is compiled without OpenMP support
+///
+/// # Arguments
+/// * `text` - The text used for suffix array construction
+/// * `_threads` - The number of threads, which is ignored without the `openmp` feature
+///
+/// # Returns
+///
+/// Returns Some with the suffix array build over the text if construction succeeds
+/// Returns None if construction of the suffix array failed
+#[cfg(not(feature = "openmp"))]
+pub fn sais64_parallel(text: &[u8], _threads: usize) -> Option<Vec<i64>> {
+    sais64(text)
+}
+
+/// Builds the Burrows-Wheeler transform of the `text` using the libsais64 algorithm
+///
+/// # Arguments
+/// * `text` - The text used for the Burrows-Wheeler transform
+///
+/// # Returns
+///
+/// Returns Some with the transformed text and the primary index if the transform succeeds. The
+/// transformed text does not contain the implicit sentinel, whose position is given by the primary
+/// index.
+/// Returns None if the transform failed
+pub fn bwt64(text: &[u8]) -> Option<(Vec<u8>, usize)> {
+    let mut bwt = vec![0; text.len()];
+    let mut temporary_array = vec![0; text.len()];
+    let primary_index = unsafe {
+        libsais64_bwt(
+            text.as_ptr(),
+            bwt.as_mut_ptr(),
+            temporary_array.as_mut_ptr(),
+            text.len() as i64,
+            0,
+            std::ptr::null_mut()
+        )
+    };
+    if primary_index >= 0 {
+        Some((bwt, primary_index as usize))
+    } else {
+        None
+    }
+}
+
+/// Builds the longest common prefix array of the `text` using the libsais64 algorithm
+///
+/// # Arguments
+/// * `text` - The text over which the suffix array is built
+/// * `sa` - The (not sparse) suffix array of the text
+///
+/// # Returns
+///
+/// Returns Some with the longest common prefix array if construction succeeds. The value at index
+/// `i` is the length of the longest common prefix of the suffixes at index `i - 1` and `i` in the
+/// suffix array.
+/// Returns None if the suffix array is sparse or does not belong to the text, or if construction of
+/// the longest common prefix array failed
+pub fn lcp64(text: &[u8], sa: &[i64]) -> Option<Vec<i64>> {
+    // libsais reads `text.len()` values from the suffix array and uses them as indices in the text
+    if sa.len() != text.len() || sa.iter().any(|&suffix| suffix < 0 || suffix as usize >= text.len()) {
+        return None;
+    }
+
+    let mut plcp = vec![0; text.len()];
+    let exit_code = unsafe { libsais64_plcp(text.as_ptr(), sa.as_ptr(), plcp.as_mut_ptr(), text.len() as i64) };
+    if exit_code != 0 {
+        return None;
+    }
+
+    let mut lcp = vec![0; text.len()];
+    let exit_code = unsafe { libsais64_lcp(plcp.as_ptr(), sa.as_ptr(), lcp.as_mut_ptr(), text.len() as i64) };
+    if exit_code == 0 { Some(lcp) } else { None }
+}
+
 #[cfg(test)]
 mod tests {
-    use crate::sais64;
+    use crate::{bwt64, lcp64, sais64, sais64_parallel};
 
     #[test]
     fn check_build_sa_with_libsais64() {
@@ -29,4 +130,37 @@ mod tests {
         let sa = sais64(text.as_bytes());
         assert_eq!(sa, Some(vec![6, 5, 3, 1, 0, 4, 2]));
     }
+
+    #[test]
+    fn check_build_sa_with_libsais64_parallel() {
+        let text = "abracadabra-banana$";
+        assert_eq!(sais64_parallel(text.as_bytes(), 4), sais64(text.as_bytes()));
+        assert_eq!(sais64_parallel(text.as_bytes(), 1), sais64(text.as_bytes()));
+    }
+
+    #[test]
+    fn check_build_bwt_with_libsais64() {
+        let text = "banana$";
+        let bwt = bwt64(text.as_bytes());
+        assert_eq!(bwt, Some((b"$annbaa".to_vec(), 5)));
+    }
+
+    #[test]
+    fn check_build_lcp_with_libsais64() {
+        let text = "banana$";
+        let sa = sais64(text.as_bytes()).unwrap();
+        let lcp = lcp64(text.as_bytes(), &sa);
+        assert_eq!(lcp, Some(vec![0, 0, 1, 3, 0, 0, 2]));
+    }
+
+    #[test]
+    fn check_build_lcp_with_libsais64_invalid_sa() {
+        let text = "banana$";
+        let sa = sais64(text.as_bytes()).unwrap();
+
+        // a sparse suffix array is shorter than the text
+        assert_eq!(lcp64(text.as_bytes(), &sa[..3]), None);
+        assert_eq!(lcp64(text.as_bytes(), &[6, 5, 3, 1, 0, 4, 7]), None);
+        assert_eq!(lcp64(text.as_bytes(), &[6, 5, 3, 1, 0, 4, -1]), None);
+    }
 }
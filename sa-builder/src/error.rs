@@ -0,0 +1,28 @@
+//! This module contains the error that is returned when the suffix array can not be built from the
+//! given text.
+
+use std::{error::Error, fmt};
+
+/// The errors that can occur while building the suffix array.
+#[derive(Debug, PartialEq)]
+pub enum BuildError {
+    /// The text contains a character that is not part of the protein alphabet.
+    InvalidCharacter {
+        /// The position of the first invalid character in the text.
+        position: usize,
+        /// The invalid character.
+        byte: u8
+    }
+}
+
+impl fmt::Display for BuildError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BuildError::InvalidCharacter { position, byte } => {
+                write!(f, "Invalid character '{}' at position {} in the text", *byte as char, position)
+            }
+        }
+    }
+}
+
+impl Error for BuildError {}
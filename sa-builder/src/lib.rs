@@ -1,6 +1,12 @@
 use std::error::Error;
 
-use clap::{Parser, ValueEnum};
+use clap::{ArgAction, Parser, ValueEnum};
+use sa_mappings::proteins::{AnnotationFormat, Delimiters, DuplicateAccessions, Protein, Proteins};
+use text_compression::{ProteinText, PROTEIN_ALPHABET};
+
+mod error;
+
+pub use error::BuildError;
 
 /// Build a (sparse, compressed) suffix array from the given text
 #[derive(Parser, Debug)]
@@ -16,19 +22,86 @@ pub struct Arguments {
     /// the SA is used)
     #[arg(short, long, default_value_t = 1)]
     pub sparseness_factor: u8,
-    /// The algorithm used to construct the suffix array (default value LibSais)
-    #[arg(short('a'), long, value_enum, default_value_t = SAConstructionAlgorithm::LibSais)]
+    /// The algorithm used to construct the suffix array (default value LibSais, or Native if the
+    /// builder is compiled without the libsais feature)
+    #[arg(short('a'), long, value_enum, default_value_t = SAConstructionAlgorithm::default())]
     pub construction_algorithm: SAConstructionAlgorithm,
     /// If the suffix array should be compressed (default value true)
     #[arg(short, long, default_value_t = false)]
-    pub compress_sa: bool
+    pub compress_sa: bool,
+    /// The number of threads used to construct the suffix array with LibSais (default value 1), only
+    /// used if the builder is compiled with `--features openmp`
+    #[arg(short, long, default_value_t = 1)]
+    pub threads: usize,
+    /// Optional output location where to store the LCP array, can only be used if the
+    /// sparseness_factor is 1 and if the builder is compiled with the libsais feature
+    #[arg(long)]
+    pub write_lcp: Option<String>,
+    /// Optional output location where to store the proteins in a binary format, which can be loaded
+    /// by the server instead of the database file
+    #[arg(long)]
+    pub write_proteins: Option<String>,
+    /// If the sequences in the database file are already uppercase, which avoids converting them
+    /// while loading the proteins
+    #[arg(long, default_value_t = false)]
+    pub assume_uppercase: bool,
+    /// What to do with proteins of which the accession already occurred in the database file: keep,
+    /// skip or fail (default value keep). The server should load the database with the same value.
+    #[arg(long, default_value = "keep")]
+    pub duplicate_accessions: DuplicateAccessions,
+    /// How the functional annotations are stored in the database file: text, or encoded if the
+    /// annotation columns already contain the encoded bytes (default value text). Only used for the
+    /// proteins written with `--write-proteins`.
+    #[arg(long, default_value = "text")]
+    pub annotation_format: AnnotationFormat,
+    /// If the L's in the text should be translated to I's before building the suffix array (default
+    /// value true). The choice is stored in the header of the suffix array, and a suffix array built
+    /// without the translation can only be searched with I and L distinguished.
+    #[arg(long, default_value_t = true, action = ArgAction::Set)]
+    pub equate_il: bool,
+    /// The format in which the suffix array is written (default value binary). The text format
+    /// writes one value per line and is only meant for debugging, it can not be loaded again.
+    #[arg(long, value_enum, default_value_t = OutputFormat::Binary)]
+    pub format: OutputFormat,
+    /// The amount of characters of every suffix that is written after its value when the text
+    /// format is used (default value 0)
+    #[arg(long, default_value_t = 0)]
+    pub text_prefix_length: usize,
+    /// Optional zstd compression level, used to further compress the compressed suffix array with
+    /// zstd. Can only be used together with `--compress-sa`.
+    #[arg(long, requires = "compress_sa")]
+    pub zstd_level: Option<i32>
+}
+
+/// Enum representing the possible formats in which the suffix array is written
+#[derive(ValueEnum, Clone, Debug, PartialEq)]
+pub enum OutputFormat {
+    Binary,
+    Text
 }
 
-/// Enum representing the two possible algorithms to construct the suffix array
+/// Enum representing the possible algorithms to construct the suffix array
+/// The native algorithm does not use the C libraries, but is slower than the other algorithms. The
+/// other algorithms are only available if the builder is compiled with their feature.
 #[derive(ValueEnum, Clone, Debug, PartialEq)]
 pub enum SAConstructionAlgorithm {
+    #[cfg(feature = "libdivsufsort")]
     LibDivSufSort,
-    LibSais
+    #[cfg(feature = "libsais")]
+    LibSais,
+    Native
+}
+
+impl Default for SAConstructionAlgorithm {
+    #[cfg(feature = "libsais")]
+    fn default() -> Self {
+        SAConstructionAlgorithm::LibSais
+    }
+
+    #[cfg(not(feature = "libsais"))]
+    fn default() -> Self {
+        SAConstructionAlgorithm::Native
+    }
 }
 
 /// Build a sparse suffix array from the given text
@@ -37,6 +110,9 @@ pub enum SAConstructionAlgorithm {
 /// * `text` - The text on which we want to build the suffix array
 /// * `construction_algorithm` - The algorithm used during construction
 /// * `sparseness_factor` - The sparseness factor used on the suffix array
+/// * `threads` - The number of threads used during construction, only used by LibSais
+/// * `equate_il` - True if the L's in the text should be translated to I's before construction.
+///   This must match the I/L handling of the searcher that uses the suffix array.
 ///
 /// # Returns
 ///
@@ -44,19 +120,30 @@ pub enum SAConstructionAlgorithm {
 ///
 /// # Errors
 ///
-/// The errors that occurred during the building of the suffix array itself
+/// Returns an error if the text contains invalid characters, or if building the suffix array
+/// itself failed
+#[cfg_attr(not(feature = "libsais"), allow(unused_variables))]
 pub fn build_ssa(
     text: &mut Vec<u8>,
     construction_algorithm: &SAConstructionAlgorithm,
-    sparseness_factor: u8
+    sparseness_factor: u8,
+    threads: usize,
+    equate_il: bool
 ) -> Result<Vec<i64>, Box<dyn Error>> {
-    // translate all L's to a I
-    translate_l_to_i(text);
+    validate_text(text)?;
+
+    // translate all L's to a I, unless I and L should be distinguished
+    if equate_il {
+        translate_l_to_i(text);
+    }
 
     // Build the suffix array using the selected algorithm
     let mut sa = match construction_algorithm {
-        SAConstructionAlgorithm::LibSais => libsais64_rs::sais64(text),
-        SAConstructionAlgorithm::LibDivSufSort => libdivsufsort_rs::divsufsort64(text)
+        #[cfg(feature = "libsais")]
+        SAConstructionAlgorithm::LibSais => libsais64_rs::sais64_parallel(text, threads),
+        #[cfg(feature = "libdivsufsort")]
+        SAConstructionAlgorithm::LibDivSufSort => libdivsufsort_rs::divsufsort64(text),
+        SAConstructionAlgorithm::Native => Some(native_suffix_array(text))
     }
     .ok_or("Building suffix array failed")?;
 
@@ -66,6 +153,105 @@ pub fn build_ssa(
     Ok(sa)
 }
 
+/// Build the Burrows-Wheeler transform of the given text
+/// The L's in the text are translated to I's, in the same way as for the suffix array
+///
+/// # Arguments
+/// * `text` - The text of which we want to build the Burrows-Wheeler transform
+///
+/// # Returns
+///
+/// Returns the transformed text and the primary index, which is the position of the implicit
+/// sentinel that is not part of the transformed text
+///
+/// # Errors
+///
+/// Returns an error if the text contains invalid characters, or if building the transform itself
+/// failed
+#[cfg(feature = "libsais")]
+pub fn build_bwt(text: &mut [u8]) -> Result<(Vec<u8>, usize), Box<dyn Error>> {
+    validate_text(text)?;
+
+    // translate all L's to a I
+    translate_l_to_i(text);
+
+    let bwt = libsais64_rs::bwt64(text).ok_or("Building the Burrows-Wheeler transform failed")?;
+
+    Ok(bwt)
+}
+
+/// Build the longest common prefix array from the given text and its suffix array
+///
+/// # Arguments
+/// * `text` - The text over which the suffix array is built, with all L's translated to I's
+/// * `sa` - The suffix array of the text, which can not be sparse
+///
+/// # Returns
+///
+/// Returns the longest common prefix array, where the value at index `i` is the length of the
+/// longest common prefix of the suffixes at index `i - 1` and `i` in the suffix array
+///
+/// # Errors
+///
+/// Returns an error if the suffix array is sparse, or if building the array itself failed
+#[cfg(feature = "libsais")]
+pub fn build_lcp(text: &[u8], sa: &[i64]) -> Result<Vec<i64>, Box<dyn Error>> {
+    if sa.len() != text.len() {
+        return Err("The LCP array can only be built from a suffix array that is not sparse".into());
+    }
+
+    let lcp = libsais64_rs::lcp64(text, sa).ok_or("Building the LCP array failed")?;
+
+    Ok(lcp)
+}
+
+/// Build the proteins that are written with `--write-proteins` from the text over which the suffix
+/// array is built. The text must be compressed before `build_ssa` translates its L's to I's.
+///
+/// # Arguments
+/// * `text` - The text on which we want to build the suffix array
+/// * `proteins` - The proteins in the text, in the same order
+///
+/// # Returns
+///
+/// Returns the proteins with the compressed text
+///
+/// # Errors
+///
+/// Returns a `BuildError::InvalidCharacter` if the text contains invalid characters
+pub fn build_proteins(text: &[u8], proteins: Vec<Protein>) -> Result<Proteins, BuildError> {
+    validate_text(text)?;
+
+    Ok(Proteins {
+        text: ProteinText::from_vec(text),
+        proteins,
+        accession_index: None,
+        original_sequences: None,
+        delimiters: Delimiters::default(),
+        skipped_accessions: Vec::new()
+    })
+}
+
+/// Checks that the text only contains characters of the protein alphabet, which includes the
+/// separation characters (`-`) and termination characters (`$`)
+///
+/// # Arguments
+/// * `text` - The text on which we want to build the suffix array
+///
+/// # Returns
+///
+/// Returns () if the text only contains valid characters
+///
+/// # Errors
+///
+/// Returns a `BuildError::InvalidCharacter` with the position of the first invalid character
+pub fn validate_text(text: &[u8]) -> Result<(), BuildError> {
+    match text.iter().position(|c| !PROTEIN_ALPHABET.contains(c)) {
+        Some(position) => Err(BuildError::InvalidCharacter { position, byte: text[position] }),
+        None => Ok(())
+    }
+}
+
 /// Translate all L's to I's in the given text
 ///
 /// # Arguments
@@ -82,6 +268,49 @@ fn translate_l_to_i(text: &mut [u8]) {
     }
 }
 
+/// Build the suffix array of the given text using prefix doubling, without relying on a C library
+/// In every round, the suffixes are sorted on their first `2k` characters by using the ranks of
+/// their first `k` characters, until all the ranks are unique.
+///
+/// # Arguments
+/// * `text` - The text on which we want to build the suffix array
+///
+/// # Returns
+///
+/// Returns the suffix array of the text
+fn native_suffix_array(text: &[u8]) -> Vec<i64> {
+    let n = text.len();
+    if n == 0 {
+        return vec![];
+    }
+
+    let mut sa: Vec<usize> = (0..n).collect();
+    let mut rank: Vec<usize> = text.iter().map(|&c| c as usize).collect();
+    let mut new_rank = vec![0; n];
+
+    let mut k = 1;
+    loop {
+        // a suffix that ends within the first 2k characters is smaller than the suffixes it is a
+        // prefix of, so it gets the smallest second rank
+        let key = |i: usize| (rank[i], if i + k < n { rank[i + k] + 1 } else { 0 });
+        sa.sort_unstable_by_key(|&i| key(i));
+
+        new_rank[sa[0]] = 0;
+        for j in 1..n {
+            new_rank[sa[j]] = new_rank[sa[j - 1]] + usize::from(key(sa[j - 1]) != key(sa[j]));
+        }
+        std::mem::swap(&mut rank, &mut new_rank);
+
+        // stop when all the suffixes have a different rank
+        if rank[sa[n - 1]] == n - 1 {
+            break;
+        }
+        k *= 2;
+    }
+
+    sa.into_iter().map(|i| i as i64).collect()
+}
+
 /// Sample the suffix array with the given sparseness factor
 ///
 /// # Arguments
@@ -111,9 +340,14 @@ fn sample_sa(sa: &mut Vec<i64>, sparseness_factor: u8) {
 
 #[cfg(test)]
 mod tests {
+    use sa_index::{peptide_search::search_peptide, sa_searcher::SparseSearcher, SuffixArray};
+    use sa_mappings::proteins::{dump_proteins, load_proteins};
+    use tempdir::TempDir;
+
     use super::*;
 
     #[test]
+    #[cfg(feature = "libdivsufsort")]
     fn test_arguments() {
         let args = Arguments::parse_from(&[
             "sa-builder",
@@ -125,7 +359,14 @@ mod tests {
             "2",
             "--construction-algorithm",
             "lib-div-suf-sort",
-            "--compress-sa"
+            "--compress-sa",
+            "--threads",
+            "4",
+            "--assume-uppercase",
+            "--duplicate-accessions",
+            "skip",
+            "--equate-il",
+            "false"
         ]);
 
         assert_eq!(args.database_file, "database.fa");
@@ -133,59 +374,332 @@ mod tests {
         assert_eq!(args.sparseness_factor, 2);
         assert_eq!(args.construction_algorithm, SAConstructionAlgorithm::LibDivSufSort);
         assert_eq!(args.compress_sa, true);
+        assert_eq!(args.threads, 4);
+        assert!(args.assume_uppercase);
+        assert_eq!(args.duplicate_accessions, DuplicateAccessions::Skip);
+        assert!(!args.equate_il);
+    }
+
+    #[test]
+    fn test_arguments_annotation_format() {
+        let args = Arguments::parse_from(["sa-builder", "--database-file", "database.fa", "--output", "output.fa"]);
+        assert_eq!(args.annotation_format, AnnotationFormat::Text);
+
+        let args = Arguments::parse_from([
+            "sa-builder",
+            "--database-file",
+            "database.fa",
+            "--output",
+            "output.fa",
+            "--annotation-format",
+            "encoded"
+        ]);
+        assert_eq!(args.annotation_format, AnnotationFormat::Encoded);
+    }
+
+    #[test]
+    fn test_arguments_equate_il_default() {
+        let args = Arguments::parse_from(["sa-builder", "--database-file", "database.fa", "--output", "output.fa"]);
+        assert!(args.equate_il);
+    }
+
+    #[test]
+    fn test_arguments_format() {
+        let args = Arguments::parse_from(["sa-builder", "--database-file", "database.fa", "--output", "output.fa"]);
+        assert_eq!(args.format, OutputFormat::Binary);
+        assert_eq!(args.text_prefix_length, 0);
+
+        let args = Arguments::parse_from([
+            "sa-builder",
+            "--database-file",
+            "database.fa",
+            "--output",
+            "output.txt",
+            "--format",
+            "text",
+            "--text-prefix-length",
+            "10"
+        ]);
+        assert_eq!(args.format, OutputFormat::Text);
+        assert_eq!(args.text_prefix_length, 10);
+    }
+
+    #[test]
+    fn test_arguments_zstd_level() {
+        let args = Arguments::parse_from(["sa-builder", "--database-file", "database.fa", "--output", "output.fa"]);
+        assert_eq!(args.zstd_level, None);
+
+        let args = Arguments::parse_from([
+            "sa-builder",
+            "--database-file",
+            "database.fa",
+            "--output",
+            "output.fa",
+            "--compress-sa",
+            "--zstd-level",
+            "19"
+        ]);
+        assert_eq!(args.zstd_level, Some(19));
+
+        // zstd compression requires a compressed suffix array
+        assert!(Arguments::try_parse_from([
+            "sa-builder",
+            "--database-file",
+            "database.fa",
+            "--output",
+            "output.fa",
+            "--zstd-level",
+            "19"
+        ])
+        .is_err());
     }
 
     #[test]
     fn test_sa_construction_algorithm() {
+        #[cfg(feature = "libdivsufsort")]
         assert_eq!(
             SAConstructionAlgorithm::from_str("lib-div-suf-sort", false),
             Ok(SAConstructionAlgorithm::LibDivSufSort)
         );
+        #[cfg(feature = "libsais")]
         assert_eq!(SAConstructionAlgorithm::from_str("lib-sais", false), Ok(SAConstructionAlgorithm::LibSais));
+        assert_eq!(SAConstructionAlgorithm::from_str("native", false), Ok(SAConstructionAlgorithm::Native));
+    }
+
+    #[test]
+    fn test_validate_text() {
+        assert!(validate_text(b"ABRACADABRA-BANANA$").is_ok());
+        assert!(validate_text(b"").is_ok());
+    }
+
+    #[test]
+    fn test_validate_text_digit() {
+        let err = validate_text(b"ABRA1CADABRA$").unwrap_err();
+        assert_eq!(err.to_string(), "Invalid character '1' at position 4 in the text");
+    }
+
+    #[test]
+    fn test_validate_text_lowercase() {
+        let err = validate_text(b"ABRACADaBRA$").unwrap_err();
+        assert_eq!(err.to_string(), "Invalid character 'a' at position 7 in the text");
+    }
+
+    #[test]
+    fn test_validate_text_not_in_alphabet() {
+        // J is an uppercase letter, but it is not part of the protein alphabet
+        assert_eq!(validate_text(b"ABRAJCADABRA$"), Err(BuildError::InvalidCharacter { position: 4, byte: b'J' }));
+    }
+
+    #[test]
+    fn test_build_proteins() {
+        let protein = Protein { uniprot_id: "P12345".to_string(), taxon_id: 1, functional_annotations: vec![] };
+        let proteins = build_proteins(b"IALA-AC$", vec![protein]).unwrap();
+        assert_eq!(proteins.text.decode_range(0, proteins.text.len()), b"IALA-AC$".to_vec());
+        assert_eq!(proteins.proteins.len(), 1);
+
+        assert!(build_proteins(b"IAJA$", vec![]).is_err());
+    }
+
+    #[test]
+    fn test_build_proteins_search_matches_database_file() {
+        // Create a temporary directory for this test
+        let tmp_dir = TempDir::new("test_build_proteins_search").unwrap();
+
+        let database_file = tmp_dir.path().join("database.tsv");
+        std::fs::write(
+            &database_file,
+            "P12345\t1\tMLPGLALLLLAAWTARALEV\tGO:0009279\nP54321\t2\tPTDGNAGLIAEPQIAMFCGRLNMHMNVQNG\tIPR:IPR016364\n"
+        )
+        .unwrap();
+        let database_file = database_file.to_str().unwrap();
+
+        // the proteins as they are written with `--write-proteins` and loaded by the server
+        let (mut text, proteins, _) =
+            Proteins::try_from_database_file_uncompressed_with_proteins(
+                database_file,
+                true,
+                &DuplicateAccessions::Keep,
+                &AnnotationFormat::Text
+            )
+            .unwrap();
+        let mut buffer = Vec::new();
+        dump_proteins(&build_proteins(&text, proteins).unwrap(), &mut buffer).unwrap();
+        let binary_proteins = load_proteins(&mut buffer.as_slice(), false).unwrap();
+
+        let sa = build_ssa(&mut text, &SAConstructionAlgorithm::Native, 1, 1, true).unwrap();
+        let binary_searcher = SparseSearcher::new(SuffixArray::Original(sa.clone(), 1), binary_proteins);
+        let database_searcher =
+            SparseSearcher::new(SuffixArray::Original(sa, 1), Proteins::try_from_database_file(database_file).unwrap());
+
+        for peptide in ["LALL", "GLIA", "IAM", "MHMNVQNG"] {
+            for equate_il in [true, false] {
+                let binary_result = search_peptide(&binary_searcher, peptide, 10_000, equate_il, false);
+                let database_result = search_peptide(&database_searcher, peptide, 10_000, equate_il, false);
+                assert_eq!(format!("{:?}", binary_result), format!("{:?}", database_result));
+            }
+        }
+
+        // the translated L's are not written to the binary proteins
+        let result = search_peptide(&binary_searcher, "LALL", 10_000, false, false).unwrap();
+        assert_eq!(result.proteins.len(), 1);
+        assert!(search_peptide(&binary_searcher, "IAII", 10_000, false, false).is_none());
+    }
+
+    #[test]
+    fn test_build_ssa_invalid_text() {
+        let mut text = b"ABRACADaBRA$".to_vec();
+        assert!(build_ssa(&mut text, &SAConstructionAlgorithm::Native, 1, 1, true).is_err());
+    }
+
+    #[test]
+    #[cfg(feature = "libsais")]
+    fn test_build_bwt() {
+        let mut text = b"BANANA$".to_vec();
+        let (bwt, primary_index) = build_bwt(&mut text).unwrap();
+        assert_eq!(bwt, b"$ANNBAA".to_vec());
+        assert_eq!(primary_index, 5);
+    }
+
+    #[test]
+    #[cfg(feature = "libsais")]
+    fn test_build_bwt_translates_l() {
+        let mut text = b"BALALA$".to_vec();
+        let (bwt, _) = build_bwt(&mut text).unwrap();
+        assert_eq!(bwt, b"$AIIBAA".to_vec());
+    }
+
+    #[test]
+    #[cfg(feature = "libsais")]
+    fn test_build_lcp() {
+        let mut text = b"ABRACADABRA-BANANA$".to_vec();
+        let sa = build_ssa(&mut text, &SAConstructionAlgorithm::LibSais, 1, 1, true).unwrap();
+        let lcp = build_lcp(&text, &sa).unwrap();
+
+        // compare with a brute-force computation
+        let mut expected = vec![0];
+        for i in 1..sa.len() {
+            let previous_suffix = &text[sa[i - 1] as usize..];
+            let suffix = &text[sa[i] as usize..];
+            expected.push(previous_suffix.iter().zip(suffix).take_while(|(a, b)| a == b).count() as i64);
+        }
+        assert_eq!(lcp, expected);
     }
 
     #[test]
+    #[cfg(feature = "libsais")]
+    fn test_build_lcp_sparse() {
+        let mut text = b"ABRACADABRA$".to_vec();
+        let sa = build_ssa(&mut text, &SAConstructionAlgorithm::LibSais, 2, 1, true).unwrap();
+        assert!(build_lcp(&text, &sa).is_err());
+    }
+
+    #[test]
+    #[cfg(feature = "libsais")]
     fn test_build_ssa_libsais() {
         let mut text = b"ABRACADABRA$".to_vec();
-        let sa = build_ssa(&mut text, &SAConstructionAlgorithm::LibSais, 1).unwrap();
+        let sa = build_ssa(&mut text, &SAConstructionAlgorithm::LibSais, 1, 1, true).unwrap();
         assert_eq!(sa, vec![11, 10, 7, 0, 3, 5, 8, 1, 4, 6, 9, 2]);
     }
 
     #[test]
+    #[cfg(feature = "libsais")]
     fn test_build_ssa_libsais_empty() {
         let mut text = b"".to_vec();
-        let sa = build_ssa(&mut text, &SAConstructionAlgorithm::LibSais, 1).unwrap();
+        let sa = build_ssa(&mut text, &SAConstructionAlgorithm::LibSais, 1, 1, true).unwrap();
         assert_eq!(sa, vec![]);
     }
 
     #[test]
+    #[cfg(feature = "libsais")]
     fn test_build_ssa_libsais_sparse() {
         let mut text = b"ABRACADABRA$".to_vec();
-        let sa = build_ssa(&mut text, &SAConstructionAlgorithm::LibSais, 2).unwrap();
+        let sa = build_ssa(&mut text, &SAConstructionAlgorithm::LibSais, 2, 1, true).unwrap();
         assert_eq!(sa, vec![10, 0, 8, 4, 6, 2]);
     }
 
     #[test]
+    #[cfg(feature = "libdivsufsort")]
     fn test_build_ssa_libdivsufsort() {
         let mut text = b"ABRACADABRA$".to_vec();
-        let sa = build_ssa(&mut text, &SAConstructionAlgorithm::LibDivSufSort, 1).unwrap();
+        let sa = build_ssa(&mut text, &SAConstructionAlgorithm::LibDivSufSort, 1, 1, true).unwrap();
         assert_eq!(sa, vec![11, 10, 7, 0, 3, 5, 8, 1, 4, 6, 9, 2]);
     }
 
     #[test]
+    #[cfg(feature = "libdivsufsort")]
     fn test_build_ssa_libdivsufsort_empty() {
         let mut text = b"".to_vec();
-        let sa = build_ssa(&mut text, &SAConstructionAlgorithm::LibDivSufSort, 1).unwrap();
+        let sa = build_ssa(&mut text, &SAConstructionAlgorithm::LibDivSufSort, 1, 1, true).unwrap();
         assert_eq!(sa, vec![]);
     }
 
     #[test]
+    #[cfg(feature = "libdivsufsort")]
     fn test_build_ssa_libdivsufsort_sparse() {
         let mut text = b"ABRACADABRA$".to_vec();
-        let sa = build_ssa(&mut text, &SAConstructionAlgorithm::LibDivSufSort, 2).unwrap();
+        let sa = build_ssa(&mut text, &SAConstructionAlgorithm::LibDivSufSort, 2, 1, true).unwrap();
+        assert_eq!(sa, vec![10, 0, 8, 4, 6, 2]);
+    }
+
+    #[test]
+    fn test_build_ssa_equate_il() {
+        let mut text = b"IALA$".to_vec();
+        let sa = build_ssa(&mut text, &SAConstructionAlgorithm::Native, 1, 1, true).unwrap();
+        assert_eq!(text, b"IAIA$".to_vec());
+        // the suffix "IA$" is sorted before "IAIA$"
+        assert_eq!(sa, vec![4, 3, 1, 2, 0]);
+    }
+
+    #[test]
+    fn test_build_ssa_distinguish_il() {
+        let mut text = b"IALA$".to_vec();
+        let sa = build_ssa(&mut text, &SAConstructionAlgorithm::Native, 1, 1, false).unwrap();
+        assert_eq!(text, b"IALA$".to_vec());
+        // the suffix "LA$" is now sorted after "IALA$"
+        assert_eq!(sa, vec![4, 3, 1, 0, 2]);
+    }
+
+    #[test]
+    fn test_build_ssa_native() {
+        let mut text = b"ABRACADABRA$".to_vec();
+        let sa = build_ssa(&mut text, &SAConstructionAlgorithm::Native, 1, 1, true).unwrap();
+        assert_eq!(sa, vec![11, 10, 7, 0, 3, 5, 8, 1, 4, 6, 9, 2]);
+    }
+
+    #[test]
+    fn test_build_ssa_native_empty() {
+        let mut text = b"".to_vec();
+        let sa = build_ssa(&mut text, &SAConstructionAlgorithm::Native, 1, 1, true).unwrap();
+        assert_eq!(sa, vec![]);
+    }
+
+    #[test]
+    fn test_build_ssa_native_sparse() {
+        let mut text = b"ABRACADABRA$".to_vec();
+        let sa = build_ssa(&mut text, &SAConstructionAlgorithm::Native, 2, 1, true).unwrap();
         assert_eq!(sa, vec![10, 0, 8, 4, 6, 2]);
     }
 
+    #[test]
+    #[cfg(feature = "libsais")]
+    fn test_build_ssa_native_random() {
+        // generate the texts with a simple linear congruential generator, so the test is reproducible
+        let mut state: u64 = 42;
+        for (length, alphabet_size) in [(1, 20), (10, 1), (100, 2), (1000, 4), (5000, 20)] {
+            let mut text: Vec<u8> = (0..length)
+                .map(|_| {
+                    state = state.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+                    PROTEIN_ALPHABET[((state >> 33) % alphabet_size) as usize]
+                })
+                .collect();
+            text.push(b'$');
+
+            let native_sa = build_ssa(&mut text.clone(), &SAConstructionAlgorithm::Native, 1, 1, true).unwrap();
+            let libsais_sa = build_ssa(&mut text, &SAConstructionAlgorithm::LibSais, 1, 1, true).unwrap();
+            assert_eq!(native_sa, libsais_sa);
+        }
+    }
+
     #[test]
     fn test_translate_l_to_i() {
         let mut text = b"ABCDEFGHIJKLMNOPQRSTUVWXYZ$-".to_vec();
@@ -5,10 +5,14 @@ use std::{
 };
 
 use clap::Parser;
-use sa_builder::{build_ssa, Arguments};
+#[cfg(feature = "libsais")]
+use sa_builder::build_lcp;
+use sa_builder::{build_proteins, build_ssa, Arguments, OutputFormat};
 use sa_compression::dump_compressed_suffix_array;
-use sa_index::binary::dump_suffix_array;
-use sa_mappings::proteins::Proteins;
+#[cfg(feature = "libsais")]
+use sa_index::binary::Binary;
+use sa_index::binary::{dump_suffix_array, dump_suffix_array_text};
+use sa_mappings::proteins::{dump_proteins, Proteins};
 
 fn main() {
     let Arguments {
@@ -16,22 +20,61 @@ fn main() {
         output,
         sparseness_factor,
         construction_algorithm,
-        compress_sa
+        compress_sa,
+        threads,
+        write_lcp,
+        write_proteins,
+        assume_uppercase,
+        duplicate_accessions,
+        annotation_format,
+        equate_il,
+        format,
+        text_prefix_length,
+        zstd_level
     } = Arguments::parse();
+
+    // the LCP array needs the full suffix array, so fail before spending time on loading and building
+    if write_lcp.is_some() && sparseness_factor > 1 {
+        eprint_and_exit("The LCP array can only be written if the sparseness_factor is 1");
+    }
+    #[cfg(not(feature = "libsais"))]
+    if write_lcp.is_some() {
+        eprint_and_exit("The LCP array can only be written if the builder is compiled with the libsais feature");
+    }
+
     eprintln!();
     eprintln!("📋 Started loading the proteins...");
     let start_proteins_time = get_time_ms().unwrap();
-    let mut data = Proteins::try_from_database_file_uncompressed(&database_file)
+    let (mut data, proteins, skipped_accessions) = if write_proteins.is_some() {
+        let (data, proteins, skipped_accessions) = Proteins::try_from_database_file_uncompressed_with_proteins(
+            &database_file,
+            assume_uppercase,
+            &duplicate_accessions,
+            &annotation_format
+        )
         .unwrap_or_else(|err| eprint_and_exit(err.to_string().as_str()));
+
+        // compress the text now, since building the suffix array translates its L's to I's
+        let proteins = build_proteins(&data, proteins).unwrap_or_else(|err| eprint_and_exit(err.to_string().as_str()));
+        (data, Some(proteins), skipped_accessions)
+    } else {
+        let (data, skipped_accessions) =
+            Proteins::try_from_database_file_uncompressed(&database_file, assume_uppercase, &duplicate_accessions)
+                .unwrap_or_else(|err| eprint_and_exit(err.to_string().as_str()));
+        (data, None, skipped_accessions)
+    };
     eprintln!(
         "✅ Successfully loaded the proteins in {} seconds!",
         (get_time_ms().unwrap() - start_proteins_time) / 1000.0
     );
+    for accession in &skipped_accessions {
+        eprintln!("\tSkipped duplicate accession {}", accession);
+    }
 
     eprintln!();
     eprintln!("📋 Started building the suffix array...");
     let start_ssa_time = get_time_ms().unwrap();
-    let sa = build_ssa(&mut data, &construction_algorithm, sparseness_factor)
+    let sa = build_ssa(&mut data, &construction_algorithm, sparseness_factor, threads, equate_il)
         .unwrap_or_else(|err| eprint_and_exit(err.to_string().as_str()));
     eprintln!(
         "✅ Successfully built the suffix array in {} seconds!",
@@ -40,6 +83,26 @@ fn main() {
     eprintln!("\tAmount of items: {}", sa.len());
     eprintln!("\tSample rate: {}", sparseness_factor);
 
+    #[cfg(feature = "libsais")]
+    if let Some(lcp_output) = write_lcp {
+        eprintln!();
+        eprintln!("📋 Started building and dumping the LCP array...");
+        let start_lcp_time = get_time_ms().unwrap();
+
+        let lcp = build_lcp(&data, &sa).unwrap_or_else(|err| eprint_and_exit(err.to_string().as_str()));
+        let mut lcp_file = open_file_buffer(&lcp_output, 100 * 1024 * 1024)
+            .unwrap_or_else(|err| eprint_and_exit(err.to_string().as_str()));
+
+        if let Err(err) = lcp.write_binary(&mut lcp_file) {
+            eprint_and_exit(err.to_string().as_str());
+        }
+
+        eprintln!(
+            "✅ Successfully built and dumped the LCP array in {} seconds!",
+            (get_time_ms().unwrap() - start_lcp_time) / 1000.0
+        );
+    }
+
     // open the output file
     let mut file =
         open_file_buffer(&output, 100 * 1024 * 1024).unwrap_or_else(|err| eprint_and_exit(err.to_string().as_str()));
@@ -48,10 +111,21 @@ fn main() {
     eprintln!("📋 Started dumping the suffix array...");
     let start_dump_time = get_time_ms().unwrap();
 
-    if compress_sa {
+    if format == OutputFormat::Text {
+        if let Err(err) = dump_suffix_array_text(&sa, &data, text_prefix_length, &mut file) {
+            eprint_and_exit(err.to_string().as_str());
+        }
+
+        eprintln!(
+            "✅ Successfully dumped the suffix array as text in {} seconds!",
+            (get_time_ms().unwrap() - start_dump_time) / 1000.0
+        );
+    } else if compress_sa {
         let bits_per_value = (data.len() as f64).log2().ceil() as usize;
 
-        if let Err(err) = dump_compressed_suffix_array(sa, sparseness_factor, bits_per_value, &mut file) {
+        if let Err(err) =
+            dump_compressed_suffix_array(sa, sparseness_factor, equate_il, bits_per_value, zstd_level, &mut file)
+        {
             eprint_and_exit(err.to_string().as_str());
         };
 
@@ -61,7 +135,7 @@ fn main() {
         );
         eprintln!("\tAmount of bits per item: {}", bits_per_value);
     } else {
-        if let Err(err) = dump_suffix_array(&sa, sparseness_factor, &mut file) {
+        if let Err(err) = dump_suffix_array(&sa, sparseness_factor, equate_il, &mut file) {
             eprint_and_exit(err.to_string().as_str());
         }
 
@@ -71,6 +145,24 @@ fn main() {
         );
         eprintln!("\tAmount of bits per item: 64");
     }
+
+    if let (Some(proteins_output), Some(proteins)) = (write_proteins, proteins) {
+        eprintln!();
+        eprintln!("📋 Started dumping the proteins...");
+        let start_proteins_dump_time = get_time_ms().unwrap();
+
+        let mut proteins_file = open_file_buffer(&proteins_output, 100 * 1024 * 1024)
+            .unwrap_or_else(|err| eprint_and_exit(err.to_string().as_str()));
+
+        if let Err(err) = dump_proteins(&proteins, &mut proteins_file) {
+            eprint_and_exit(err.to_string().as_str());
+        }
+
+        eprintln!(
+            "✅ Successfully dumped the proteins in {} seconds!",
+            (get_time_ms().unwrap() - start_proteins_dump_time) / 1000.0
+        );
+    }
 }
 
 fn open_file_buffer(file: &str, buffer_size: usize) -> std::io::Result<BufWriter<File>> {
@@ -1,48 +1,75 @@
 use std::{
     error::Error,
-    io::{BufRead, Write}
+    io::{BufRead, BufReader, Write}
 };
 
 use bitarray::{data_to_writer, Binary, BitArray};
+use index_format::{write_header, FileKind, IndexError, EQUATE_IL_FLAG};
 use sa_index::SuffixArray;
 
-/// Writes the compressed suffix array to a writer.
+/// Flag that is set in the bits per value byte if the compressed suffix array is also compressed
+/// with zstd. The amount of bits per value is at most 64, so this bit is never set in files
+/// without zstd compression.
+pub const ZSTD_FLAG: u8 = 0x80;
+
+/// Writes the compressed suffix array to a writer. If a zstd level is given, the packed values are
+/// also compressed with zstd. This exploits the redundancy across values, which the packing of the
+/// values does not. The `ZSTD_FLAG` is then set in the bits per value byte, so
+/// `load_compressed_suffix_array` can detect it.
 ///
 /// # Arguments
 ///
 /// * `sa` - The suffix array to be compressed.
 /// * `sparseness_factor` - The sparseness factor used for compression.
+/// * `equate_il` - True if the suffix array was built with every L in the text translated to an I.
 /// * `bits_per_value` - The number of bits used to represent each value in the compressed array.
+/// * `zstd_level` - The zstd compression level, or `None` to only pack the values.
 /// * `writer` - The writer to which the compressed array will be written.
 ///
 /// # Errors
 ///
-/// Returns an error if writing to the writer fails.
+/// Returns an error if writing to the writer or compressing the values fails.
 pub fn dump_compressed_suffix_array(
     sa: Vec<i64>,
     sparseness_factor: u8,
+    equate_il: bool,
     bits_per_value: usize,
+    zstd_level: Option<i32>,
     writer: &mut impl Write
 ) -> Result<(), Box<dyn Error>> {
+    // Write the header to the writer
+    write_header(FileKind::SuffixArray, if equate_il { EQUATE_IL_FLAG } else { 0 }, writer)?;
+
     // Write the flags to the writer
     // 00000001 indicates that the suffix array is compressed
+    let flags = if zstd_level.is_some() { bits_per_value as u8 | ZSTD_FLAG } else { bits_per_value as u8 };
     writer
-        .write(&[bits_per_value as u8])
+        .write_all(&[flags])
         .map_err(|_| "Could not write the required bits to the writer")?;
 
     // Write the sparseness factor to the writer
     writer
-        .write(&[sparseness_factor])
+        .write_all(&[sparseness_factor])
         .map_err(|_| "Could not write the sparseness factor to the writer")?;
 
     // Write the size of the suffix array to the writer
     writer
-        .write(&(sa.len() as u64).to_le_bytes())
+        .write_all(&(sa.len() as u64).to_le_bytes())
         .map_err(|_| "Could not write the size of the suffix array to the writer")?;
 
-    // Compress the suffix array and write it to the writer
-    data_to_writer(sa, bits_per_value, 8 * 1024, writer)
-        .map_err(|_| "Could not write the compressed suffix array to the writer")?;
+    // Compress the suffix array and write it to the writer, through a zstd encoder if requested
+    match zstd_level {
+        Some(level) => {
+            let mut encoder = zstd::Encoder::new(writer, level)?;
+            data_to_writer(sa, bits_per_value, 8 * 1024, &mut encoder)
+                .map_err(|_| "Could not write the compressed suffix array to the writer")?;
+            encoder.finish().map_err(|_| "Could not write the compressed suffix array to the writer")?;
+        }
+        None => {
+            data_to_writer(sa, bits_per_value, 8 * 1024, writer)
+                .map_err(|_| "Could not write the compressed suffix array to the writer")?;
+        }
+    }
 
     Ok(())
 }
@@ -52,34 +79,50 @@ pub fn dump_compressed_suffix_array(
 /// # Arguments
 ///
 /// * `reader` - The reader from which the compressed array will be read.
-/// * `bits_per_value` - The number of bits used to represent each value in the compressed array.
+/// * `bits_per_value` - The number of bits used to represent each value in the compressed array,
+///   as read from the file. If the `ZSTD_FLAG` is set, the values are decompressed with zstd.
 ///
 /// # Errors
 ///
-/// Returns an error if reading from the reader fails.
+/// Returns an `IndexError` if reading from the reader fails, or if the reader contains fewer values
+/// than the size of the suffix array.
 pub fn load_compressed_suffix_array(
     reader: &mut impl BufRead,
     bits_per_value: usize
-) -> Result<SuffixArray, Box<dyn Error>> {
+) -> Result<SuffixArray, IndexError> {
     // Read the sample rate from the binary file (1 byte)
     let mut sample_rate_buffer = [0_u8; 1];
     reader
         .read_exact(&mut sample_rate_buffer)
-        .map_err(|_| "Could not read the sample rate from the binary file")?;
+        .map_err(|err| IndexError::read("the sample rate", err))?;
     let sample_rate = sample_rate_buffer[0];
 
     // Read the size of the suffix array from the binary file (8 bytes)
     let mut size_buffer = [0_u8; 8];
     reader
         .read_exact(&mut size_buffer)
-        .map_err(|_| "Could not read the size of the suffix array from the binary file")?;
+        .map_err(|err| IndexError::read("the size of the suffix array", err))?;
     let size = u64::from_le_bytes(size_buffer) as usize;
 
     // Read the compressed suffix array from the binary file
-    let mut compressed_suffix_array = BitArray::with_capacity(size, bits_per_value);
-    compressed_suffix_array
-        .read_binary(reader)
-        .map_err(|_| "Could not read the compressed suffix array from the binary file")?;
+    let zstd_compressed = bits_per_value & ZSTD_FLAG as usize != 0;
+    let mut compressed_suffix_array = BitArray::with_capacity(size, bits_per_value & !(ZSTD_FLAG as usize));
+    let expected_words = compressed_suffix_array.capacity_words();
+    if zstd_compressed {
+        let decoder = zstd::Decoder::with_buffer(reader)
+            .map_err(|err| IndexError::read("the compressed suffix array", err))?
+            .single_frame();
+        compressed_suffix_array
+            .read_binary(BufReader::new(decoder))
+            .map_err(|err| IndexError::read("the compressed suffix array", err))?;
+    } else {
+        compressed_suffix_array
+            .read_binary(reader)
+            .map_err(|err| IndexError::read("the compressed suffix array", err))?;
+    }
+    if compressed_suffix_array.capacity_words() < expected_words {
+        return Err(IndexError::Truncated { part: "the compressed suffix array" });
+    }
 
     Ok(SuffixArray::Compressed(compressed_suffix_array, sample_rate))
 }
@@ -139,10 +182,11 @@ mod tests {
         let sa = vec![1, 2, 3, 4, 5, 6, 7, 8, 9, 10];
 
         let mut writer = vec![];
-        dump_compressed_suffix_array(sa, 1, 8, &mut writer).unwrap();
+        dump_compressed_suffix_array(sa, 1, true, 8, None, &mut writer).unwrap();
 
         assert_eq!(writer, vec![
-            // bits per value
+            // header
+            b'U', b'P', b'I', b'1', 1, 1, // bits per value
             8, // sparseness factor
             1, // size of the suffix array
             10, 0, 0, 0, 0, 0, 0, 0, // compressed suffix array
@@ -150,36 +194,44 @@ mod tests {
         ]);
     }
 
+    #[test]
+    #[should_panic(expected = "Could not write the header to the writer")]
+    fn test_dump_compressed_suffix_array_fail_header() {
+        let mut writer = FailingWriter { valid_write_count: 0 };
+
+        dump_compressed_suffix_array(vec![], 1, true, 8, None, &mut writer).unwrap();
+    }
+
     #[test]
     #[should_panic(expected = "Could not write the required bits to the writer")]
     fn test_dump_compressed_suffix_array_fail_required_bits() {
-        let mut writer = FailingWriter { valid_write_count: 0 };
+        let mut writer = FailingWriter { valid_write_count: 6 };
 
-        dump_compressed_suffix_array(vec![], 1, 8, &mut writer).unwrap();
+        dump_compressed_suffix_array(vec![], 1, true, 8, None, &mut writer).unwrap();
     }
 
     #[test]
     #[should_panic(expected = "Could not write the sparseness factor to the writer")]
     fn test_dump_compressed_suffix_array_fail_sparseness_factor() {
-        let mut writer = FailingWriter { valid_write_count: 1 };
+        let mut writer = FailingWriter { valid_write_count: 7 };
 
-        dump_compressed_suffix_array(vec![], 1, 8, &mut writer).unwrap();
+        dump_compressed_suffix_array(vec![], 1, true, 8, None, &mut writer).unwrap();
     }
 
     #[test]
     #[should_panic(expected = "Could not write the size of the suffix array to the writer")]
     fn test_dump_compressed_suffix_array_fail_size() {
-        let mut writer = FailingWriter { valid_write_count: 2 };
+        let mut writer = FailingWriter { valid_write_count: 8 };
 
-        dump_compressed_suffix_array(vec![], 1, 8, &mut writer).unwrap();
+        dump_compressed_suffix_array(vec![], 1, true, 8, None, &mut writer).unwrap();
     }
 
     #[test]
     #[should_panic(expected = "Could not write the compressed suffix array to the writer")]
     fn test_dump_compressed_suffix_array_fail_compressed_suffix_array() {
-        let mut writer = FailingWriter { valid_write_count: 3 };
+        let mut writer = FailingWriter { valid_write_count: 16 };
 
-        dump_compressed_suffix_array(vec![1], 1, 8, &mut writer).unwrap();
+        dump_compressed_suffix_array(vec![1], 1, true, 8, None, &mut writer).unwrap();
     }
 
     #[test]
@@ -201,27 +253,95 @@ mod tests {
     }
 
     #[test]
-    #[should_panic(expected = "Could not read the sample rate from the binary file")]
     fn test_load_compressed_suffix_array_fail_sample_rate() {
         let mut reader = FailingReader { valid_read_count: 0 };
 
-        load_compressed_suffix_array(&mut reader, 8).unwrap();
+        let err = load_compressed_suffix_array(&mut reader, 8).err().unwrap();
+        assert!(matches!(err, IndexError::Io { part: "the sample rate", .. }));
     }
 
     #[test]
-    #[should_panic(expected = "Could not read the size of the suffix array from the binary file")]
     fn test_load_compressed_suffix_array_fail_size() {
         let mut reader = FailingReader { valid_read_count: 1 };
 
-        load_compressed_suffix_array(&mut reader, 8).unwrap();
+        let err = load_compressed_suffix_array(&mut reader, 8).err().unwrap();
+        assert!(matches!(err, IndexError::Io { part: "the size of the suffix array", .. }));
     }
 
     #[test]
-    #[should_panic(expected = "Could not read the compressed suffix array from the binary file")]
     fn test_load_compressed_suffix_array_fail_compressed_suffix_array() {
         let mut reader = FailingReader { valid_read_count: 2 };
 
-        load_compressed_suffix_array(&mut reader, 8).unwrap();
+        let err = load_compressed_suffix_array(&mut reader, 8).err().unwrap();
+        assert!(matches!(err, IndexError::Io { part: "the compressed suffix array", .. }));
+    }
+
+    #[test]
+    fn test_load_compressed_suffix_array_truncated() {
+        let data = vec![
+            // sparseness factor
+            1, // size of the suffix array
+            10, 0, 0, 0, 0, 0, 0, 0, // only the first word of the compressed suffix array
+            8, 7, 6, 5, 4, 3, 2, 1,
+        ];
+
+        let mut reader = std::io::BufReader::new(&data[..]);
+        let err = load_compressed_suffix_array(&mut reader, 8).err().unwrap();
+        assert!(matches!(err, IndexError::Truncated { part: "the compressed suffix array" }));
+    }
+
+    #[test]
+    fn test_zstd_compressed_suffix_array() {
+        // a sparse suffix array with a lot of redundancy across the values
+        let sa: Vec<i64> = (0..100_000).map(|i| (i % 64) * 1000).collect();
+
+        let mut packed = vec![];
+        dump_compressed_suffix_array(sa.clone(), 3, true, 17, None, &mut packed).unwrap();
+        let mut zstd_compressed = vec![];
+        dump_compressed_suffix_array(sa.clone(), 3, true, 17, Some(3), &mut zstd_compressed).unwrap();
+
+        assert!(zstd_compressed.len() < packed.len() / 2);
+        assert_eq!(zstd_compressed[6], 17 | ZSTD_FLAG);
+
+        // skip the header of 6 bytes and the bits per value
+        for data in [packed, zstd_compressed] {
+            let mut reader = std::io::BufReader::new(&data[7..]);
+            let compressed_suffix_array = load_compressed_suffix_array(&mut reader, data[6] as usize).unwrap();
+
+            assert_eq!(compressed_suffix_array.sample_rate(), 3);
+            assert_eq!(compressed_suffix_array.bits_per_value(), 17);
+            assert_eq!(compressed_suffix_array.len(), sa.len());
+            for (i, &value) in sa.iter().enumerate() {
+                assert_eq!(compressed_suffix_array.get(i), value);
+            }
+        }
+    }
+
+    #[test]
+    fn test_load_zstd_compressed_suffix_array_fail() {
+        let data = vec![
+            // sparseness factor
+            1, // size of the suffix array
+            10, 0, 0, 0, 0, 0, 0, 0, // not a zstd frame
+            8, 7, 6, 5, 4, 3, 2, 1,
+        ];
+
+        let mut reader = std::io::BufReader::new(&data[..]);
+        let err = load_compressed_suffix_array(&mut reader, 8 | ZSTD_FLAG as usize).err().unwrap();
+        assert!(matches!(err, IndexError::Io { part: "the compressed suffix array", .. }));
+    }
+
+    #[test]
+    fn test_load_zstd_compressed_suffix_array_truncated() {
+        let mut data = vec![];
+        dump_compressed_suffix_array(vec![1, 2, 3, 4, 5, 6, 7, 8, 9, 10], 1, true, 8, Some(3), &mut data).unwrap();
+
+        // the zstd frame is complete, but contains fewer values than the size of the suffix array
+        data[8..16].copy_from_slice(&20_u64.to_le_bytes());
+
+        let mut reader = std::io::BufReader::new(&data[7..]);
+        let err = load_compressed_suffix_array(&mut reader, data[6] as usize).err().unwrap();
+        assert!(matches!(err, IndexError::Truncated { part: "the compressed suffix array" }));
     }
 
     #[test]
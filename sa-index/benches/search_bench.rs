@@ -0,0 +1,99 @@
+use criterion::{black_box, criterion_group, criterion_main, BatchSize, Criterion};
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use sa_index::sa_searcher::Searcher;
+use sa_mappings::proteins::{Delimiters, Protein, Proteins};
+use text_compression::ProteinText;
+
+/// The amino acids used to generate the synthetic proteins and peptides.
+const AMINO_ACIDS: &[u8] = b"ACDEFGHIKLMNPQRSTVWY";
+
+/// The total length of the synthetic protein text, about 1MB.
+const TEXT_LENGTH: usize = 1 << 20;
+
+/// The length of a single synthetic protein.
+const PROTEIN_LENGTH: usize = 400;
+
+/// The length of the searched peptides.
+const PEPTIDE_LENGTH: usize = 10;
+
+/// Generate a random sequence of amino acids.
+fn generate_sequence(random: &mut impl Rng, length: usize) -> Vec<u8> {
+    (0..length).map(|_| AMINO_ACIDS[random.gen_range(0..AMINO_ACIDS.len())]).collect()
+}
+
+/// Build a searcher over a synthetic protein text of about 1MB. The same seed is used every time,
+/// so the results of different runs can be compared.
+fn build_searcher() -> (Searcher, Vec<u8>) {
+    let mut random = StdRng::seed_from_u64(42);
+
+    let protein_count = TEXT_LENGTH / (PROTEIN_LENGTH + 1);
+    let mut text = Vec::with_capacity(TEXT_LENGTH);
+    let mut proteins = Vec::with_capacity(protein_count);
+    for i in 0..protein_count {
+        text.extend(generate_sequence(&mut random, PROTEIN_LENGTH));
+        text.push(b'-');
+        proteins.push(Protein {
+            uniprot_id: format!("P{:05}", i),
+            taxon_id: 1,
+            functional_annotations: vec![]
+        });
+    }
+    text.pop();
+    text.push(b'$');
+
+    let proteins = Proteins {
+        text: ProteinText::from_vec(&text),
+        proteins,
+        accession_index: None,
+        original_sequences: None,
+        delimiters: Delimiters::default(),
+        skipped_accessions: Vec::new()
+    };
+
+    (Searcher::from_proteins(proteins, 1).unwrap(), text)
+}
+
+/// Pick a random peptide that occurs in the text.
+fn occurring_peptide(random: &mut impl Rng, text: &[u8]) -> Vec<u8> {
+    loop {
+        let start = random.gen_range(0..text.len() - PEPTIDE_LENGTH);
+        let peptide = &text[start..start + PEPTIDE_LENGTH];
+        if peptide.iter().all(|c| c.is_ascii_uppercase()) {
+            return peptide.to_vec();
+        }
+    }
+}
+
+pub fn search_benchmark(c: &mut Criterion) {
+    let (searcher, text) = build_searcher();
+
+    c.bench_function("search_bounds", |b| {
+        b.iter_batched(
+            || occurring_peptide(&mut rand::thread_rng(), &text),
+            |peptide| black_box(searcher.search_bounds(&peptide)),
+            BatchSize::SmallInput
+        )
+    });
+
+    for equate_il in [false, true] {
+        c.bench_function(&format!("search_matching_suffixes_hit_equate_il_{}", equate_il), |b| {
+            b.iter_batched(
+                || occurring_peptide(&mut rand::thread_rng(), &text),
+                |peptide| black_box(searcher.search_matching_suffixes(&peptide, 10_000, equate_il, false)),
+                BatchSize::SmallInput
+            )
+        });
+
+        // random peptides of this length are very unlikely to occur in the text
+        c.bench_function(&format!("search_matching_suffixes_miss_equate_il_{}", equate_il), |b| {
+            b.iter_batched(
+                || generate_sequence(&mut rand::thread_rng(), PEPTIDE_LENGTH),
+                |peptide| black_box(searcher.search_matching_suffixes(&peptide, 10_000, equate_il, false)),
+                BatchSize::SmallInput
+            )
+        });
+    }
+}
+
+criterion_group!(benches, search_benchmark);
+criterion_main!(benches);
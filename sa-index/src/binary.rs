@@ -3,6 +3,8 @@ use std::{
     io::{BufRead, Read, Write}
 };
 
+use index_format::{write_header, FileKind, IndexError, EQUATE_IL_FLAG};
+
 use crate::SuffixArray;
 
 /// The `Binary` trait provides methods for reading and writing a struct as binary.
@@ -84,12 +86,21 @@ impl Binary for Vec<i64> {
 ///
 /// * `sa` - The suffix array to dump.
 /// * `sparseness_factor` - The sparseness factor to write to the file.
+/// * `equate_il` - True if the suffix array was built with every L in the text translated to an I.
 /// * `writer` - The writer to write the binary data to.
 ///
 /// # Returns
 ///
 /// Returns `Ok(())` if the write operation is successful, or an `Err` if an error occurs.
-pub fn dump_suffix_array(sa: &Vec<i64>, sparseness_factor: u8, writer: &mut impl Write) -> Result<(), Box<dyn Error>> {
+pub fn dump_suffix_array(
+    sa: &Vec<i64>,
+    sparseness_factor: u8,
+    equate_il: bool,
+    writer: &mut impl Write
+) -> Result<(), Box<dyn Error>> {
+    // Write the header to the writer
+    write_header(FileKind::SuffixArray, if equate_il { EQUATE_IL_FLAG } else { 0 }, writer)?;
+
     // Write the required bits to the writer
     // 01000000 indicates that the suffix array is not compressed
     writer.write(&[64_u8]).map_err(|_| "Could not write the required bits to the writer")?;
@@ -111,6 +122,44 @@ pub fn dump_suffix_array(sa: &Vec<i64>, sparseness_factor: u8, writer: &mut impl
     Ok(())
 }
 
+/// Writes the suffix array as text, with one value per line in decimal. This format is only meant
+/// for inspecting the suffix array while debugging, it can not be loaded again.
+///
+/// # Arguments
+///
+/// * `sa` - The suffix array to dump.
+/// * `text` - The text over which the suffix array is built.
+/// * `prefix_length` - The amount of characters of every suffix that is written after its value,
+///   separated by a tab. No characters are written if this is 0.
+/// * `writer` - The writer to write the text to.
+///
+/// # Returns
+///
+/// Returns `Ok(())` if the write operation is successful, or an `Err` if an error occurs.
+pub fn dump_suffix_array_text(
+    sa: &[i64],
+    text: &[u8],
+    prefix_length: usize,
+    writer: &mut impl Write
+) -> Result<(), Box<dyn Error>> {
+    for &suffix in sa {
+        write!(writer, "{}", suffix).map_err(|_| "Could not write the suffix array to the writer")?;
+
+        if prefix_length > 0 {
+            let start = (suffix as usize).min(text.len());
+            let end = (start + prefix_length).min(text.len());
+            writer
+                .write_all(b"\t")
+                .and_then(|_| writer.write_all(&text[start..end]))
+                .map_err(|_| "Could not write the suffix to the writer")?;
+        }
+
+        writer.write_all(b"\n").map_err(|_| "Could not write the suffix array to the writer")?;
+    }
+
+    Ok(())
+}
+
 /// Loads the suffix array from the file with the given `filename`
 ///
 /// # Arguments
@@ -122,24 +171,28 @@ pub fn dump_suffix_array(sa: &Vec<i64>, sparseness_factor: u8, writer: &mut impl
 ///
 /// # Errors
 ///
-/// Returns any error from opening the file or reading the file
-pub fn load_suffix_array(reader: &mut impl BufRead) -> Result<SuffixArray, Box<dyn Error>> {
+/// Returns an `IndexError` if reading the file fails, or if the file contains fewer values than the
+/// size of the suffix array
+pub fn load_suffix_array(reader: &mut impl BufRead) -> Result<SuffixArray, IndexError> {
     // Read the sample rate from the binary file (1 byte)
     let mut sample_rate_buffer = [0_u8; 1];
     reader
         .read_exact(&mut sample_rate_buffer)
-        .map_err(|_| "Could not read the sample rate from the binary file")?;
+        .map_err(|err| IndexError::read("the sample rate", err))?;
     let sample_rate = sample_rate_buffer[0];
 
     // Read the size of the suffix array from the binary file (8 bytes)
     let mut size_buffer = [0_u8; 8];
     reader
         .read_exact(&mut size_buffer)
-        .map_err(|_| "Could not read the size of the suffix array from the binary file")?;
+        .map_err(|err| IndexError::read("the size of the suffix array", err))?;
     let size = u64::from_le_bytes(size_buffer) as usize;
 
     let mut sa = Vec::with_capacity(size);
-    sa.read_binary(reader).map_err(|_| "Could not read the suffix array from the binary file")?;
+    sa.read_binary(reader).map_err(|err| IndexError::read("the suffix array", err))?;
+    if sa.len() < size {
+        return Err(IndexError::Truncated { part: "the suffix array" });
+    }
 
     Ok(SuffixArray::Original(sa, sample_rate))
 }
@@ -290,48 +343,87 @@ mod tests {
         let mut buffer = Vec::new();
         let sa = vec![1, 2, 3, 4, 5];
 
-        dump_suffix_array(&sa, 1, &mut buffer).unwrap();
+        dump_suffix_array(&sa, 1, true, &mut buffer).unwrap();
 
         assert_eq!(buffer, vec![
-            // required bits
+            // header
+            b'U', b'P', b'I', b'1', 1, 1, // required bits
             64, // Sparseness factor
             1,  // Size of the suffix array
             5, 0, 0, 0, 0, 0, 0, 0, // Suffix array
             1, 0, 0, 0, 0, 0, 0, 0, 2, 0, 0, 0, 0, 0, 0, 0, 3, 0, 0, 0, 0, 0, 0, 0, 4, 0, 0, 0, 0, 0, 0, 0, 5, 0, 0, 0,
             0, 0, 0, 0
         ]);
+
+        // the equate_il flag is not set for a suffix array that distinguishes I and L
+        let mut buffer = Vec::new();
+        dump_suffix_array(&sa, 1, false, &mut buffer).unwrap();
+        assert_eq!(buffer[5], 0);
+    }
+
+    #[test]
+    fn test_dump_suffix_array_text() {
+        let text = b"BANANA$";
+        let sa = vec![6, 5, 3, 1, 0, 4, 2];
+
+        let mut buffer = Vec::new();
+        dump_suffix_array_text(&sa, text, 0, &mut buffer).unwrap();
+        assert_eq!(String::from_utf8(buffer).unwrap(), "6\n5\n3\n1\n0\n4\n2\n");
+
+        let mut buffer = Vec::new();
+        dump_suffix_array_text(&sa, text, 3, &mut buffer).unwrap();
+        assert_eq!(
+            String::from_utf8(buffer).unwrap(),
+            "6\t$\n5\tA$\n3\tANA\n1\tANA\n0\tBAN\n4\tNA$\n2\tNAN\n"
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "Could not write the suffix array to the writer")]
+    fn test_dump_suffix_array_text_fail() {
+        let mut writer = FailingWriter { valid_write_count: 0 };
+
+        dump_suffix_array_text(&[1, 2], b"AB$", 0, &mut writer).unwrap();
+    }
+
+    #[test]
+    #[should_panic(expected = "Could not write the header to the writer")]
+    fn test_dump_suffix_array_fail_header() {
+        let mut writer = FailingWriter { valid_write_count: 0 };
+
+        dump_suffix_array(&vec![], 1, true, &mut writer).unwrap();
     }
 
     #[test]
     #[should_panic(expected = "Could not write the required bits to the writer")]
     fn test_dump_suffix_array_fail_required_bits() {
-        let mut writer = FailingWriter { valid_write_count: 0 };
+        let mut writer = FailingWriter { valid_write_count: 6 };
 
-        dump_suffix_array(&vec![], 1, &mut writer).unwrap();
+        dump_suffix_array(&vec![], 1, true, &mut writer).unwrap();
     }
 
     #[test]
     #[should_panic(expected = "Could not write the sparseness factor to the writer")]
     fn test_dump_suffix_array_fail_sparseness_factor() {
-        let mut writer = FailingWriter { valid_write_count: 1 };
+        let mut writer = FailingWriter { valid_write_count: 7 };
 
-        dump_suffix_array(&vec![], 1, &mut writer).unwrap();
+        dump_suffix_array(&vec![], 1, true, &mut writer).unwrap();
     }
 
     #[test]
     #[should_panic(expected = "Could not write the size of the suffix array to the writer")]
     fn test_dump_suffix_array_fail_size() {
-        let mut writer = FailingWriter { valid_write_count: 2 };
+        let mut writer = FailingWriter { valid_write_count: 8 };
 
-        dump_suffix_array(&vec![], 1, &mut writer).unwrap();
+        dump_suffix_array(&vec![], 1, true, &mut writer).unwrap();
     }
 
     #[test]
     #[should_panic(expected = "Could not write the suffix array to the writer")]
     fn test_dump_suffix_array_fail_suffix_array() {
-        let mut writer = FailingWriter { valid_write_count: 3 };
+        let mut writer = FailingWriter { valid_write_count: 9 };
 
-        dump_suffix_array(&vec![1], 1, &mut writer).unwrap();
+        dump_suffix_array(&vec![1], 1, true, &mut writer).unwrap();
     }
 
     #[test]
@@ -354,26 +446,40 @@ mod tests {
     }
 
     #[test]
-    #[should_panic(expected = "Could not read the sample rate from the binary file")]
     fn test_load_suffix_array_fail_sample_rate() {
         let mut reader = FailingReader { valid_read_count: 0 };
 
-        load_suffix_array(&mut reader).unwrap();
+        let err = load_suffix_array(&mut reader).err().unwrap();
+        assert!(matches!(err, IndexError::Io { part: "the sample rate", .. }));
     }
 
     #[test]
-    #[should_panic(expected = "Could not read the size of the suffix array from the binary file")]
     fn test_load_suffix_array_fail_size() {
         let mut reader = FailingReader { valid_read_count: 1 };
 
-        load_suffix_array(&mut reader).unwrap();
+        let err = load_suffix_array(&mut reader).err().unwrap();
+        assert!(matches!(err, IndexError::Io { part: "the size of the suffix array", .. }));
     }
 
     #[test]
-    #[should_panic(expected = "Could not read the suffix array from the binary file")]
     fn test_load_suffix_array_fail_suffix_array() {
         let mut reader = FailingReader { valid_read_count: 2 };
 
-        load_suffix_array(&mut reader).unwrap();
+        let err = load_suffix_array(&mut reader).err().unwrap();
+        assert!(matches!(err, IndexError::Io { part: "the suffix array", .. }));
+    }
+
+    #[test]
+    fn test_load_suffix_array_truncated() {
+        // the size of the suffix array is cut off
+        let buffer = vec![1, 5, 0, 0];
+        let err = load_suffix_array(&mut buffer.as_slice()).err().unwrap();
+        assert!(matches!(err, IndexError::Truncated { part: "the size of the suffix array" }));
+
+        // the suffix array contains fewer values than its size
+        let buffer = vec![1, 5, 0, 0, 0, 0, 0, 0, 0, 1, 0, 0, 0, 0, 0, 0, 0];
+        let err = load_suffix_array(&mut buffer.as_slice()).err().unwrap();
+        assert!(matches!(err, IndexError::Truncated { part: "the suffix array" }));
+        assert_eq!(err.to_string(), "Could not read the suffix array from the binary file: the file is truncated");
     }
 }
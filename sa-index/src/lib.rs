@@ -3,6 +3,8 @@ use bitarray::BitArray;
 pub mod binary;
 pub mod peptide_search;
 pub mod sa_searcher;
+#[cfg(feature = "serde")]
+mod serialization;
 pub mod suffix_to_protein_index;
 
 /// Represents a suffix array.
@@ -66,6 +68,15 @@ impl SuffixArray {
         }
     }
 
+    /// Returns an iterator over the values of the suffix array.
+    ///
+    /// # Returns
+    ///
+    /// An iterator over the values of the suffix array, which can also be iterated in reverse.
+    pub fn iter(&self) -> impl DoubleEndedIterator<Item = i64> + ExactSizeIterator + '_ {
+        (0..self.len()).map(move |index| self.get(index))
+    }
+
     /// Returns whether the suffix array is empty.
     ///
     /// # Returns
@@ -74,6 +85,53 @@ impl SuffixArray {
     pub fn is_empty(&self) -> bool {
         self.len() == 0
     }
+
+    /// Returns whether the suffix array is compressed.
+    ///
+    /// # Returns
+    ///
+    /// Returns `true` if the suffix array is compressed, `false` otherwise.
+    pub fn is_compressed(&self) -> bool {
+        matches!(self, SuffixArray::Compressed(_, _))
+    }
+
+    /// Packs the values of the suffix array into a compressed suffix array, with the same sample
+    /// rate.
+    ///
+    /// # Arguments
+    ///
+    /// * `bits_per_value` - The number of bits used for every value, which should be enough to
+    ///   store the largest value.
+    ///
+    /// # Returns
+    ///
+    /// The compressed suffix array.
+    pub fn compress(&self, bits_per_value: usize) -> SuffixArray {
+        let values: Vec<u64> = self.iter().map(|value| value as u64).collect();
+        SuffixArray::Compressed(BitArray::from_values(&values, bits_per_value), self.sample_rate())
+    }
+
+    /// Expands the values of the suffix array into an uncompressed suffix array, with the same
+    /// sample rate.
+    ///
+    /// # Returns
+    ///
+    /// The uncompressed suffix array.
+    pub fn decompress(&self) -> SuffixArray {
+        SuffixArray::Original(self.iter().collect(), self.sample_rate())
+    }
+
+    /// Returns the name of the variant of the suffix array.
+    ///
+    /// # Returns
+    ///
+    /// Returns `"original"` or `"compressed"`, depending on the variant of the suffix array.
+    pub fn variant_name(&self) -> &'static str {
+        match self {
+            SuffixArray::Original(_, _) => "original",
+            SuffixArray::Compressed(_, _) => "compressed"
+        }
+    }
 }
 
 /// Custom trait implemented by types that have a value that represents NULL
@@ -130,6 +188,63 @@ mod tests {
         assert_eq!(sa.get(4), 5);
     }
 
+    #[test]
+    fn test_suffix_array_iter() {
+        let sa = SuffixArray::Original(vec![1, 2, 3, 4, 5], 1);
+        assert_eq!(sa.iter().len(), 5);
+        assert_eq!(sa.iter().collect::<Vec<i64>>(), (0..5).map(|i| sa.get(i)).collect::<Vec<i64>>());
+        assert_eq!(sa.iter().rev().collect::<Vec<i64>>(), (0..5).rev().map(|i| sa.get(i)).collect::<Vec<i64>>());
+
+        let mut bitarray = BitArray::with_capacity(5, 40);
+        for (i, value) in [5, 3, 1, 4, 2].iter().enumerate() {
+            bitarray.set(i, *value);
+        }
+        let sa = SuffixArray::Compressed(bitarray, 1);
+        assert_eq!(sa.iter().len(), 5);
+        assert_eq!(sa.iter().collect::<Vec<i64>>(), vec![5, 3, 1, 4, 2]);
+        assert_eq!(sa.iter().rev().collect::<Vec<i64>>(), vec![2, 4, 1, 3, 5]);
+    }
+
+    #[test]
+    fn test_suffix_array_compress() {
+        let sa = SuffixArray::Original(vec![11, 10, 7, 0, 3, 5, 8, 1, 4, 6, 9, 2], 2);
+        let compressed = sa.compress(4);
+
+        assert!(compressed.is_compressed());
+        assert_eq!(compressed.bits_per_value(), 4);
+        assert_eq!(compressed.sample_rate(), 2);
+        assert_eq!(compressed.len(), sa.len());
+        for i in 0..sa.len() {
+            assert_eq!(compressed.get(i), sa.get(i));
+        }
+
+        let decompressed = compressed.decompress();
+        assert!(!decompressed.is_compressed());
+        assert_eq!(decompressed.sample_rate(), 2);
+        assert_eq!(decompressed.iter().collect::<Vec<i64>>(), sa.iter().collect::<Vec<i64>>());
+    }
+
+    #[test]
+    fn test_suffix_array_decompress() {
+        let mut bitarray = BitArray::with_capacity(5, 40);
+        for (i, value) in [5, 3, 1, 4, 2].iter().enumerate() {
+            bitarray.set(i, *value);
+        }
+        let sa = SuffixArray::Compressed(bitarray, 3);
+        let decompressed = sa.decompress();
+
+        assert!(!decompressed.is_compressed());
+        assert_eq!(decompressed.sample_rate(), 3);
+        for i in 0..sa.len() {
+            assert_eq!(decompressed.get(i), sa.get(i));
+        }
+
+        // compressing again with fewer bits gives the same values
+        let compressed = decompressed.compress(3);
+        assert_eq!(compressed.bits_per_value(), 3);
+        assert_eq!(compressed.iter().collect::<Vec<i64>>(), vec![5, 3, 1, 4, 2]);
+    }
+
     #[test]
     fn test_suffix_array_len() {
         let sa = SuffixArray::Original(vec![1, 2, 3, 4, 5], 1);
@@ -170,6 +285,18 @@ mod tests {
         assert_eq!(sa.is_empty(), true);
     }
 
+    #[test]
+    fn test_suffix_array_variant() {
+        let sa = SuffixArray::Original(vec![1, 2, 3, 4, 5], 1);
+        assert!(!sa.is_compressed());
+        assert_eq!(sa.variant_name(), "original");
+
+        let bitarray = BitArray::with_capacity(5, 40);
+        let sa = SuffixArray::Compressed(bitarray, 1);
+        assert!(sa.is_compressed());
+        assert_eq!(sa.variant_name(), "compressed");
+    }
+
     #[test]
     fn test_nullable_is_null() {
         assert_eq!(u32::NULL.is_null(), true);
@@ -1,3 +1,9 @@
+use std::{
+    collections::HashSet,
+    fmt,
+    sync::atomic::{AtomicUsize, Ordering}
+};
+
 use rayon::prelude::*;
 use sa_mappings::proteins::Protein;
 use serde::Serialize;
@@ -8,7 +14,74 @@ use crate::sa_searcher::{SearchAllSuffixesResult, Searcher};
 pub struct SearchResult {
     pub sequence: String,
     pub proteins: Vec<ProteinInfo>,
-    pub cutoff_used: bool
+    pub cutoff_used: bool,
+    /// The amount of distinct accessions in `proteins`, so only the matches within the cutoff are
+    /// counted
+    pub distinct_protein_count: usize,
+    /// The amount of distinct taxa in `proteins`, so only the matches within the cutoff are counted
+    pub distinct_taxon_count: usize,
+    /// The amount of matches of the peptide. If the cutoff is used, this is estimated from the
+    /// search bounds in the suffix array, and it can be much larger than the amount of matches in
+    /// `proteins`.
+    pub total_matches_estimate: usize,
+    /// The positions in the text of the suffixes that match the peptide, only present if requested
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub suffixes: Option<Vec<i64>>
+}
+
+impl SearchResult {
+    /// Creates a search result, counting the distinct proteins and taxa of the matching proteins
+    ///
+    /// # Arguments
+    /// * `sequence` - The peptide that was searched
+    /// * `proteins` - The proteins that match the peptide
+    /// * `cutoff_used` - True if the cutoff was reached while searching the peptide
+    /// * `total_matches_estimate` - The (estimated) amount of matches of the peptide
+    ///
+    /// # Returns
+    ///
+    /// Returns the search result
+    fn new(sequence: String, proteins: Vec<ProteinInfo>, cutoff_used: bool, total_matches_estimate: usize) -> Self {
+        let distinct_protein_count =
+            proteins.iter().map(|protein| protein.uniprot_accession.as_str()).collect::<HashSet<&str>>().len();
+        let distinct_taxon_count = proteins.iter().map(|protein| protein.taxon).collect::<HashSet<u32>>().len();
+
+        SearchResult {
+            sequence,
+            proteins,
+            cutoff_used,
+            distinct_protein_count,
+            distinct_taxon_count,
+            total_matches_estimate,
+            suffixes: None
+        }
+    }
+}
+
+impl fmt::Display for SearchResult {
+    /// Formats the result as the peptide followed by the accessions of the matching proteins
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:", self.sequence)?;
+        for (index, protein) in self.proteins.iter().enumerate() {
+            let separator = if index == 0 { " " } else { ", " };
+            write!(f, "{}{}", separator, protein.uniprot_accession)?;
+        }
+        Ok(())
+    }
+}
+
+/// Struct that contains the search results of a batch of peptides, together with the parameters
+/// that were used to produce them
+#[derive(Debug, Serialize)]
+pub struct BatchSearchResponse {
+    pub results: Vec<SearchResult>,
+    pub cutoff: usize,
+    pub equate_il: bool,
+    pub tryptic: bool,
+    /// The identifier of the index that produced the results, as set in `Searcher::index_id`
+    pub index_id: String,
+    /// The amount of peptides in the request, including the peptides without matches
+    pub total_peptides: usize
 }
 
 /// Struct that represents all information known about a certain protein in our database
@@ -16,7 +89,10 @@ pub struct SearchResult {
 pub struct ProteinInfo {
     pub taxon: u32,
     pub uniprot_accession: String,
-    pub functional_annotations: String
+    pub functional_annotations: String,
+    /// The start positions of the peptide within the protein, only present if requested
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub match_positions: Option<Vec<usize>>
 }
 
 impl From<&Protein> for ProteinInfo {
@@ -24,7 +100,43 @@ impl From<&Protein> for ProteinInfo {
         ProteinInfo {
             taxon: protein.taxon_id,
             uniprot_accession: protein.uniprot_id.clone(),
-            functional_annotations: protein.get_functional_annotations()
+            functional_annotations: protein.get_functional_annotations(),
+            match_positions: None
+        }
+    }
+}
+
+/// The parameters that are used to search a single peptide in the index
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SearchConfig {
+    /// The maximum amount of matches we want to process from the index
+    pub cutoff: usize,
+    /// True if we want to equate I and L during search
+    pub equate_il: bool,
+    /// True if we only want tryptic matches
+    pub tryptic: bool,
+    /// True if we want the positions of the peptide within each protein. If true, every matching
+    /// protein is only reported once, together with all its match positions.
+    pub include_positions: bool,
+    /// The maximum amount of proteins that is reported for a peptide, or None to report all
+    /// matching proteins. In contrast to the `cutoff`, which limits the amount of matching
+    /// suffixes, this limits the amount of proteins, since multiple suffixes can match the same
+    /// protein when `include_positions` is used.
+    pub max_proteins_per_peptide: Option<usize>,
+    /// True if we want the positions in the text of the matching suffixes, which are not limited
+    /// by `max_proteins_per_peptide`
+    pub include_suffixes: bool
+}
+
+impl Default for SearchConfig {
+    fn default() -> Self {
+        SearchConfig {
+            cutoff: usize::MAX,
+            equate_il: false,
+            tryptic: false,
+            include_positions: false,
+            max_proteins_per_peptide: None,
+            include_suffixes: false
         }
     }
 }
@@ -54,25 +166,110 @@ pub fn search_proteins_for_peptide<'a>(
     equate_il: bool,
     tryptic: bool
 ) -> Option<(bool, Vec<&'a Protein>)> {
-    let peptide = peptide.trim_end().to_uppercase();
+    let (cutoff_used, suffixes, _) = search_suffixes_for_peptide(searcher, peptide, cutoff, equate_il, tryptic)?;
+
+    let proteins = searcher.retrieve_proteins(&suffixes);
+
+    Some((cutoff_used, proteins))
+}
+
+/// Normalizes a peptide before it is searched, by removing all whitespace (also whitespace within
+/// the peptide, which occurs in pasted sequences) and converting it to uppercase
+///
+/// # Arguments
+/// * `peptide` - The peptide as provided by the user
+///
+/// # Returns
+///
+/// Returns the normalized peptide, or None if the peptide contains characters other than letters
+/// and whitespace
+pub fn normalize_peptide(peptide: &str) -> Option<String> {
+    let mut normalized = String::with_capacity(peptide.len());
+    for character in peptide.chars().filter(|character| !character.is_whitespace()) {
+        if !character.is_ascii_alphabetic() {
+            return None;
+        }
+        normalized.push(character.to_ascii_uppercase());
+    }
+
+    Some(normalized)
+}
+
+/// Splits a sequence into all its overlapping substrings of length `k`, which can be searched as
+/// peptides in the index
+///
+/// # Arguments
+/// * `sequence` - The sequence that is split into windows
+/// * `k` - The length of the windows
+///
+/// # Returns
+///
+/// Returns an iterator over the windows of length `k`, in the order of their start position. Nothing
+/// is returned if `k` is 0 or larger than the length of the sequence.
+pub fn sliding_windows(sequence: &str, k: usize) -> impl Iterator<Item = &str> {
+    let window_count = if k == 0 || k > sequence.len() { 0 } else { sequence.len() - k + 1 };
+
+    // windows that do not start or end at a character boundary are skipped, these can only occur
+    // in sequences with non-ASCII characters
+    (0..window_count).filter_map(move |start| sequence.get(start..start + k))
+}
+
+/// Searches the `peptide` in the index and retrieves the matching suffixes
+///
+/// # Arguments
+/// * `searcher` - The Searcher which contains the protein database
+/// * `peptide` - The peptide that is being searched in the index
+/// * `cutoff` - The maximum amount of matches we want to process from the index
+/// * `equate_il` - Boolean indicating if we want to equate I and L during search
+/// * `tryptic` - Boolean indicating if we only want tryptic matches.
+///
+/// # Returns
+///
+/// Returns Some if matches are found.
+/// The first argument is true if the cutoff is used, otherwise false
+/// The second argument is a list of all matching suffixes for the peptide
+/// The third argument is the estimated amount of matching suffixes, as described in
+/// `Searcher::search_matching_suffixes_with_estimate`
+/// Returns None if the peptides does not have any matches, if the peptide contains characters that
+/// are not letters, or if the peptide is shorter than the sparseness factor k used in the index
+fn search_suffixes_for_peptide(
+    searcher: &Searcher,
+    peptide: &str,
+    cutoff: usize,
+    equate_il: bool,
+    tryptic: bool
+) -> Option<(bool, Vec<i64>, usize)> {
+    let peptide = normalize_peptide(peptide)?;
 
     // words that are shorter than the sample rate are not searchable
     if peptide.len() < searcher.sa.sample_rate() as usize {
         return None;
     }
 
-    let suffix_search = searcher.search_matching_suffixes(peptide.as_bytes(), cutoff, equate_il, tryptic);
-    let (suffixes, cutoff_used) = match suffix_search {
-        SearchAllSuffixesResult::MaxMatches(matched_suffixes) => Some((matched_suffixes, true)),
-        SearchAllSuffixesResult::SearchResult(matched_suffixes) => Some((matched_suffixes, false)),
+    let (suffix_search, total_matches_estimate) =
+        searcher.search_matching_suffixes_with_estimate(peptide.as_bytes(), cutoff, equate_il, tryptic);
+    match suffix_search {
+        SearchAllSuffixesResult::MaxMatches(matched_suffixes) => Some((true, matched_suffixes, total_matches_estimate)),
+        SearchAllSuffixesResult::SearchResult(matched_suffixes) => {
+            Some((false, matched_suffixes, total_matches_estimate))
+        }
         SearchAllSuffixesResult::NoMatches => None
-    }?;
-
-    let proteins = searcher.retrieve_proteins(&suffixes);
-
-    Some((cutoff_used, proteins))
+    }
 }
 
+/// Searches the `peptide` in the index and builds the search result
+///
+/// # Arguments
+/// * `searcher` - The Searcher which contains the protein database
+/// * `peptide` - The peptide that is being searched in the index
+/// * `cutoff` - The maximum amount of matches we want to process from the index
+/// * `equate_il` - Boolean indicating if we want to equate I and L during search
+/// * `tryptic` - Boolean indicating if we only want tryptic matches.
+///
+/// # Returns
+///
+/// Returns Some with the search result if matches are found, otherwise None. If the cutoff is used,
+/// the total amount of matches is estimated from the search bounds.
 pub fn search_peptide(
     searcher: &Searcher,
     peptide: &str,
@@ -80,13 +277,59 @@ pub fn search_peptide(
     equate_il: bool,
     tryptic: bool
 ) -> Option<SearchResult> {
-    let (cutoff_used, proteins) = search_proteins_for_peptide(searcher, peptide, cutoff, equate_il, tryptic)?;
+    let config = SearchConfig { cutoff, equate_il, tryptic, ..SearchConfig::default() };
+    search_peptide_with_config(searcher, peptide, &config)
+}
 
-    Some(SearchResult {
-        sequence: peptide.to_string(),
-        proteins: proteins.iter().map(|&protein| protein.into()).collect(),
-        cutoff_used
-    })
+/// Searches the `peptide` in the index and builds the search result, using the parameters in
+/// `config`
+///
+/// # Arguments
+/// * `searcher` - The Searcher which contains the protein database
+/// * `peptide` - The peptide that is being searched in the index
+/// * `config` - The parameters of the search
+///
+/// # Returns
+///
+/// Returns Some with the search result if matches are found, otherwise None. If the cutoff is used,
+/// the total amount of matches is estimated from the search bounds. If more proteins match than
+/// `config.max_proteins_per_peptide`, only the first proteins are reported and `cutoff_used` is set.
+pub fn search_peptide_with_config(searcher: &Searcher, peptide: &str, config: &SearchConfig) -> Option<SearchResult> {
+    let (mut cutoff_used, suffixes, bounds_estimate) =
+        search_suffixes_for_peptide(searcher, peptide, config.cutoff, config.equate_il, config.tryptic)?;
+
+    // the suffixes are complete if the cutoff is not used, so they are counted exactly
+    let total_matches_estimate = if cutoff_used { bounds_estimate } else { suffixes.len() };
+
+    // take one protein more than the limit, to know if the limit was reached
+    let max_proteins = config.max_proteins_per_peptide.unwrap_or(usize::MAX);
+    let mut proteins: Vec<ProteinInfo> = if config.include_positions {
+        searcher
+            .retrieve_proteins_with_positions(&suffixes)
+            .into_iter()
+            .take(max_proteins.saturating_add(1))
+            .map(|(protein, match_positions)| ProteinInfo { match_positions: Some(match_positions), ..protein.into() })
+            .collect()
+    } else {
+        searcher
+            .retrieve_proteins(&suffixes)
+            .into_iter()
+            .take(max_proteins.saturating_add(1))
+            .map(|protein| protein.into())
+            .collect()
+    };
+
+    if proteins.len() > max_proteins {
+        proteins.truncate(max_proteins);
+        cutoff_used = true;
+    }
+
+    let mut search_result = SearchResult::new(peptide.to_string(), proteins, cutoff_used, total_matches_estimate);
+    if config.include_suffixes {
+        search_result.suffixes = Some(suffixes);
+    }
+
+    Some(search_result)
 }
 
 /// Searches the list of `peptides` in the index and retrieves all related information about the
@@ -111,15 +354,131 @@ pub fn search_all_peptides(
     equate_il: bool,
     tryptic: bool
 ) -> Vec<SearchResult> {
+    search_all_peptides_with_progress(searcher, peptides, cutoff, equate_il, tryptic, |_| {})
+}
+
+/// Searches the list of `peptides` in the index and retrieves all related information about the
+/// found proteins, while reporting the progress of the search
+///
+/// # Arguments
+/// * `searcher` - The Searcher which contains the protein database
+/// * `peptides` - List of peptides we want to search in the index
+/// * `cutoff` - The maximum amount of matches we want to process from the index
+/// * `equate_il` - Boolean indicating if we want to equate I and L during search
+/// * `tryptic` - Boolean indicating if we only want tryptic matches.
+/// * `progress` - Callback that is called every time a peptide is processed, with the total amount
+///   of peptides that are processed so far. The peptides are searched in parallel, so this callback
+///   can be called from multiple threads at once.
+///
+/// # Returns
+///
+/// Returns the search results for the peptides
+pub fn search_all_peptides_with_progress(
+    searcher: &Searcher,
+    peptides: &Vec<String>,
+    cutoff: usize,
+    equate_il: bool,
+    tryptic: bool,
+    progress: impl Fn(usize) + Sync
+) -> Vec<SearchResult> {
+    let processed_peptides = AtomicUsize::new(0);
+
     peptides
         .par_iter()
-        .filter_map(|peptide| search_peptide(searcher, peptide, cutoff, equate_il, tryptic))
+        .filter_map(|peptide| {
+            let search_result = search_peptide(searcher, peptide, cutoff, equate_il, tryptic);
+            progress(processed_peptides.fetch_add(1, Ordering::Relaxed) + 1);
+            search_result
+        })
         .collect()
 }
 
+/// Lazily searches the list of `peptides` in the index, one peptide at a time. In contrast to
+/// `search_all_peptides`, the results are not collected, so every result can be processed and
+/// dropped before the next peptide is searched
+///
+/// # Arguments
+/// * `searcher` - The Searcher which contains the protein database
+/// * `peptides` - List of peptides we want to search in the index
+/// * `cutoff` - The maximum amount of matches we want to process from the index
+/// * `equate_il` - Boolean indicating if we want to equate I and L during search
+/// * `tryptic` - Boolean indicating if we only want tryptic matches.
+///
+/// # Returns
+///
+/// Returns an iterator over the search results of the peptides that have matches, in the order of
+/// the peptides
+pub fn search_peptides_streaming<'a>(
+    searcher: &'a Searcher,
+    peptides: &'a [String],
+    cutoff: usize,
+    equate_il: bool,
+    tryptic: bool
+) -> impl Iterator<Item = SearchResult> + 'a {
+    peptides
+        .iter()
+        .filter_map(move |peptide| search_peptide(searcher, peptide, cutoff, equate_il, tryptic))
+}
+
+/// Searches the list of `peptides` in the index, and wraps the results in a `BatchSearchResponse`
+/// that describes the used search parameters
+///
+/// # Arguments
+/// * `searcher` - The Searcher which contains the protein database
+/// * `peptides` - List of peptides we want to search in the index
+/// * `cutoff` - The maximum amount of matches we want to process from the index
+/// * `equate_il` - Boolean indicating if we want to equate I and L during search
+/// * `tryptic` - Boolean indicating if we only want tryptic matches.
+///
+/// # Returns
+///
+/// Returns a `BatchSearchResponse` with the search results for the peptides
+pub fn search_all_peptides_batch(
+    searcher: &Searcher,
+    peptides: &[String],
+    cutoff: usize,
+    equate_il: bool,
+    tryptic: bool
+) -> BatchSearchResponse {
+    let config = SearchConfig { cutoff, equate_il, tryptic, ..SearchConfig::default() };
+    search_all_peptides_batch_with_config(searcher, peptides, &config)
+}
+
+/// Searches the list of `peptides` in the index using the parameters in `config`, and wraps the
+/// results in a `BatchSearchResponse` that describes the used search parameters
+///
+/// # Arguments
+/// * `searcher` - The Searcher which contains the protein database
+/// * `peptides` - List of peptides we want to search in the index
+/// * `config` - The parameters of the search
+///
+/// # Returns
+///
+/// Returns a `BatchSearchResponse` with the search results for the peptides
+pub fn search_all_peptides_batch_with_config(
+    searcher: &Searcher,
+    peptides: &[String],
+    config: &SearchConfig
+) -> BatchSearchResponse {
+    let results = peptides.par_iter().filter_map(|peptide| search_peptide_with_config(searcher, peptide, config));
+
+    BatchSearchResponse {
+        results: results.collect(),
+        cutoff: config.cutoff,
+        equate_il: config.equate_il,
+        tryptic: config.tryptic,
+        index_id: searcher.index_id.clone(),
+        total_peptides: peptides.len()
+    }
+}
+
 #[cfg(test)]
 mod tests {
+    use sa_mappings::proteins::{Delimiters, Proteins};
+    use text_compression::ProteinText;
+
     use super::*;
+    use crate::{suffix_to_protein_index::SparseSuffixToProtein, SuffixArray};
 
     fn assert_json_eq(generated_json: &str, expected_json: &str) {
         assert_eq!(
@@ -128,12 +487,61 @@ mod tests {
         );
     }
 
+    fn get_example_searcher() -> Searcher {
+        let text = ProteinText::from_string("AKCD-MAKCAKCD$");
+        let proteins = Proteins {
+            text,
+            proteins: vec![
+                Protein {
+                    uniprot_id: "P12345".to_string(),
+                    taxon_id: 1,
+                    functional_annotations: vec![]
+                },
+                Protein {
+                    uniprot_id: "P54321".to_string(),
+                    taxon_id: 2,
+                    functional_annotations: vec![]
+                },
+            ],
+            accession_index: None,
+            original_sequences: None,
+            delimiters: Delimiters::default(),
+            skipped_accessions: Vec::new()
+        };
+
+        let sa = SuffixArray::Original(vec![13, 4, 6, 9, 0, 8, 11, 2, 12, 3, 7, 10, 1, 5], 1);
+        let suffix_index_to_protein = SparseSuffixToProtein::new(&proteins.text);
+        Searcher::new(sa, proteins, Box::new(suffix_index_to_protein))
+    }
+
+    #[test]
+    fn test_normalize_peptide() {
+        assert_eq!(normalize_peptide("MSKIAS"), Some("MSKIAS".to_string()));
+        assert_eq!(normalize_peptide("  msk ias "), Some("MSKIAS".to_string()));
+        assert_eq!(normalize_peptide("MSK\tIAS\n"), Some("MSKIAS".to_string()));
+        assert_eq!(normalize_peptide(""), Some(String::new()));
+
+        assert_eq!(normalize_peptide("MSK-IAS"), None);
+        assert_eq!(normalize_peptide("MSK1AS"), None);
+        assert_eq!(normalize_peptide("MSKÏAS"), None);
+    }
+
+    #[test]
+    fn test_sliding_windows() {
+        assert_eq!(sliding_windows("ABCDE", 3).collect::<Vec<&str>>(), vec!["ABC", "BCD", "CDE"]);
+        assert_eq!(sliding_windows("ABCDE", 5).collect::<Vec<&str>>(), vec!["ABCDE"]);
+        assert_eq!(sliding_windows("ABCDE", 6).count(), 0);
+        assert_eq!(sliding_windows("ABCDE", 0).count(), 0);
+        assert_eq!(sliding_windows("", 1).count(), 0);
+    }
+
     #[test]
     fn test_serialize_protein_info() {
         let protein_info = ProteinInfo {
             taxon: 1,
             uniprot_accession: "P12345".to_string(),
-            functional_annotations: "GO:0001234;GO:0005678".to_string()
+            functional_annotations: "GO:0001234;GO:0005678".to_string(),
+            match_positions: None
         };
 
         let generated_json = serde_json::to_string(&protein_info).unwrap();
@@ -144,16 +552,298 @@ mod tests {
     }
 
     #[test]
-    fn test_serialize_search_result() {
-        let search_result = SearchResult {
-            sequence: "MSKIAALLPSV".to_string(),
-            proteins: vec![],
-            cutoff_used: true
+    fn test_serialize_protein_info_with_match_positions() {
+        let protein_info = ProteinInfo {
+            taxon: 1,
+            uniprot_accession: "P12345".to_string(),
+            functional_annotations: "GO:0001234;GO:0005678".to_string(),
+            match_positions: Some(vec![2, 5])
+        };
+
+        let generated_json = serde_json::to_string(&protein_info).unwrap();
+        let expected_json = "{\"taxon\":1,\"uniprot_accession\":\"P12345\",\"functional_annotations\":\"GO:0001234;GO:0005678\",\"match_positions\":[2,5]}";
+
+        assert_json_eq(&generated_json, expected_json);
+    }
+
+    #[test]
+    fn test_display_search_result() {
+        let protein_info = |accession: &str| ProteinInfo {
+            taxon: 1,
+            uniprot_accession: accession.to_string(),
+            functional_annotations: String::new(),
+            match_positions: None
+        };
+        let search_result =
+            SearchResult::new("KCD".to_string(), vec![protein_info("P12345"), protein_info("P54321")], false, 2);
+        assert_eq!(search_result.to_string(), "KCD: P12345, P54321");
+
+        let empty_result = SearchResult::new("KCD".to_string(), vec![], false, 0);
+        assert_eq!(empty_result.to_string(), "KCD:");
+    }
+
+    #[test]
+    fn test_search_peptide_match_positions() {
+        let searcher = get_example_searcher();
+
+        let config = SearchConfig { include_positions: true, ..SearchConfig::default() };
+        let search_result = search_peptide_with_config(&searcher, "KCD", &config).unwrap();
+
+        assert_eq!(search_result.proteins.len(), 2);
+        let mut proteins = search_result.proteins;
+        proteins.sort_by(|a, b| a.uniprot_accession.cmp(&b.uniprot_accession));
+        assert_eq!(proteins[0].match_positions, Some(vec![1]));
+        assert_eq!(proteins[1].match_positions, Some(vec![5]));
+
+        let search_result = search_peptide(&searcher, "KCD", usize::MAX, false, false).unwrap();
+        assert!(search_result.proteins.iter().all(|protein| protein.match_positions.is_none()));
+    }
+
+    #[test]
+    fn test_search_peptide_multiple_matches_in_protein() {
+        let text = ProteinText::from_string("MAKCAKCD-KCG$");
+        let proteins = Proteins {
+            text,
+            proteins: vec![
+                Protein {
+                    uniprot_id: "P12345".to_string(),
+                    taxon_id: 1,
+                    functional_annotations: vec![]
+                },
+                Protein {
+                    uniprot_id: "P54321".to_string(),
+                    taxon_id: 2,
+                    functional_annotations: vec![]
+                },
+            ],
+            accession_index: None,
+            original_sequences: None,
+            delimiters: Delimiters::default(),
+            skipped_accessions: Vec::new()
         };
 
+        let sa = SuffixArray::Original(vec![12, 8, 1, 4, 3, 6, 10, 7, 11, 2, 5, 9, 0], 1);
+        let suffix_index_to_protein = SparseSuffixToProtein::new(&proteins.text);
+        let searcher = Searcher::new(sa, proteins, Box::new(suffix_index_to_protein));
+
+        let config = SearchConfig { include_positions: true, ..SearchConfig::default() };
+        let search_result = search_peptide_with_config(&searcher, "KC", &config).unwrap();
+
+        // every protein is only reported once, with all the positions of the peptide in it
+        assert_eq!(search_result.proteins.len(), 2);
+        let mut proteins = search_result.proteins;
+        proteins.sort_by(|a, b| a.uniprot_accession.cmp(&b.uniprot_accession));
+        let mut positions = proteins[0].match_positions.clone().unwrap();
+        positions.sort();
+        assert_eq!(positions, vec![2, 5]);
+        assert_eq!(proteins[1].match_positions, Some(vec![0]));
+    }
+
+    #[test]
+    fn test_search_all_peptides_batch() {
+        let mut searcher = get_example_searcher();
+        searcher.index_id = "example-index".to_string();
+
+        let peptides = vec!["KCD".to_string(), "WWW".to_string()];
+        let response = search_all_peptides_batch(&searcher, &peptides, 50, true, false);
+
+        assert_eq!(response.cutoff, 50);
+        assert!(response.equate_il);
+        assert!(!response.tryptic);
+        assert_eq!(response.index_id, "example-index");
+        assert_eq!(response.total_peptides, 2);
+        assert_eq!(response.results.len(), 1);
+        assert_eq!(response.results[0].sequence, "KCD");
+    }
+
+    #[test]
+    fn test_search_all_peptides_with_progress() {
+        let searcher = get_example_searcher();
+
+        let peptides: Vec<String> = ["KCD", "WWW", "AKC", "MAK", "CAK"].iter().map(|p| p.to_string()).collect();
+        let invocations = AtomicUsize::new(0);
+        let max_progress = AtomicUsize::new(0);
+        let results = search_all_peptides_with_progress(&searcher, &peptides, 10000, false, false, |progress| {
+            invocations.fetch_add(1, Ordering::Relaxed);
+            max_progress.fetch_max(progress, Ordering::Relaxed);
+        });
+
+        assert_eq!(results.len(), 4);
+        assert_eq!(invocations.load(Ordering::Relaxed), peptides.len());
+        assert_eq!(max_progress.load(Ordering::Relaxed), peptides.len());
+    }
+
+    #[test]
+    fn test_search_peptides_streaming() {
+        let searcher = get_example_searcher();
+
+        let peptides: Vec<String> = ["KCD", "WWW", "AKC", "MAK", "CAK"].iter().map(|p| p.to_string()).collect();
+        let streamed: Vec<String> = search_peptides_streaming(&searcher, &peptides, 10000, false, false)
+            .map(|result| serde_json::to_string(&result).unwrap())
+            .collect();
+        let collected: Vec<String> = search_all_peptides(&searcher, &peptides, 10000, false, false)
+            .iter()
+            .map(|result| serde_json::to_string(result).unwrap())
+            .collect();
+
+        assert_eq!(streamed.len(), 4);
+        assert_eq!(streamed, collected);
+    }
+
+    #[test]
+    fn test_serialize_search_result() {
+        let search_result = SearchResult::new("MSKIAALLPSV".to_string(), vec![], true, 0);
+
         let generated_json = serde_json::to_string(&search_result).unwrap();
-        let expected_json = "{\"sequence\":\"MSKIAALLPSV\",\"proteins\":[],\"cutoff_used\":true}";
+        let expected_json = "{\"sequence\":\"MSKIAALLPSV\",\"proteins\":[],\"cutoff_used\":true,\"distinct_protein_count\":0,\"distinct_taxon_count\":0,\"total_matches_estimate\":0}";
 
         assert_json_eq(&generated_json, expected_json);
     }
+
+    #[test]
+    fn test_search_peptide_distinct_counts() {
+        let text = ProteinText::from_string("AKCD-MKCDAKCD-KCDA$");
+        let proteins = Proteins {
+            text,
+            proteins: vec![
+                Protein {
+                    uniprot_id: "P12345".to_string(),
+                    taxon_id: 1,
+                    functional_annotations: vec![]
+                },
+                Protein {
+                    uniprot_id: "P54321".to_string(),
+                    taxon_id: 2,
+                    functional_annotations: vec![]
+                },
+                Protein {
+                    uniprot_id: "P11111".to_string(),
+                    taxon_id: 2,
+                    functional_annotations: vec![]
+                },
+            ],
+            accession_index: None,
+            original_sequences: None,
+            delimiters: Delimiters::default(),
+            skipped_accessions: Vec::new()
+        };
+
+        let sa = SuffixArray::Original(vec![18, 13, 4, 17, 9, 0, 11, 2, 15, 7, 12, 3, 16, 8, 10, 1, 14, 6, 5], 1);
+        let suffix_index_to_protein = SparseSuffixToProtein::new(&proteins.text);
+        let searcher = Searcher::new(sa, proteins, Box::new(suffix_index_to_protein));
+
+        // the second protein contains the peptide twice, and two proteins share a taxon
+        let search_result = search_peptide(&searcher, "KCD", usize::MAX, false, false).unwrap();
+        assert_eq!(search_result.proteins.len(), 4);
+        assert_eq!(search_result.distinct_protein_count, 3);
+        assert_eq!(search_result.distinct_taxon_count, 2);
+
+        // only the matches within the cutoff are counted
+        let search_result = search_peptide(&searcher, "KCD", 1, false, false).unwrap();
+        assert_eq!(search_result.distinct_protein_count, 1);
+        assert_eq!(search_result.distinct_taxon_count, 1);
+    }
+
+    #[test]
+    fn test_search_peptide_total_matches_estimate() {
+        let proteins = Proteins {
+            text: ProteinText::from_string("AAAAA-AAAAA$"),
+            proteins: vec![
+                Protein {
+                    uniprot_id: "P12345".to_string(),
+                    taxon_id: 1,
+                    functional_annotations: vec![]
+                },
+                Protein {
+                    uniprot_id: "P54321".to_string(),
+                    taxon_id: 2,
+                    functional_annotations: vec![]
+                },
+            ],
+            accession_index: None,
+            original_sequences: None,
+            delimiters: Delimiters::default(),
+            skipped_accessions: Vec::new()
+        };
+        let searcher = Searcher::from_proteins(proteins, 1).unwrap();
+
+        // the cutoff only keeps 2 matches, but the search bounds contain all 10 matches
+        let search_result = search_peptide(&searcher, "A", 2, false, false).unwrap();
+        assert!(search_result.cutoff_used);
+        assert_eq!(search_result.proteins.len(), 2);
+        assert_eq!(search_result.total_matches_estimate, 10);
+
+        let search_result = search_peptide(&searcher, "AAAA", usize::MAX, false, false).unwrap();
+        assert!(!search_result.cutoff_used);
+        assert_eq!(search_result.total_matches_estimate, 4);
+    }
+
+    #[test]
+    fn test_search_peptide_max_proteins_per_peptide() {
+        let protein = |accession: &str, taxon_id: u32| Protein {
+            uniprot_id: accession.to_string(),
+            taxon_id,
+            functional_annotations: vec![]
+        };
+        let proteins = Proteins {
+            text: ProteinText::from_string("MKCD-AKCD-GKCD-TKCD-VKCD-WWW$"),
+            proteins: vec![
+                protein("P00001", 1),
+                protein("P00002", 2),
+                protein("P00003", 3),
+                protein("P00004", 4),
+                protein("P00005", 5),
+                protein("P00006", 6),
+            ],
+            accession_index: None,
+            original_sequences: None,
+            delimiters: Delimiters::default(),
+            skipped_accessions: Vec::new()
+        };
+        let searcher = Searcher::from_proteins(proteins, 1).unwrap();
+
+        let search_result = search_peptide(&searcher, "KCD", usize::MAX, false, false).unwrap();
+        assert!(!search_result.cutoff_used);
+        assert_eq!(search_result.proteins.len(), 5);
+
+        let config = SearchConfig { max_proteins_per_peptide: Some(2), ..SearchConfig::default() };
+        let search_result = search_peptide_with_config(&searcher, "KCD", &config).unwrap();
+        assert!(search_result.cutoff_used);
+        assert_eq!(search_result.proteins.len(), 2);
+        assert_eq!(search_result.distinct_protein_count, 2);
+        assert_eq!(search_result.total_matches_estimate, 5);
+
+        let config = SearchConfig { max_proteins_per_peptide: Some(2), include_positions: true, ..config };
+        let search_result = search_peptide_with_config(&searcher, "KCD", &config).unwrap();
+        assert!(search_result.cutoff_used);
+        assert_eq!(search_result.proteins.len(), 2);
+
+        // the limit is not reached if exactly as many proteins match
+        let config = SearchConfig { max_proteins_per_peptide: Some(5), ..SearchConfig::default() };
+        let search_result = search_peptide_with_config(&searcher, "KCD", &config).unwrap();
+        assert!(!search_result.cutoff_used);
+        assert_eq!(search_result.proteins.len(), 5);
+    }
+
+    #[test]
+    fn test_search_peptide_include_suffixes() {
+        let searcher = get_example_searcher();
+
+        let config = SearchConfig { include_suffixes: true, ..SearchConfig::default() };
+        let search_result = search_peptide_with_config(&searcher, "KC", &config).unwrap();
+
+        assert_eq!(
+            SearchAllSuffixesResult::SearchResult(search_result.suffixes.unwrap()),
+            searcher.search_matching_suffixes(b"KC", usize::MAX, false, false)
+        );
+        assert_eq!(search_result.proteins.len(), 3);
+
+        // the suffixes are only included if requested
+        assert!(search_peptide(&searcher, "KC", usize::MAX, false, false).unwrap().suffixes.is_none());
+
+        let response = search_all_peptides_batch_with_config(&searcher, &["KCD".to_string()], &config);
+        let mut suffixes = response.results[0].suffixes.clone().unwrap();
+        suffixes.sort();
+        assert_eq!(suffixes, vec![1, 10]);
+    }
 }
@@ -1,6 +1,13 @@
-use std::{cmp::min, ops::Deref};
+use std::{
+    cmp::{min, Ordering},
+    collections::{BTreeMap, HashMap, HashSet},
+    error::Error,
+    fmt,
+    mem::size_of,
+    ops::{Deref, DerefMut}
+};
 
-use sa_mappings::proteins::{Protein, Proteins, SEPARATION_CHARACTER, TERMINATION_CHARACTER};
+use sa_mappings::proteins::{Protein, Proteins};
 use text_compression::ProteinTextSlice;
 
 use crate::{
@@ -72,11 +79,115 @@ impl PartialEq for SearchAllSuffixesResult {
     }
 }
 
+/// Enum representing how unique a peptide is, based on the taxa of the proteins it matches
+/// The index does not contain a taxonomy, so the uniqueness is expressed in taxon ids. A caller
+/// with a taxonomy can compute the lowest common ancestor of the taxa of a shared peptide.
+#[derive(Debug, PartialEq)]
+pub enum Uniqueness {
+    /// The peptide does not match any protein
+    NoMatches,
+    /// All the matching proteins belong to this taxon
+    Unique(u32),
+    /// The matching proteins belong to multiple taxa, which are sorted in ascending order
+    Shared(Vec<u32>)
+}
+
+/// Error returned by `Searcher::verify` when the suffix array is not consistent with the text
+#[derive(Debug, PartialEq)]
+pub enum VerifyError {
+    /// The suffix at `index` in the suffix array points outside of the text
+    SuffixOutOfBounds { index: usize, suffix: i64 },
+    /// The suffix at `index` in the suffix array is larger than the suffix at `index + 1`
+    OutOfOrder { index: usize }
+}
+
+impl fmt::Display for VerifyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            VerifyError::SuffixOutOfBounds { index, suffix } => {
+                write!(f, "The suffix {} at index {} of the suffix array is out of bounds", suffix, index)
+            }
+            VerifyError::OutOfOrder { index } => {
+                write!(f, "The suffixes at index {} and {} of the suffix array are not sorted", index, index + 1)
+            }
+        }
+    }
+}
+
+impl Error for VerifyError {}
+
+/// Set of residues that are allowed at a single position of a search profile
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ResidueSet {
+    /// Bitmask where bit `i` is set if the residue `b'A' + i` is allowed
+    mask: u32
+}
+
+impl ResidueSet {
+    /// Creates a new `ResidueSet` containing the given residues
+    ///
+    /// # Arguments
+    /// * `residues` - The residues allowed at this position, non-alphabetic characters are ignored
+    ///
+    /// # Returns
+    ///
+    /// Returns a new `ResidueSet`
+    pub fn new(residues: &[u8]) -> Self {
+        let mask = residues
+            .iter()
+            .filter(|residue| residue.is_ascii_alphabetic())
+            .fold(0, |mask, residue| mask | 1 << (residue.to_ascii_uppercase() - b'A'));
+        Self { mask }
+    }
+
+    /// Checks if a residue is part of this set
+    ///
+    /// # Arguments
+    /// * `residue` - The residue that is checked
+    /// * `equate_il` - True if I and L should be considered the same residue
+    ///
+    /// # Returns
+    ///
+    /// Returns true if the residue is allowed at this position
+    pub fn contains(&self, residue: u8, equate_il: bool) -> bool {
+        if !residue.is_ascii_uppercase() {
+            return false;
+        }
+
+        if equate_il && (residue == b'I' || residue == b'L') {
+            return self.mask & (1 << (b'I' - b'A') | 1 << (b'L' - b'A')) != 0;
+        }
+
+        self.mask & 1 << (residue - b'A') != 0
+    }
+
+    /// Returns the residue if this set only allows a single residue. If `equate_il` is true, a set
+    /// containing only I and L is also considered to be a single residue.
+    ///
+    /// # Arguments
+    /// * `equate_il` - True if I and L should be considered the same residue
+    ///
+    /// # Returns
+    ///
+    /// Returns the only residue in this set, or None if this set contains zero or multiple residues
+    fn single_residue(&self, equate_il: bool) -> Option<u8> {
+        let il_mask = 1 << (b'I' - b'A') | 1 << (b'L' - b'A');
+        let mask =
+            if equate_il && self.mask & il_mask != 0 { self.mask & !il_mask | 1 << (b'I' - b'A') } else { self.mask };
+
+        if mask.count_ones() == 1 {
+            Some(b'A' + mask.trailing_zeros() as u8)
+        } else {
+            None
+        }
+    }
+}
+
 pub struct SparseSearcher(Searcher);
 
 impl SparseSearcher {
     pub fn new(sa: SuffixArray, proteins: Proteins) -> Self {
-        let suffix_index_to_protein = SparseSuffixToProtein::new(&proteins.text);
+        let suffix_index_to_protein = SparseSuffixToProtein::new_with_delimiters(&proteins.text, &proteins.delimiters);
         let searcher = Searcher::new(sa, proteins, Box::new(suffix_index_to_protein));
         Self(searcher)
     }
@@ -90,11 +201,17 @@ impl Deref for SparseSearcher {
     }
 }
 
+impl DerefMut for SparseSearcher {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
 pub struct DenseSearcher(Searcher);
 
 impl DenseSearcher {
     pub fn new(sa: SuffixArray, proteins: Proteins) -> Self {
-        let suffix_index_to_protein = DenseSuffixToProtein::new(&proteins.text);
+        let suffix_index_to_protein = DenseSuffixToProtein::new_with_delimiters(&proteins.text, &proteins.delimiters);
         let searcher = Searcher::new(sa, proteins, Box::new(suffix_index_to_protein));
         Self(searcher)
     }
@@ -108,6 +225,12 @@ impl Deref for DenseSearcher {
     }
 }
 
+impl DerefMut for DenseSearcher {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
 /// Struct that contains all the elements needed to search a peptide in the suffix array
 /// This struct also contains all the functions used for search
 ///
@@ -120,10 +243,25 @@ impl Deref for DenseSearcher {
 ///   taxonomic analysis provided by Unipept
 /// * `function_aggregator` - Object used to retrieve the functional annotations and to calculate
 ///   the functional analysis provided by Unipept
+/// * `max_result_bytes` - The maximum amount of memory used by the matching suffixes of a single
+///   search, on top of the maximum amount of matches of that search
+/// * `sort_matches` - True if the matching suffixes are sorted ascending, so the order of the
+///   matches does not depend on the sparseness factor of the suffix array
+/// * `inverse_sa` - Optional inverse suffix array, mapping a position in the text to the index of
+///   its suffix in the suffix array, only present after calling `build_inverse`
+/// * `il_equated` - True if the suffix array was built with every L in the text translated to an I.
+///   A suffix array that distinguishes I and L can not be searched with I and L equated, so I and L
+///   are always distinguished when searching it.
 pub struct Searcher {
     pub sa: SuffixArray,
     pub proteins: Proteins,
-    pub suffix_index_to_protein: Box<dyn SuffixToProteinIndex>
+    pub suffix_index_to_protein: Box<dyn SuffixToProteinIndex>,
+    pub max_result_bytes: usize,
+    pub sort_matches: bool,
+    pub inverse_sa: Option<Vec<usize>>,
+    pub il_equated: bool,
+    /// Identifies the index in the search results, so they can be cached per index
+    pub index_id: String
 }
 
 impl Searcher {
@@ -143,13 +281,222 @@ impl Searcher {
     /// # Returns
     ///
     /// Returns a new Searcher object
+    ///
+    /// # Panics
+    ///
+    /// Panics if the sample rate of the suffix array is 0, since nothing can be found in such an
+    /// index
     pub fn new(sa: SuffixArray, proteins: Proteins, suffix_index_to_protein: Box<dyn SuffixToProteinIndex>) -> Self {
-        Self { sa, proteins, suffix_index_to_protein }
+        assert!(sa.sample_rate() >= 1, "The sample rate of the suffix array should be at least 1, but it is 0");
+
+        Self {
+            sa,
+            proteins,
+            suffix_index_to_protein,
+            max_result_bytes: usize::MAX,
+            sort_matches: false,
+            inverse_sa: None,
+            il_equated: true,
+            index_id: String::new()
+        }
+    }
+
+    /// Creates a new Searcher object from the proteins, by building the (sparse) suffix array in
+    /// memory. The suffix array is built in the same way as the builder does, with the L's in the
+    /// text translated to I's, but by sorting the suffixes. This is only meant for small sets of
+    /// proteins, such as in tests or when embedding the index.
+    ///
+    /// # Arguments
+    /// * `proteins` - List of all the proteins where the suffix array is build on
+    /// * `sparseness_factor` - The sparseness factor used on the suffix array
+    ///
+    /// # Returns
+    ///
+    /// Returns a new Searcher object
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the sparseness factor is 0
+    pub fn from_proteins(proteins: Proteins, sparseness_factor: u8) -> Result<Self, Box<dyn Error>> {
+        if sparseness_factor == 0 {
+            return Err("The sparseness factor should be at least 1".into());
+        }
+
+        let text: Vec<u8> =
+            proteins.text.iter().map(|character| if character == b'L' { b'I' } else { character }).collect();
+        let mut sa: Vec<i64> = (0..text.len() as i64).step_by(sparseness_factor as usize).collect();
+        sa.sort_unstable_by(|&a, &b| text[a as usize..].cmp(&text[b as usize..]));
+
+        let suffix_index_to_protein = SparseSuffixToProtein::new_with_delimiters(&proteins.text, &proteins.delimiters);
+        Ok(Self::new(SuffixArray::Original(sa, sparseness_factor), proteins, Box::new(suffix_index_to_protein)))
+    }
+
+    /// Builds the inverse suffix array, which is used by `text_position_to_rank`. The inverse is not
+    /// built by default to avoid the extra memory usage.
+    ///
+    /// # Returns
+    ///
+    /// Returns () if the inverse suffix array was built
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the suffix array is sparse, since the positions that are not sampled do
+    /// not have an index in the suffix array
+    pub fn build_inverse(&mut self) -> Result<(), Box<dyn Error>> {
+        if self.sa.sample_rate() != 1 {
+            return Err("The inverse suffix array can only be built from a suffix array that is not sparse".into());
+        }
+
+        let mut inverse_sa = vec![0; self.sa.len()];
+        for (sa_index, suffix) in self.sa.iter().enumerate() {
+            inverse_sa[suffix as usize] = sa_index;
+        }
+
+        self.inverse_sa = Some(inverse_sa);
+        Ok(())
+    }
+
+    /// Returns the index in the suffix array of the suffix starting at the given position in the
+    /// text
+    ///
+    /// # Arguments
+    /// * `pos` - The position in the text
+    ///
+    /// # Returns
+    ///
+    /// Returns the index of the suffix in the suffix array, or None if the position is out of bounds
+    /// or if the inverse suffix array was not built using `build_inverse`
+    pub fn text_position_to_rank(&self, pos: usize) -> Option<usize> {
+        self.inverse_sa.as_ref()?.get(pos).copied()
+    }
+
+    /// Checks that a sample of adjacent entries in the suffix array are sorted, to detect an index
+    /// that does not belong to the text or that was corrupted. The sampled pairs are picked with a
+    /// pseudo-random generator seeded by the length of the suffix array, so the check is
+    /// reproducible. If `sample` is at least the number of adjacent pairs, every pair is checked.
+    ///
+    /// # Arguments
+    /// * `sample` - The number of adjacent pairs in the suffix array that are checked
+    ///
+    /// # Returns
+    ///
+    /// Returns () if all the sampled pairs are sorted
+    ///
+    /// # Errors
+    ///
+    /// Returns a `VerifyError` for the first sampled pair that points outside of the text or that is
+    /// not sorted
+    pub fn verify(&self, sample: usize) -> Result<(), VerifyError> {
+        let pair_count = self.sa.len().saturating_sub(1);
+        if sample >= pair_count {
+            return (0..pair_count).try_for_each(|index| self.verify_pair(index));
+        }
+
+        // xorshift64, the state should never be 0
+        let mut state = (self.sa.len() as u64) | 1;
+        for _ in 0..sample {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            self.verify_pair((state % pair_count as u64) as usize)?;
+        }
+
+        Ok(())
+    }
+
+    /// Checks that the suffixes at `index` and `index + 1` in the suffix array are in bounds and
+    /// sorted
+    ///
+    /// # Arguments
+    /// * `index` - The index of the first suffix of the pair in the suffix array
+    ///
+    /// # Returns
+    ///
+    /// Returns () if the pair is sorted
+    ///
+    /// # Errors
+    ///
+    /// Returns a `VerifyError` if one of the suffixes is out of bounds or if the pair is not sorted
+    fn verify_pair(&self, index: usize) -> Result<(), VerifyError> {
+        let text_length = self.proteins.text.len() as i64;
+        for sa_index in [index, index + 1] {
+            let suffix = self.sa.get(sa_index);
+            if suffix < 0 || suffix >= text_length {
+                return Err(VerifyError::SuffixOutOfBounds { index: sa_index, suffix });
+            }
+        }
+
+        if self.compare_suffixes(self.sa.get(index), self.sa.get(index + 1)) == Ordering::Greater {
+            return Err(VerifyError::OutOfOrder { index });
+        }
+
+        Ok(())
+    }
+
+    /// Returns the character as it is sorted in the suffix array, which is an I for an L if the
+    /// suffix array was built with I and L equated
+    ///
+    /// # Arguments
+    /// * `character` - The character of the text or the search string
+    ///
+    /// # Returns
+    ///
+    /// Returns the character used to compare with the suffixes in the suffix array
+    #[inline]
+    fn sorted_character(&self, character: u8) -> u8 {
+        if self.il_equated && character == b'L' {
+            b'I'
+        } else {
+            character
+        }
+    }
+
+    /// Returns if I and L can be equated while searching the suffix array, which is only possible if
+    /// the suffix array was built with I and L equated
+    ///
+    /// # Arguments
+    /// * `equate_il` - True if I and L should be equated during search
+    ///
+    /// # Returns
+    ///
+    /// Returns true if I and L are equated during search
+    #[inline]
+    fn searched_equate_il(&self, equate_il: bool) -> bool {
+        equate_il && self.il_equated
+    }
+
+    /// Compares 2 suffixes of the text in the same way as the suffix array is sorted, with every L
+    /// replaced by an I if the suffix array was built with I and L equated
+    ///
+    /// # Arguments
+    /// * `first` - The start of the first suffix in the text
+    /// * `second` - The start of the second suffix in the text
+    ///
+    /// # Returns
+    ///
+    /// Returns the ordering of the first suffix compared to the second suffix
+    fn compare_suffixes(&self, first: i64, second: i64) -> Ordering {
+        let text_length = self.proteins.text.len();
+        let (mut first, mut second) = (first as usize, second as usize);
+        while first < text_length && second < text_length {
+            let ordering = self
+                .sorted_character(self.proteins.text.get(first))
+                .cmp(&self.sorted_character(self.proteins.text.get(second)));
+            if ordering != Ordering::Equal {
+                return ordering;
+            }
+
+            first += 1;
+            second += 1;
+        }
+
+        // the shortest suffix is the smallest if one is a prefix of the other
+        (text_length - first).cmp(&(text_length - second))
     }
 
     /// Compares the `search_string` to the `suffix`
-    /// During search this function performs extra logic since the suffix array is build with I ==
-    /// L, while ` self.proteins.input_string` is the original text where I != L
+    /// During search this function performs extra logic if the suffix array is build with I == L,
+    /// while ` self.proteins.input_string` is the original text where I != L
     ///
     /// # Arguments
     /// * `search_string` - The string/peptide being searched in the suffix array
@@ -178,9 +525,8 @@ impl Searcher {
         // match as long as possible
         while index_in_search_string < search_string.len()
             && index_in_suffix < self.proteins.text.len()
-            && (search_string[index_in_search_string] == self.proteins.text.get(index_in_suffix)
-                || (search_string[index_in_search_string] == b'L' && self.proteins.text.get(index_in_suffix) == b'I')
-                || (search_string[index_in_search_string] == b'I' && self.proteins.text.get(index_in_suffix) == b'L'))
+            && self.sorted_character(search_string[index_in_search_string])
+                == self.sorted_character(self.proteins.text.get(index_in_suffix))
         {
             index_in_suffix += 1;
             index_in_search_string += 1;
@@ -191,19 +537,10 @@ impl Searcher {
             if index_in_search_string == search_string.len() {
                 is_cond_or_equal = true
             } else if index_in_suffix < self.proteins.text.len() {
-                // in our index every L was replaced by a I, so we need to replace them if we want
+                // if every L was replaced by a I in our index, we need to replace them if we want
                 // to search in the right direction
-                let peptide_char = if search_string[index_in_search_string] == b'L' {
-                    b'I'
-                } else {
-                    search_string[index_in_search_string]
-                };
-
-                let protein_char = if self.proteins.text.get(index_in_suffix) == b'L' {
-                    b'I'
-                } else {
-                    self.proteins.text.get(index_in_suffix)
-                };
+                let peptide_char = self.sorted_character(search_string[index_in_search_string]);
+                let protein_char = self.sorted_character(self.proteins.text.get(index_in_suffix));
 
                 is_cond_or_equal = condition_check(peptide_char, protein_char);
             }
@@ -217,14 +554,17 @@ impl Searcher {
     /// # Arguments
     /// * `bound` - Indicates if we are searching the minimum or maximum bound
     /// * `search_string` - The string/peptide we are searching in the suffix array
+    /// * `start_bound` - The index in the suffix array from which the search starts, all suffixes
+    ///   before this index should be smaller than `search_string`
     ///
     /// # Returns
     ///
     /// The first argument is true if a match was found
     /// The second argument indicates the index of the minimum or maximum bound for the match
     /// (depending on `bound`)
-    fn binary_search_bound(&self, bound: BoundSearch, search_string: &[u8]) -> (bool, usize) {
-        let mut left: usize = 0;
+    fn binary_search_bound(&self, bound: BoundSearch, search_string: &[u8], start_bound: usize) -> (bool, usize) {
+        // the suffix before the start bound is known to be smaller, so it does not need to be compared
+        let mut left: usize = start_bound.saturating_sub(1);
         let mut right: usize = self.sa.len();
         let mut lcp_left: usize = 0;
         let mut lcp_right: usize = 0;
@@ -277,19 +617,62 @@ impl Searcher {
     /// Returns the minimum and maximum bound of all matches in the suffix array, or `NoMatches` if
     /// no matches were found
     pub fn search_bounds(&self, search_string: &[u8]) -> BoundSearchResult {
-        let (found_min, min_bound) = self.binary_search_bound(Minimum, search_string);
+        self.search_bounds_from(search_string, 0).0
+    }
+
+    /// Searches for the minimum and maximum bound for a string in the suffix array, starting from
+    /// the given index in the suffix array
+    ///
+    /// # Arguments
+    /// * `search_string` - The string/peptide we are searching in the suffix array
+    /// * `start_bound` - The index in the suffix array from which the search starts, all suffixes
+    ///   before this index should be smaller than `search_string`
+    ///
+    /// # Returns
+    ///
+    /// Returns the minimum and maximum bound of all matches in the suffix array, or `NoMatches` if
+    /// no matches were found, together with the minimum bound. The minimum bound is also returned
+    /// if nothing was found, since all suffixes before it are smaller than `search_string`.
+    fn search_bounds_from(&self, search_string: &[u8], start_bound: usize) -> (BoundSearchResult, usize) {
+        let (found_min, min_bound) = self.binary_search_bound(Minimum, search_string, start_bound);
 
         if !found_min {
-            return BoundSearchResult::NoMatches;
+            return (BoundSearchResult::NoMatches, min_bound);
         }
 
-        let (_, max_bound) = self.binary_search_bound(Maximum, search_string);
+        let (_, max_bound) = self.binary_search_bound(Maximum, search_string, start_bound);
+
+        (BoundSearchResult::SearchResult((min_bound, max_bound + 1)), min_bound)
+    }
 
-        BoundSearchResult::SearchResult((min_bound, max_bound + 1))
+    /// Resolves the position in the text where a match starts, for a suffix found while searching
+    /// the search string without its first `skip` characters
+    /// A sparse suffix array only contains the suffixes that start at a multiple of `sample_rate`,
+    /// so a match that does not start at such a position is found by searching the search string
+    /// without its first `skip` characters, with `skip` ranging from 0 to `sample_rate - 1`. The
+    /// match then starts `skip` characters before the found suffix. For a dense suffix array, `skip`
+    /// is always 0 and the match starts at the found suffix.
+    ///
+    /// # Arguments
+    /// * `sa_index` - The index in the suffix array of the found suffix
+    /// * `skip` - The amount of characters that were skipped at the start of the search string
+    ///
+    /// # Returns
+    ///
+    /// Returns the position in the text where the match starts, or None if the found suffix starts
+    /// less than `skip` characters from the start of the text
+    #[inline]
+    pub fn resolve_suffix(&self, sa_index: usize, skip: usize) -> Option<i64> {
+        let suffix = self.sa.get(sa_index);
+        if suffix >= skip as i64 {
+            Some(suffix - skip as i64)
+        } else {
+            None
+        }
     }
 
     /// Searches for the suffixes matching a search string
-    /// During search I and L can be equated
+    /// During search I and L can be equated, unless the suffix array distinguishes I and L
     ///
     /// # Arguments
     /// * `search_string` - The string/peptide we are searching in the suffix array
@@ -300,7 +683,9 @@ impl Searcher {
     ///
     /// # Returns
     ///
-    /// Returns all the matching suffixes
+    /// Returns all the matching suffixes, sorted ascending if `sort_matches` is set. Returns
+    /// `MaxMatches` if `max_matches` is reached, or if the matching suffixes would use more than
+    /// `max_result_bytes` of memory.
     #[inline]
     pub fn search_matching_suffixes(
         &self,
@@ -309,7 +694,107 @@ impl Searcher {
         equate_il: bool,
         tryptic: bool
     ) -> SearchAllSuffixesResult {
+        self.search_matching_suffixes_from(search_string, max_matches, equate_il, tryptic, 0).0
+    }
+
+    /// Searches for the suffixes matching a search string, like `search_matching_suffixes`, and
+    /// estimates the total amount of matches from the widths of the search bounds
+    /// The estimate is the sum of the widths of the search bounds of every skipped prefix, so it is
+    /// also computed for the matches that are not retrieved when `max_matches` is reached. It ignores
+    /// the I and L filter and the tryptic filter: if the suffix array is built with I and L equated,
+    /// the estimate counts the matches with I and L equated, and it includes matches that are not
+    /// tryptic. For a sparse suffix array, it can also include suffixes of which the skipped prefix
+    /// does not match.
+    ///
+    /// # Arguments
+    /// * `search_string` - The string/peptide we are searching in the suffix array
+    /// * `max_matches` - The maximum amount of matches processed, if more matches are found we
+    ///   don't process them
+    /// * `equate_il` - True if we want to equate I and L during search, otherwise false
+    /// * `tryptic` - Boolean indicating if we only want tryptic matches.
+    ///
+    /// # Returns
+    ///
+    /// Returns the matching suffixes as described in `search_matching_suffixes`, together with the
+    /// estimated amount of matching suffixes
+    pub fn search_matching_suffixes_with_estimate(
+        &self,
+        search_string: &[u8],
+        max_matches: usize,
+        equate_il: bool,
+        tryptic: bool
+    ) -> (SearchAllSuffixesResult, usize) {
+        let (result, _, total_matches_estimate) =
+            self.search_matching_suffixes_from(search_string, max_matches, equate_il, tryptic, 0);
+        (result, total_matches_estimate)
+    }
+
+    /// Searches for the suffixes matching multiple search strings, which are searched in sorted
+    /// order. Since a search string is never smaller than the previous one, the search for its
+    /// bounds can start from the minimum bound of the previous search string instead of from the
+    /// start of the suffix array. This is only done for the search strings themselves, not for the
+    /// shorter search strings that are searched in a sparse suffix array.
+    ///
+    /// # Arguments
+    /// * `peptides` - The strings/peptides we are searching in the suffix array
+    /// * `equate_il` - True if we want to equate I and L during search, otherwise false
+    ///
+    /// # Returns
+    ///
+    /// Returns all the matching suffixes of every search string, in the same order as `peptides`
+    pub fn search_sorted_batch(&self, peptides: &[Vec<u8>], equate_il: bool) -> Vec<SearchAllSuffixesResult> {
+        // the search strings are sorted in the same way as the suffix array
+        let mut order: Vec<usize> = (0..peptides.len()).collect();
+        order.sort_by_cached_key(|&index| {
+            peptides[index].iter().map(|&c| self.sorted_character(c)).collect::<Vec<u8>>()
+        });
+
+        let mut results: Vec<Option<SearchAllSuffixesResult>> = (0..peptides.len()).map(|_| None).collect();
+        let mut start_bound = 0;
+        for index in order {
+            let (result, min_bound, _) =
+                self.search_matching_suffixes_from(&peptides[index], usize::MAX, equate_il, false, start_bound);
+
+            // an empty search string is never found, so its minimum bound is not a valid start
+            if !peptides[index].is_empty() {
+                start_bound = min_bound;
+            }
+            results[index] = Some(result);
+        }
+
+        results.into_iter().map(|result| result.unwrap()).collect()
+    }
+
+    /// Searches for the suffixes matching a search string, starting the search for the bounds of
+    /// the full search string from the given index in the suffix array
+    ///
+    /// # Arguments
+    /// * `search_string` - The string/peptide we are searching in the suffix array
+    /// * `max_matches` - The maximum amount of matches processed, if more matches are found we
+    ///   don't process them
+    /// * `equate_il` - True if we want to equate I and L during search, otherwise false
+    /// * `tryptic` - Boolean indicating if we only want tryptic matches.
+    /// * `start_bound` - The index in the suffix array from which the search starts, all suffixes
+    ///   before this index should be smaller than `search_string`
+    ///
+    /// # Returns
+    ///
+    /// Returns the matching suffixes as described in `search_matching_suffixes`, together with the
+    /// minimum bound of the full search string in the suffix array and the estimated amount of
+    /// matching suffixes as described in `search_matching_suffixes_with_estimate`
+    fn search_matching_suffixes_from(
+        &self,
+        search_string: &[u8],
+        max_matches: usize,
+        equate_il: bool,
+        tryptic: bool,
+        start_bound: usize
+    ) -> (SearchAllSuffixesResult, usize, usize) {
+        let equate_il = self.searched_equate_il(equate_il);
+        let max_matches = max_matches.min(self.max_result_bytes / size_of::<i64>()).max(1);
         let mut matching_suffixes: Vec<i64> = vec![];
+        let mut max_matches_reached = false;
+        let mut total_matches_estimate = 0;
         let mut il_locations = vec![];
         for (i, &character) in search_string.iter().enumerate() {
             if character == b'I' || character == b'L' {
@@ -317,6 +802,7 @@ impl Searcher {
             }
         }
 
+        let mut min_bound_full = start_bound;
         let mut skip: usize = 0;
         while skip < self.sa.sample_rate() as usize {
             let mut il_locations_start = 0;
@@ -326,19 +812,27 @@ impl Searcher {
             let il_locations_current_suffix = &il_locations[il_locations_start..];
             let current_search_string_prefix = &search_string[..skip];
             let current_search_string_suffix = &search_string[skip..];
-            let search_bound_result = self.search_bounds(&search_string[skip..]);
+            let search_bound_result = if skip == 0 {
+                let (search_bound_result, min_bound) = self.search_bounds_from(search_string, start_bound);
+                min_bound_full = min_bound;
+                search_bound_result
+            } else {
+                self.search_bounds(&search_string[skip..])
+            };
             // if the shorter part is matched, see if what goes before the matched suffix matches
             // the unmatched part of the prefix
             if let BoundSearchResult::SearchResult((min_bound, max_bound)) = search_bound_result {
+                total_matches_estimate += max_bound.saturating_sub(min_bound);
+
                 // try all the partially matched suffixes and store the matching suffixes in an
-                // array (stop when our max number of matches is reached)
-                let mut sa_index = min_bound;
+                // array (stop when our max number of matches is reached, after which only the
+                // bounds are needed for the estimate)
+                let mut sa_index = if max_matches_reached { max_bound } else { min_bound };
                 while sa_index < max_bound {
-                    let suffix = self.sa.get(sa_index) as usize;
-
-                    if suffix >= skip {
-                        let match_start = suffix - skip;
-                        let match_end = suffix + search_string.len() - skip;
+                    if let Some(match_start) = self.resolve_suffix(sa_index, skip) {
+                        let match_start = match_start as usize;
+                        let suffix = match_start + skip;
+                        let match_end = match_start + search_string.len();
 
                         // filter away matches where I was wrongfully equalized to L, and check the
                         // unmatched prefix when I and L equalized, we only need to
@@ -361,11 +855,12 @@ impl Searcher {
                                 || ((self.check_start_of_protein(match_start) || self.check_tryptic_cut(match_start))
                                     && (self.check_end_of_protein(match_end) || self.check_tryptic_cut(match_end))))
                         {
-                            matching_suffixes.push((suffix - skip) as i64);
+                            matching_suffixes.push(match_start as i64);
 
-                            // return if max number of matches is reached
+                            // stop if max number of matches is reached
                             if matching_suffixes.len() >= max_matches {
-                                return SearchAllSuffixesResult::MaxMatches(matching_suffixes);
+                                max_matches_reached = true;
+                                break;
                             }
                         }
                     }
@@ -376,40 +871,431 @@ impl Searcher {
             skip += 1;
         }
 
-        if matching_suffixes.is_empty() {
-            SearchAllSuffixesResult::NoMatches
+        if self.sort_matches {
+            matching_suffixes.sort_unstable();
+        }
+
+        if max_matches_reached {
+            (SearchAllSuffixesResult::MaxMatches(matching_suffixes), min_bound_full, total_matches_estimate)
+        } else if matching_suffixes.is_empty() {
+            (SearchAllSuffixesResult::NoMatches, min_bound_full, total_matches_estimate)
         } else {
-            SearchAllSuffixesResult::SearchResult(matching_suffixes)
+            (SearchAllSuffixesResult::SearchResult(matching_suffixes), min_bound_full, total_matches_estimate)
         }
     }
 
-    /// Check if a cut is the start of a protein.
+    /// Searches for the suffixes matching a search string with at most `max_mismatches`
+    /// substitutions
+    /// The search string is split into `max_mismatches + 1` pieces, of which at least one has to
+    /// match exactly. Every exact match of a piece is then verified against the full search string.
     ///
     /// # Arguments
-    /// * `cut_index` - The index of the cut in the text of proteins.
+    /// * `search_string` - The string/peptide we are searching in the suffix array
+    /// * `max_mismatches` - The maximum amount of substituted residues in a match
+    /// * `max_matches` - The maximum amount of matches processed, if more matches are found we
+    ///   don't process them
+    /// * `equate_il` - True if we want to equate I and L during search, otherwise false
     ///
     /// # Returns
     ///
-    /// Returns true if the cut is at the start of a protein.
-    #[inline]
-    fn check_start_of_protein(&self, cut_index: usize) -> bool {
-        cut_index == 0 || self.proteins.text.get(cut_index - 1) == SEPARATION_CHARACTER
+    /// Returns all the matching suffixes. Returns `NoMatches` if the pieces of the search string
+    /// are shorter than the sparseness factor of the suffix array, since they can not be searched.
+    pub fn search_matching_suffixes_mismatch(
+        &self,
+        search_string: &[u8],
+        max_mismatches: usize,
+        max_matches: usize,
+        equate_il: bool
+    ) -> SearchAllSuffixesResult {
+        let piece_count = max_mismatches + 1;
+        let piece_length = search_string.len() / piece_count;
+        if piece_length == 0 || piece_length < self.sa.sample_rate() as usize {
+            return SearchAllSuffixesResult::NoMatches;
+        }
+
+        let mut checked_starts: HashSet<usize> = HashSet::new();
+        let mut matching_suffixes: Vec<i64> = vec![];
+        for piece_index in 0..piece_count {
+            // the last piece also contains the remaining characters
+            let piece_start = piece_index * piece_length;
+            let piece_end =
+                if piece_index == piece_count - 1 { search_string.len() } else { piece_start + piece_length };
+
+            let piece_matches = match self.search_matching_suffixes(
+                &search_string[piece_start..piece_end],
+                usize::MAX,
+                equate_il,
+                false
+            ) {
+                SearchAllSuffixesResult::MaxMatches(matched_suffixes) => matched_suffixes,
+                SearchAllSuffixesResult::SearchResult(matched_suffixes) => matched_suffixes,
+                SearchAllSuffixesResult::NoMatches => vec![]
+            };
+
+            for piece_match in piece_matches {
+                let piece_match = piece_match as usize;
+                if piece_match < piece_start {
+                    continue;
+                }
+
+                // the same match can be found by multiple pieces
+                let match_start = piece_match - piece_start;
+                if !checked_starts.insert(match_start) {
+                    continue;
+                }
+
+                if self.count_mismatches(search_string, match_start, max_mismatches, equate_il) <= max_mismatches {
+                    matching_suffixes.push(match_start as i64);
+
+                    // return if max number of matches is reached
+                    if matching_suffixes.len() >= max_matches {
+                        return SearchAllSuffixesResult::MaxMatches(matching_suffixes);
+                    }
+                }
+            }
+        }
+
+        if matching_suffixes.is_empty() {
+            SearchAllSuffixesResult::NoMatches
+        } else {
+            SearchAllSuffixesResult::SearchResult(matching_suffixes)
+        }
     }
 
-    /// Check if a cut is the end of a protein.
+    /// Counts the residues that differ between the search string and the text at `match_start`
+    /// A match that crosses the end of a protein is never valid, and counts as too many mismatches.
     ///
     /// # Arguments
-    /// * `cut_index` - The index of the cut in the text of proteins.
+    /// * `search_string` - The string/peptide we are searching in the suffix array
+    /// * `match_start` - The start of the possible match in the text
+    /// * `max_mismatches` - The maximum amount of mismatches, counting stops when it is exceeded
+    /// * `equate_il` - True if we want to equate I and L during search, otherwise false
     ///
     /// # Returns
     ///
-    /// Returns true if the cut is at the end of a protein.
-    #[inline]
-    fn check_end_of_protein(&self, cut_index: usize) -> bool {
-        self.proteins.text.get(cut_index) == TERMINATION_CHARACTER
-            || self.proteins.text.get(cut_index) == SEPARATION_CHARACTER
-    }
-
+    /// Returns the number of mismatches, or a number larger than `max_mismatches` if the match is
+    /// not valid
+    fn count_mismatches(
+        &self,
+        search_string: &[u8],
+        match_start: usize,
+        max_mismatches: usize,
+        equate_il: bool
+    ) -> usize {
+        let equate_il = self.searched_equate_il(equate_il);
+        if match_start + search_string.len() > self.proteins.text.len() {
+            return max_mismatches + 1;
+        }
+
+        let mut mismatches = 0;
+        for (i, &search_character) in search_string.iter().enumerate() {
+            let text_character = self.proteins.text.get(match_start + i);
+            if self.proteins.delimiters.contains(text_character) {
+                return max_mismatches + 1;
+            }
+
+            let is_equal = search_character == text_character
+                || (equate_il
+                    && (search_character == b'I' || search_character == b'L')
+                    && (text_character == b'I' || text_character == b'L'));
+            if !is_equal {
+                mismatches += 1;
+                if mismatches > max_mismatches {
+                    break;
+                }
+            }
+        }
+
+        mismatches
+    }
+
+    /// Searches for the longest prefix of a search string that occurs in the proteins
+    /// Since every prefix of a matching prefix also matches, the length of the longest matching
+    /// prefix is found with a binary search over the prefix lengths.
+    ///
+    /// # Arguments
+    /// * `search_string` - The string/peptide of which we are searching the longest matching prefix
+    /// * `equate_il` - True if we want to equate I and L during search, otherwise false
+    ///
+    /// # Returns
+    ///
+    /// Returns the length of the longest matching prefix, which is the length of the search string
+    /// if it matches completely. Returns 0 if no prefix matches, or if only prefixes shorter than
+    /// the sparseness factor of the suffix array match, since those can not be searched.
+    pub fn longest_matching_prefix(&self, search_string: &[u8], equate_il: bool) -> usize {
+        let prefix_matches = |length: usize| {
+            self.search_matching_suffixes(&search_string[..length], 1, equate_il, false)
+                != SearchAllSuffixesResult::NoMatches
+        };
+
+        // the invariant is that the prefix of length `low` matches and that of length `high` does not
+        let min_length = self.sa.sample_rate() as usize;
+        if search_string.len() < min_length || !prefix_matches(min_length) {
+            return 0;
+        }
+        if prefix_matches(search_string.len()) {
+            return search_string.len();
+        }
+
+        let (mut low, mut high) = (min_length, search_string.len());
+        while high - low > 1 {
+            let middle = low + (high - low) / 2;
+            if prefix_matches(middle) {
+                low = middle;
+            } else {
+                high = middle;
+            }
+        }
+
+        low
+    }
+
+    /// Enumerates all the distinct substrings of length `k` in the proteins, together with their
+    /// number of occurrences
+    /// The suffix array is traversed in order, so the occurrences of a substring are grouped
+    /// together. If I and L are equated in the suffix array, substrings that only differ in I and
+    /// L are grouped together and are split up afterwards. Substrings that cross the end of a
+    /// protein are skipped.
+    /// The suffix array should not be sparse, otherwise only the occurrences at sampled positions
+    /// are counted.
+    ///
+    /// # Arguments
+    /// * `k` - The length of the substrings
+    ///
+    /// # Returns
+    ///
+    /// Returns an iterator over the distinct substrings of length `k` and their occurrence counts
+    pub fn distinct_kmers(&self, k: usize) -> impl Iterator<Item = (Vec<u8>, usize)> + '_ {
+        let mut sa_index = 0;
+        let mut group: BTreeMap<Vec<u8>, usize> = BTreeMap::new();
+        let mut group_key: Vec<u8> = vec![];
+
+        std::iter::from_fn(move || {
+            while sa_index < self.sa.len() {
+                let suffix = self.sa.get(sa_index) as usize;
+                sa_index += 1;
+
+                let Some(kmer) = self.kmer_at(suffix, k) else {
+                    continue;
+                };
+                let key: Vec<u8> = kmer.iter().map(|&c| self.sorted_character(c)).collect();
+
+                if key != group_key && !group.is_empty() {
+                    let finished_group = std::mem::take(&mut group);
+                    group_key = key;
+                    *group.entry(kmer).or_insert(0) += 1;
+                    return Some(finished_group);
+                }
+
+                group_key = key;
+                *group.entry(kmer).or_insert(0) += 1;
+            }
+
+            if group.is_empty() {
+                None
+            } else {
+                Some(std::mem::take(&mut group))
+            }
+        })
+        .flatten()
+    }
+
+    /// Returns the substring of length `k` starting at `suffix`, if it does not cross the end of a
+    /// protein
+    ///
+    /// # Arguments
+    /// * `suffix` - The start of the substring in the text
+    /// * `k` - The length of the substring
+    ///
+    /// # Returns
+    ///
+    /// Returns the substring, or None if the substring crosses the end of a protein
+    fn kmer_at(&self, suffix: usize, k: usize) -> Option<Vec<u8>> {
+        if k == 0 || suffix + k > self.proteins.text.len() {
+            return None;
+        }
+
+        let kmer: Vec<u8> = ProteinTextSlice::new(&self.proteins.text, suffix, suffix + k).iter().collect();
+        if kmer.iter().any(|&c| self.proteins.delimiters.contains(c)) {
+            return None;
+        }
+
+        Some(kmer)
+    }
+
+    /// Counts the suffixes matching a search string
+    /// If the suffix array is not sparse and I and L are equated in the same way as in the suffix
+    /// array, every suffix between the search bounds is a match, so the count is computed from the
+    /// bounds without retrieving the suffixes.
+    ///
+    /// # Arguments
+    /// * `search_string` - The string/peptide we are searching in the suffix array
+    /// * `max_matches` - The maximum amount of matches counted
+    /// * `equate_il` - True if we want to equate I and L during search, otherwise false
+    ///
+    /// # Returns
+    ///
+    /// Returns the number of matching suffixes, which is at most `max_matches`
+    pub fn count_matching_suffixes(&self, search_string: &[u8], max_matches: usize, equate_il: bool) -> usize {
+        if self.sa.sample_rate() == 1 && self.searched_equate_il(equate_il) == self.il_equated {
+            return match self.search_bounds(search_string) {
+                BoundSearchResult::SearchResult((min_bound, max_bound)) => min(max_bound - min_bound, max_matches),
+                BoundSearchResult::NoMatches => 0
+            };
+        }
+
+        match self.search_matching_suffixes(search_string, max_matches, equate_il, false) {
+            SearchAllSuffixesResult::MaxMatches(matched_suffixes) => matched_suffixes.len(),
+            SearchAllSuffixesResult::SearchResult(matched_suffixes) => matched_suffixes.len(),
+            SearchAllSuffixesResult::NoMatches => 0
+        }
+    }
+
+    /// Searches for the suffixes matching a search string with I and L equated, and splits off the
+    /// matches that are also an exact match (where I and L are not equated)
+    /// This only enumerates the matching suffixes once, which is cheaper than searching twice
+    ///
+    /// # Arguments
+    /// * `search_string` - The string/peptide we are searching in the suffix array
+    /// * `max_matches` - The maximum amount of matches processed, if more matches are found we
+    ///   don't process them
+    /// * `tryptic` - Boolean indicating if we only want tryptic matches.
+    ///
+    /// # Returns
+    ///
+    /// Returns a tuple where the first element contains all the suffixes matching with I and L
+    /// equated, and the second element contains the subset of those suffixes that also match when
+    /// I and L are not equated
+    pub fn search_matching_suffixes_dual(
+        &self,
+        search_string: &[u8],
+        max_matches: usize,
+        tryptic: bool
+    ) -> (Vec<i64>, Vec<i64>) {
+        let il_equated_suffixes = match self.search_matching_suffixes(search_string, max_matches, true, tryptic) {
+            SearchAllSuffixesResult::MaxMatches(matched_suffixes) => matched_suffixes,
+            SearchAllSuffixesResult::SearchResult(matched_suffixes) => matched_suffixes,
+            SearchAllSuffixesResult::NoMatches => vec![]
+        };
+
+        let strict_suffixes = il_equated_suffixes
+            .iter()
+            .copied()
+            .filter(|&suffix| {
+                let match_start = suffix as usize;
+                ProteinTextSlice::new(&self.proteins.text, match_start, match_start + search_string.len())
+                    .equals_slice(search_string, false)
+            })
+            .collect();
+
+        (il_equated_suffixes, strict_suffixes)
+    }
+
+    /// Searches for the suffixes matching a profile, where every position of the profile is a set
+    /// of allowed residues
+    /// The search is anchored on the longest contiguous segment of positions that only allow a
+    /// single residue. The matches of this segment are then verified against the full profile.
+    ///
+    /// # Arguments
+    /// * `profile` - The allowed residues for every position of the searched peptide
+    /// * `equate_il` - True if we want to equate I and L during search, otherwise false
+    /// * `cutoff` - The maximum amount of matches processed, if more matches are found we don't
+    ///   process them
+    ///
+    /// # Returns
+    ///
+    /// Returns all the matching suffixes. Returns `NoMatches` if the profile does not contain a
+    /// segment of single residues that is at least as long as the sparseness factor of the suffix
+    /// array, since such a profile cannot be anchored in the index.
+    pub fn search_profile(&self, profile: &[ResidueSet], equate_il: bool, cutoff: usize) -> SearchAllSuffixesResult {
+        let equate_il = self.searched_equate_il(equate_il);
+
+        // find the longest contiguous segment of positions that only allow a single residue
+        let mut anchor_start = 0;
+        let mut anchor: Vec<u8> = vec![];
+        let mut current_start = 0;
+        let mut current: Vec<u8> = vec![];
+        for (i, residue_set) in profile.iter().enumerate() {
+            match residue_set.single_residue(equate_il) {
+                Some(residue) => current.push(residue),
+                None => {
+                    current.clear();
+                    current_start = i + 1;
+                }
+            }
+
+            if current.len() > anchor.len() {
+                anchor_start = current_start;
+                anchor.clone_from(&current);
+            }
+        }
+
+        if anchor.is_empty() || anchor.len() < self.sa.sample_rate() as usize {
+            return SearchAllSuffixesResult::NoMatches;
+        }
+
+        let anchor_matches = match self.search_matching_suffixes(&anchor, usize::MAX, equate_il, false) {
+            SearchAllSuffixesResult::MaxMatches(matched_suffixes) => matched_suffixes,
+            SearchAllSuffixesResult::SearchResult(matched_suffixes) => matched_suffixes,
+            SearchAllSuffixesResult::NoMatches => vec![]
+        };
+
+        let mut matching_suffixes: Vec<i64> = vec![];
+        for anchor_match in anchor_matches {
+            let anchor_match = anchor_match as usize;
+            if anchor_match < anchor_start || anchor_match - anchor_start + profile.len() > self.proteins.text.len() {
+                continue;
+            }
+
+            let match_start = anchor_match - anchor_start;
+            let matches_profile = profile
+                .iter()
+                .enumerate()
+                .all(|(i, residue_set)| residue_set.contains(self.proteins.text.get(match_start + i), equate_il));
+
+            if matches_profile {
+                matching_suffixes.push(match_start as i64);
+
+                // return if max number of matches is reached
+                if matching_suffixes.len() >= cutoff {
+                    return SearchAllSuffixesResult::MaxMatches(matching_suffixes);
+                }
+            }
+        }
+
+        if matching_suffixes.is_empty() {
+            SearchAllSuffixesResult::NoMatches
+        } else {
+            SearchAllSuffixesResult::SearchResult(matching_suffixes)
+        }
+    }
+
+    /// Check if a cut is the start of a protein.
+    ///
+    /// # Arguments
+    /// * `cut_index` - The index of the cut in the text of proteins.
+    ///
+    /// # Returns
+    ///
+    /// Returns true if the cut is at the start of a protein.
+    #[inline]
+    fn check_start_of_protein(&self, cut_index: usize) -> bool {
+        cut_index == 0 || self.proteins.text.get(cut_index - 1) == self.proteins.delimiters.separation_character
+    }
+
+    /// Check if a cut is the end of a protein.
+    ///
+    /// # Arguments
+    /// * `cut_index` - The index of the cut in the text of proteins.
+    ///
+    /// # Returns
+    ///
+    /// Returns true if the cut is at the end of a protein.
+    #[inline]
+    fn check_end_of_protein(&self, cut_index: usize) -> bool {
+        self.proteins.delimiters.contains(self.proteins.text.get(cut_index))
+    }
+
     /// Check if a cut is a tryptic cut, so check if the amino acid preceding the cut is K or R and the amino acid at the cut is not P.
     ///
     /// # Arguments
@@ -465,7 +1351,11 @@ impl Searcher {
         text_slice: ProteinTextSlice,
         equate_il: bool
     ) -> bool {
-        if equate_il { true } else { text_slice.check_il_locations(skip, il_locations, search_string) }
+        if equate_il {
+            true
+        } else {
+            text_slice.check_il_locations(skip, il_locations, search_string)
+        }
     }
 
     /// Returns all the proteins that correspond with the provided suffixes
@@ -487,16 +1377,192 @@ impl Searcher {
         }
         res
     }
+
+    /// Returns all the proteins that correspond with the provided suffixes, grouped by their taxon
+    ///
+    /// # Arguments
+    /// * `suffixes` - List of suffix indices
+    ///
+    /// # Returns
+    ///
+    /// Returns a map from every taxon to the proteins of that taxon that the suffixes are a part of,
+    /// in the order of the suffixes. Like in `retrieve_proteins`, a protein occurs once for every
+    /// suffix that is a part of it.
+    pub fn retrieve_proteins_grouped_by_taxon(&self, suffixes: &[i64]) -> HashMap<u32, Vec<&Protein>> {
+        let mut proteins_by_taxon: HashMap<u32, Vec<&Protein>> = HashMap::new();
+        for &suffix in suffixes {
+            let protein_index = self.suffix_index_to_protein.suffix_to_protein(suffix);
+            if !protein_index.is_null() {
+                let protein = &self.proteins[protein_index as usize];
+                proteins_by_taxon.entry(protein.taxon_id).or_default().push(protein);
+            }
+        }
+        proteins_by_taxon
+    }
+
+    /// Returns all the proteins that correspond with the provided suffixes, together with the
+    /// positions of the suffixes relative to the start of those proteins
+    ///
+    /// # Arguments
+    /// * `suffixes` - List of suffix indices
+    ///
+    /// # Returns
+    ///
+    /// Returns every protein that at least one suffix is a part of (in order of first occurrence),
+    /// together with the start positions of all the suffixes within that protein
+    pub fn retrieve_proteins_with_positions(&self, suffixes: &[i64]) -> Vec<(&Protein, Vec<usize>)> {
+        let mut res: Vec<(&Protein, Vec<usize>)> = vec![];
+        let mut protein_to_result_index: HashMap<u32, usize> = HashMap::new();
+        for &suffix in suffixes {
+            let protein_index = self.suffix_index_to_protein.suffix_to_protein(suffix);
+            if protein_index.is_null() {
+                continue;
+            }
+
+            let position = self.position_in_protein(suffix as usize);
+            match protein_to_result_index.get(&protein_index) {
+                Some(&result_index) => res[result_index].1.push(position),
+                None => {
+                    protein_to_result_index.insert(protein_index, res.len());
+                    res.push((&self.proteins[protein_index as usize], vec![position]));
+                }
+            }
+        }
+        res
+    }
+
+    /// Searches the peptide in the index and groups the matching proteins by their taxon
+    ///
+    /// # Arguments
+    /// * `peptide` - The peptide that is being searched in the index
+    /// * `equate_il` - True if we want to equate I and L during search, otherwise false
+    /// * `cutoff` - The maximum amount of matching suffixes that we want to process
+    ///
+    /// # Returns
+    ///
+    /// Returns a map from every taxon with a matching protein to the matching proteins of that
+    /// taxon. Every matching protein occurs only once, even if the peptide matches it multiple
+    /// times.
+    pub fn search_by_taxon(&self, peptide: &[u8], equate_il: bool, cutoff: usize) -> HashMap<u32, Vec<&Protein>> {
+        let suffixes = match self.search_matching_suffixes(peptide, cutoff, equate_il, false) {
+            SearchAllSuffixesResult::MaxMatches(matched_suffixes) => matched_suffixes,
+            SearchAllSuffixesResult::SearchResult(matched_suffixes) => matched_suffixes,
+            SearchAllSuffixesResult::NoMatches => vec![]
+        };
+
+        // deduplicate on the index of the protein, a protein can be matched multiple times
+        let mut seen_proteins: HashSet<usize> = HashSet::new();
+        let mut proteins_by_taxon: HashMap<u32, Vec<&Protein>> = HashMap::new();
+        for suffix in suffixes {
+            let protein_index = self.suffix_index_to_protein.suffix_to_protein(suffix);
+            if protein_index.is_null() || !seen_proteins.insert(protein_index as usize) {
+                continue;
+            }
+
+            let protein = &self.proteins[protein_index as usize];
+            proteins_by_taxon.entry(protein.taxon_id).or_default().push(protein);
+        }
+        proteins_by_taxon
+    }
+
+    /// Determines if all the proteins that match the peptide belong to the same taxon
+    ///
+    /// # Arguments
+    /// * `peptide` - The peptide that is being searched in the index
+    /// * `equate_il` - True if we want to equate I and L during search, otherwise false
+    ///
+    /// # Returns
+    ///
+    /// Returns the `Uniqueness` of the peptide, taking all the matches of the peptide into account
+    pub fn peptide_uniqueness(&self, peptide: &[u8], equate_il: bool) -> Uniqueness {
+        let mut taxa: Vec<u32> = self.search_by_taxon(peptide, equate_il, usize::MAX).into_keys().collect();
+        taxa.sort_unstable();
+
+        match taxa.as_slice() {
+            [] => Uniqueness::NoMatches,
+            [taxon] => Uniqueness::Unique(*taxon),
+            _ => Uniqueness::Shared(taxa)
+        }
+    }
+
+    /// Returns the protein that contains the given position in the text
+    ///
+    /// # Arguments
+    /// * `pos` - A position in the text
+    ///
+    /// # Returns
+    ///
+    /// Returns the protein that contains the position, or None if the position is a separation or
+    /// termination character, or lies outside of the text
+    pub fn protein_at_text_position(&self, pos: usize) -> Option<&Protein> {
+        if pos >= self.proteins.text.len() {
+            return None;
+        }
+
+        let protein_index = self.suffix_index_to_protein.suffix_to_protein(pos as i64);
+        if protein_index.is_null() {
+            return None;
+        }
+
+        Some(&self.proteins[protein_index as usize])
+    }
+
+    /// Returns the bounds of the protein that contains the given offset in the text, by scanning to
+    /// the nearest separation characters
+    ///
+    /// # Arguments
+    /// * `offset` - An offset in the text
+    ///
+    /// # Returns
+    ///
+    /// Returns the `[start, end)` range of the protein in the text, or None if the offset is a
+    /// separation or termination character, or lies outside of the text
+    pub fn protein_bounds_at(&self, offset: usize) -> Option<(usize, usize)> {
+        let text = &self.proteins.text;
+        let is_boundary = |index: usize| self.proteins.delimiters.contains(text.get(index));
+
+        if offset >= text.len() || is_boundary(offset) {
+            return None;
+        }
+
+        let start = offset - self.position_in_protein(offset);
+        let mut end = offset + 1;
+        while end < text.len() && !is_boundary(end) {
+            end += 1;
+        }
+
+        Some((start, end))
+    }
+
+    /// Computes the position of a suffix relative to the start of the protein it is part of, by
+    /// scanning back to the preceding separation character
+    ///
+    /// # Arguments
+    /// * `suffix` - The suffix of which we want to know the position within its protein
+    ///
+    /// # Returns
+    ///
+    /// Returns the offset of the suffix from the start of its protein
+    fn position_in_protein(&self, suffix: usize) -> usize {
+        let mut protein_start = suffix;
+        let separation_character = self.proteins.delimiters.separation_character;
+        while protein_start > 0 && self.proteins.text.get(protein_start - 1) != separation_character {
+            protein_start -= 1;
+        }
+        suffix - protein_start
+    }
 }
 
 #[cfg(test)]
 mod tests {
-    use sa_mappings::proteins::{Protein, Proteins};
+    use sa_mappings::proteins::{Delimiters, Protein, Proteins};
     use text_compression::ProteinText;
 
     use crate::{
-        sa_searcher::{BoundSearchResult, SearchAllSuffixesResult, Searcher},
-        suffix_to_protein_index::SparseSuffixToProtein,
+        sa_searcher::{
+            BoundSearchResult, ResidueSet, SearchAllSuffixesResult, Searcher, Uniqueness, VerifyError
+        },
+        suffix_to_protein_index::{DenseSuffixToProtein, SparseSuffixToProtein, SuffixToProteinIndex},
         SuffixArray
     };
 
@@ -547,8 +1613,38 @@ mod tests {
                     taxon_id: 0,
                     functional_annotations: vec![]
                 },
-            ]
+            ],
+            accession_index: None,
+            original_sequences: None,
+            delimiters: Delimiters::default(),
+            skipped_accessions: Vec::new()
+        }
+    }
+
+    /// Creates a searcher over the example proteins, which are named P1 to P4 and belong to the
+    /// taxa 1, 2, 2 and 3
+    fn get_example_taxon_searcher() -> Searcher {
+        let mut proteins = get_example_proteins();
+        for (protein, (uniprot_id, taxon_id)) in
+            proteins.proteins.iter_mut().zip([("P1", 1), ("P2", 2), ("P3", 2), ("P4", 3)])
+        {
+            protein.uniprot_id = uniprot_id.to_string();
+            protein.taxon_id = taxon_id;
         }
+        let sa = SuffixArray::Original(vec![19, 10, 2, 13, 9, 8, 11, 5, 0, 12, 3, 15, 6, 1, 4, 17, 14, 16, 7, 18], 1);
+
+        let suffix_index_to_protein = SparseSuffixToProtein::new(&proteins.text);
+        Searcher::new(sa, proteins, Box::new(suffix_index_to_protein))
+    }
+
+    #[test]
+    #[should_panic(expected = "The sample rate of the suffix array should be at least 1, but it is 0")]
+    fn test_searcher_zero_sample_rate() {
+        let proteins = get_example_proteins();
+        let sa = SuffixArray::Original(vec![19, 10, 2, 13, 9, 8, 11, 5, 0, 12, 3, 15, 6, 1, 4, 17, 14, 16, 7, 18], 0);
+
+        let suffix_index_to_protein = SparseSuffixToProtein::new(&proteins.text);
+        Searcher::new(sa, proteins, Box::new(suffix_index_to_protein));
     }
 
     #[test]
@@ -634,7 +1730,11 @@ mod tests {
                 uniprot_id: String::new(),
                 taxon_id: 0,
                 functional_annotations: vec![]
-            }]
+            }],
+            accession_index: None,
+            original_sequences: None,
+            delimiters: Delimiters::default(),
+            skipped_accessions: Vec::new()
         };
 
         let sparse_sa = SuffixArray::Original(vec![0, 2, 4], 2);
@@ -657,7 +1757,11 @@ mod tests {
                 uniprot_id: String::new(),
                 taxon_id: 0,
                 functional_annotations: vec![]
-            }]
+            }],
+            accession_index: None,
+            original_sequences: None,
+            delimiters: Delimiters::default(),
+            skipped_accessions: Vec::new()
         };
 
         let sparse_sa = SuffixArray::Original(vec![6, 0, 1, 5, 4, 3, 2], 1);
@@ -679,7 +1783,11 @@ mod tests {
                 uniprot_id: String::new(),
                 taxon_id: 0,
                 functional_annotations: vec![]
-            }]
+            }],
+            accession_index: None,
+            original_sequences: None,
+            delimiters: Delimiters::default(),
+            skipped_accessions: Vec::new()
         };
 
         let sparse_sa = SuffixArray::Original(vec![6, 5, 4, 3, 2, 1, 0], 1);
@@ -701,7 +1809,11 @@ mod tests {
                 uniprot_id: String::new(),
                 taxon_id: 0,
                 functional_annotations: vec![]
-            }]
+            }],
+            accession_index: None,
+            original_sequences: None,
+            delimiters: Delimiters::default(),
+            skipped_accessions: Vec::new()
         };
 
         let sparse_sa = SuffixArray::Original(vec![6, 4, 2, 0], 2);
@@ -725,7 +1837,11 @@ mod tests {
                 uniprot_id: String::new(),
                 taxon_id: 0,
                 functional_annotations: vec![]
-            }]
+            }],
+            accession_index: None,
+            original_sequences: None,
+            delimiters: Delimiters::default(),
+            skipped_accessions: Vec::new()
         };
 
         let sparse_sa = SuffixArray::Original(vec![6, 5, 4, 3, 2, 1, 0], 1);
@@ -748,7 +1864,11 @@ mod tests {
                 uniprot_id: String::new(),
                 taxon_id: 0,
                 functional_annotations: vec![]
-            }]
+            }],
+            accession_index: None,
+            original_sequences: None,
+            delimiters: Delimiters::default(),
+            skipped_accessions: Vec::new()
         };
 
         let sparse_sa = SuffixArray::Original(vec![13, 3, 12, 11, 1, 4, 2, 5, 9, 8, 6, 10, 0, 7], 1);
@@ -761,4 +1881,636 @@ mod tests {
         let found_suffixes_2 = searcher.search_matching_suffixes(&[b'A', b'P', b'A', b'A'], usize::MAX, false, true);
         assert_eq!(found_suffixes_2, SearchAllSuffixesResult::SearchResult(vec![9]));
     }
+
+    #[test]
+    fn test_search_matching_suffixes_dual() {
+        let input_string = "CIA-CLA-CIA$";
+        let text = ProteinText::from_string(input_string);
+
+        let proteins = Proteins {
+            text,
+            proteins: vec![
+                Protein {
+                    uniprot_id: String::new(),
+                    taxon_id: 0,
+                    functional_annotations: vec![]
+                },
+                Protein {
+                    uniprot_id: String::new(),
+                    taxon_id: 0,
+                    functional_annotations: vec![]
+                },
+                Protein {
+                    uniprot_id: String::new(),
+                    taxon_id: 0,
+                    functional_annotations: vec![]
+                },
+            ],
+            accession_index: None,
+            original_sequences: None,
+            delimiters: Delimiters::default(),
+            skipped_accessions: Vec::new()
+        };
+
+        let sa = SuffixArray::Original(vec![11, 7, 3, 10, 6, 2, 8, 4, 0, 9, 5, 1], 1);
+        let suffix_index_to_protein = SparseSuffixToProtein::new(&proteins.text);
+        let searcher = Searcher::new(sa, proteins, Box::new(suffix_index_to_protein));
+
+        let (mut il_equated, mut strict) = searcher.search_matching_suffixes_dual(b"CIA", usize::MAX, false);
+        il_equated.sort();
+        strict.sort();
+
+        assert_eq!(il_equated, vec![0, 4, 8]);
+        assert_eq!(strict, vec![0, 8]);
+    }
+
+    #[test]
+    fn test_residue_set() {
+        let residue_set = ResidueSet::new(b"DE");
+        assert!(residue_set.contains(b'D', false));
+        assert!(residue_set.contains(b'E', false));
+        assert!(!residue_set.contains(b'K', false));
+        assert!(!residue_set.contains(b'-', false));
+
+        let residue_set = ResidueSet::new(b"L");
+        assert!(!residue_set.contains(b'I', false));
+        assert!(residue_set.contains(b'I', true));
+    }
+
+    #[test]
+    fn test_search_profile() {
+        let proteins = get_example_proteins();
+        let sa = SuffixArray::Original(vec![19, 10, 2, 13, 9, 8, 11, 5, 0, 12, 3, 15, 6, 1, 4, 17, 14, 16, 7, 18], 1);
+
+        let suffix_index_to_protein = SparseSuffixToProtein::new(&proteins.text);
+        let searcher = Searcher::new(sa, proteins, Box::new(suffix_index_to_protein));
+
+        // search profile '[AK]C[RV]'
+        let profile = [ResidueSet::new(b"AK"), ResidueSet::new(b"C"), ResidueSet::new(b"RV")];
+        let found_suffixes = searcher.search_profile(&profile, false, usize::MAX);
+        assert_eq!(found_suffixes, SearchAllSuffixesResult::SearchResult(vec![5, 14]));
+
+        // search profile 'C[IL]A', the anchor is only the 'C' if I and L are not equated
+        let profile = [ResidueSet::new(b"C"), ResidueSet::new(b"IL"), ResidueSet::new(b"A")];
+        let found_suffixes = searcher.search_profile(&profile, false, usize::MAX);
+        assert_eq!(found_suffixes, SearchAllSuffixesResult::SearchResult(vec![3]));
+        let found_suffixes = searcher.search_profile(&profile, true, usize::MAX);
+        assert_eq!(found_suffixes, SearchAllSuffixesResult::SearchResult(vec![3]));
+
+        // a profile without a single residue segment can not be anchored
+        let profile = [ResidueSet::new(b"AK"), ResidueSet::new(b"CR")];
+        let found_suffixes = searcher.search_profile(&profile, false, usize::MAX);
+        assert_eq!(found_suffixes, SearchAllSuffixesResult::NoMatches);
+    }
+
+    #[test]
+    fn test_count_matching_suffixes() {
+        let proteins = get_example_proteins();
+        let sa = SuffixArray::Original(vec![19, 10, 2, 13, 9, 8, 11, 5, 0, 12, 3, 15, 6, 1, 4, 17, 14, 16, 7, 18], 1);
+
+        let suffix_index_to_protein = SparseSuffixToProtein::new(&proteins.text);
+        let searcher = Searcher::new(sa, proteins, Box::new(suffix_index_to_protein));
+
+        for search_string in [&b"A"[..], b"C", b"AC", b"I", b"IA", b"VAA", b"W"] {
+            for equate_il in [true, false] {
+                let expected = match searcher.search_matching_suffixes(search_string, usize::MAX, equate_il, false) {
+                    SearchAllSuffixesResult::SearchResult(matched_suffixes) => matched_suffixes.len(),
+                    _ => 0
+                };
+                assert_eq!(searcher.count_matching_suffixes(search_string, usize::MAX, equate_il), expected);
+            }
+        }
+
+        // the cutoff is respected
+        assert_eq!(searcher.count_matching_suffixes(b"A", 2, true), 2);
+        assert_eq!(searcher.count_matching_suffixes(b"A", 2, false), 2);
+    }
+
+    #[test]
+    fn test_count_matching_suffixes_sparse() {
+        let proteins = get_example_proteins();
+        let sa = SuffixArray::Original(vec![9, 0, 3, 12, 15, 6, 18], 3);
+
+        let suffix_index_to_protein = SparseSuffixToProtein::new(&proteins.text);
+        let searcher = Searcher::new(sa, proteins, Box::new(suffix_index_to_protein));
+
+        assert_eq!(searcher.count_matching_suffixes(b"AC", usize::MAX, true), 2);
+        assert_eq!(searcher.count_matching_suffixes(b"VAA", usize::MAX, false), 1);
+    }
+
+    #[test]
+    fn test_search_matching_suffixes_with_estimate() {
+        let searcher = Searcher::from_proteins(get_example_proteins(), 1).unwrap();
+
+        // the cutoff only keeps 2 matches, but the search bounds contain all 5 matches
+        let (result, estimate) = searcher.search_matching_suffixes_with_estimate(b"A", 2, false, false);
+        match result {
+            SearchAllSuffixesResult::MaxMatches(matches) => assert_eq!(matches.len(), 2),
+            _ => panic!("Expected the cutoff to be reached")
+        }
+        assert_eq!(estimate, 5);
+
+        // the estimate ignores the I and L filter, so it also counts the 2 L's
+        let (result, estimate) = searcher.search_matching_suffixes_with_estimate(b"I", usize::MAX, false, false);
+        assert_eq!(result, SearchAllSuffixesResult::SearchResult(vec![1]));
+        assert_eq!(estimate, 3);
+
+        // in a sparse suffix array, the bounds of "C" also contain the C's that do not follow an A
+        let sparse_searcher = Searcher::from_proteins(get_example_proteins(), 3).unwrap();
+        let (result, estimate) = sparse_searcher.search_matching_suffixes_with_estimate(b"AC", 1, true, false);
+        assert!(matches!(result, SearchAllSuffixesResult::MaxMatches(_)));
+        assert_eq!(estimate, 4);
+    }
+
+    #[test]
+    fn test_search_matching_suffixes_mismatch() {
+        let proteins = get_example_proteins();
+        let sa = SuffixArray::Original(vec![19, 10, 2, 13, 9, 8, 11, 5, 0, 12, 3, 15, 6, 1, 4, 17, 14, 16, 7, 18], 1);
+
+        let suffix_index_to_protein = SparseSuffixToProtein::new(&proteins.text);
+        let searcher = Searcher::new(sa, proteins, Box::new(suffix_index_to_protein));
+
+        // exact match
+        let found_suffixes = searcher.search_matching_suffixes_mismatch(b"CLACV", 1, usize::MAX, false);
+        assert_eq!(found_suffixes, SearchAllSuffixesResult::SearchResult(vec![3]));
+
+        // one substitution
+        let found_suffixes = searcher.search_matching_suffixes_mismatch(b"CLGCV", 1, usize::MAX, false);
+        assert_eq!(found_suffixes, SearchAllSuffixesResult::SearchResult(vec![3]));
+
+        // I and L are only different if they are not equated
+        let found_suffixes = searcher.search_matching_suffixes_mismatch(b"CIGCV", 1, usize::MAX, false);
+        assert_eq!(found_suffixes, SearchAllSuffixesResult::NoMatches);
+        let found_suffixes = searcher.search_matching_suffixes_mismatch(b"CIGCV", 1, usize::MAX, true);
+        assert_eq!(found_suffixes, SearchAllSuffixesResult::SearchResult(vec![3]));
+
+        // two substitutions
+        let found_suffixes = searcher.search_matching_suffixes_mismatch(b"CKGCV", 1, usize::MAX, false);
+        assert_eq!(found_suffixes, SearchAllSuffixesResult::NoMatches);
+        let found_suffixes = searcher.search_matching_suffixes_mismatch(b"CKGCV", 2, usize::MAX, false);
+        assert_eq!(found_suffixes, SearchAllSuffixesResult::SearchResult(vec![3]));
+    }
+
+    #[test]
+    fn test_search_matching_suffixes_mismatch_protein_boundary() {
+        let proteins = get_example_proteins();
+        let sa = SuffixArray::Original(vec![19, 10, 2, 13, 9, 8, 11, 5, 0, 12, 3, 15, 6, 1, 4, 17, 14, 16, 7, 18], 1);
+
+        let suffix_index_to_protein = SparseSuffixToProtein::new(&proteins.text);
+        let searcher = Searcher::new(sa, proteins, Box::new(suffix_index_to_protein));
+
+        // 'AAW' would match 'AA-' with one substitution, but a match can not cross proteins
+        let found_suffixes = searcher.search_matching_suffixes_mismatch(b"VAAW", 1, usize::MAX, false);
+        assert_eq!(found_suffixes, SearchAllSuffixesResult::NoMatches);
+
+        // 'KCRLW' matches 'KCRLY' with one substitution
+        let found_suffixes = searcher.search_matching_suffixes_mismatch(b"KCRLW", 1, usize::MAX, false);
+        assert_eq!(found_suffixes, SearchAllSuffixesResult::SearchResult(vec![14]));
+    }
+
+    #[test]
+    fn test_distinct_kmers() {
+        let proteins = get_example_proteins();
+        let sa = SuffixArray::Original(vec![19, 10, 2, 13, 9, 8, 11, 5, 0, 12, 3, 15, 6, 1, 4, 17, 14, 16, 7, 18], 1);
+
+        let suffix_index_to_protein = SparseSuffixToProtein::new(&proteins.text);
+        let searcher = Searcher::new(sa, proteins, Box::new(suffix_index_to_protein));
+
+        let kmers: Vec<(Vec<u8>, usize)> = searcher.distinct_kmers(2).collect();
+        let expected: Vec<(Vec<u8>, usize)> = vec![
+            (b"AA".to_vec(), 1),
+            (b"AC".to_vec(), 2),
+            (b"AI".to_vec(), 1),
+            (b"CL".to_vec(), 1),
+            (b"CR".to_vec(), 1),
+            (b"CV".to_vec(), 1),
+            (b"LA".to_vec(), 1),
+            (b"LY".to_vec(), 1),
+            (b"KC".to_vec(), 1),
+            (b"RL".to_vec(), 1),
+            (b"VA".to_vec(), 1),
+        ];
+        assert_eq!(kmers, expected);
+
+        // 5-mers that cross the end of a protein are skipped
+        let kmers: Vec<(Vec<u8>, usize)> = searcher.distinct_kmers(5).collect();
+        assert_eq!(kmers, vec![
+            (b"ACVAA".to_vec(), 1),
+            (b"CLACV".to_vec(), 1),
+            (b"LACVA".to_vec(), 1),
+            (b"KCRLY".to_vec(), 1),
+        ]);
+    }
+
+    #[test]
+    fn test_search_by_taxon() {
+        let searcher = get_example_taxon_searcher();
+
+        // both proteins of taxon 2 contain "AC"
+        let proteins_by_taxon = searcher.search_by_taxon(b"AC", false, usize::MAX);
+        assert_eq!(proteins_by_taxon.len(), 1);
+        let mut uniprot_ids: Vec<&str> =
+            proteins_by_taxon[&2].iter().map(|protein| protein.uniprot_id.as_str()).collect();
+        uniprot_ids.sort();
+        assert_eq!(uniprot_ids, vec!["P2", "P3"]);
+
+        // "C" occurs twice in the second protein, but the protein is only listed once
+        let proteins_by_taxon = searcher.search_by_taxon(b"C", false, usize::MAX);
+        assert_eq!(proteins_by_taxon.len(), 2);
+        assert_eq!(proteins_by_taxon[&2].len(), 2);
+        assert_eq!(proteins_by_taxon[&3].len(), 1);
+
+        assert!(searcher.search_by_taxon(b"W", false, usize::MAX).is_empty());
+    }
+
+    #[test]
+    fn test_retrieve_proteins_grouped_by_taxon() {
+        let searcher = get_example_taxon_searcher();
+
+        // the suffix at position 2 starts with a separation character, so it is skipped
+        let proteins_by_taxon = searcher.retrieve_proteins_grouped_by_taxon(&[0, 3, 11, 2, 12, 14, 5]);
+        let uniprot_ids = |taxon_id: u32| -> Vec<&str> {
+            proteins_by_taxon[&taxon_id].iter().map(|protein| protein.uniprot_id.as_str()).collect()
+        };
+
+        assert_eq!(proteins_by_taxon.len(), 3);
+        assert_eq!(uniprot_ids(1), vec!["P1"]);
+        assert_eq!(uniprot_ids(2), vec!["P2", "P3", "P3", "P2"]);
+        assert_eq!(uniprot_ids(3), vec!["P4"]);
+
+        assert!(searcher.retrieve_proteins_grouped_by_taxon(&[]).is_empty());
+    }
+
+    #[test]
+    fn test_peptide_uniqueness() {
+        let searcher = get_example_taxon_searcher();
+
+        // both proteins that contain "AC" belong to taxon 2
+        assert_eq!(searcher.peptide_uniqueness(b"AC", false), Uniqueness::Unique(2));
+        // "C" occurs in proteins of taxon 2 and 3
+        assert_eq!(searcher.peptide_uniqueness(b"C", false), Uniqueness::Shared(vec![2, 3]));
+        assert_eq!(searcher.peptide_uniqueness(b"W", false), Uniqueness::NoMatches);
+    }
+
+    #[test]
+    fn test_protein_at_text_position() {
+        let sparse_mapping: Box<dyn SuffixToProteinIndex> =
+            Box::new(SparseSuffixToProtein::new(&get_example_proteins().text));
+        let dense_mapping: Box<dyn SuffixToProteinIndex> =
+            Box::new(DenseSuffixToProtein::new(&get_example_proteins().text));
+
+        for suffix_index_to_protein in [sparse_mapping, dense_mapping] {
+            let sa = vec![19, 10, 2, 13, 9, 8, 11, 5, 0, 12, 3, 15, 6, 1, 4, 17, 14, 16, 7, 18];
+            let searcher = Searcher::new(SuffixArray::Original(sa, 1), get_example_proteins(), suffix_index_to_protein);
+
+            // text: AI-CLACVAA-AC-KCRLY$
+            let protein_index_at = |pos: usize| {
+                searcher
+                    .protein_at_text_position(pos)
+                    .map(|protein| searcher.proteins.proteins.iter().position(|p| std::ptr::eq(p, protein)).unwrap())
+            };
+            assert_eq!(protein_index_at(0), Some(0));
+            assert_eq!(protein_index_at(1), Some(0));
+            assert_eq!(protein_index_at(3), Some(1));
+            assert_eq!(protein_index_at(9), Some(1));
+            assert_eq!(protein_index_at(11), Some(2));
+            assert_eq!(protein_index_at(18), Some(3));
+
+            assert_eq!(protein_index_at(2), None);
+            assert_eq!(protein_index_at(13), None);
+            assert_eq!(protein_index_at(19), None);
+            assert_eq!(protein_index_at(20), None);
+        }
+    }
+
+    #[test]
+    fn test_protein_bounds_at() {
+        let proteins = get_example_proteins();
+        let sa = SuffixArray::Original(vec![19, 10, 2, 13, 9, 8, 11, 5, 0, 12, 3, 15, 6, 1, 4, 17, 14, 16, 7, 18], 1);
+
+        let suffix_index_to_protein = SparseSuffixToProtein::new(&proteins.text);
+        let searcher = Searcher::new(sa, proteins, Box::new(suffix_index_to_protein));
+
+        // text: AI-CLACVAA-AC-KCRLY$
+        assert_eq!(searcher.protein_bounds_at(0), Some((0, 2)));
+        assert_eq!(searcher.protein_bounds_at(1), Some((0, 2)));
+        assert_eq!(searcher.protein_bounds_at(3), Some((3, 10)));
+        assert_eq!(searcher.protein_bounds_at(6), Some((3, 10)));
+        assert_eq!(searcher.protein_bounds_at(9), Some((3, 10)));
+        assert_eq!(searcher.protein_bounds_at(12), Some((11, 13)));
+        assert_eq!(searcher.protein_bounds_at(18), Some((14, 19)));
+
+        assert_eq!(searcher.protein_bounds_at(2), None);
+        assert_eq!(searcher.protein_bounds_at(10), None);
+        assert_eq!(searcher.protein_bounds_at(19), None);
+        assert_eq!(searcher.protein_bounds_at(20), None);
+    }
+
+    #[test]
+    fn test_search_custom_delimiters() {
+        // the proteins contain gaps, so they are separated by * and terminated by #
+        let delimiters = Delimiters::new(b'*', b'#').unwrap();
+        let text = ProteinText::from_string_with_alphabet("AC-KE*C-KEA#", &delimiters.alphabet());
+        let proteins = Proteins {
+            text,
+            proteins: vec![
+                Protein { uniprot_id: "P1".to_string(), taxon_id: 1, functional_annotations: vec![] },
+                Protein { uniprot_id: "P2".to_string(), taxon_id: 2, functional_annotations: vec![] }
+            ],
+            accession_index: None,
+            original_sequences: None,
+            delimiters,
+            skipped_accessions: Vec::new()
+        };
+        let searcher = Searcher::from_proteins(proteins, 1).unwrap();
+
+        let SearchAllSuffixesResult::SearchResult(mut suffixes) =
+            searcher.search_matching_suffixes(b"C-KE", usize::MAX, false, false)
+        else {
+            panic!("Expected a search result");
+        };
+        suffixes.sort();
+        assert_eq!(suffixes, vec![1, 6]);
+
+        let uniprot_ids: Vec<&str> =
+            searcher.retrieve_proteins(&suffixes).iter().map(|protein| protein.uniprot_id.as_str()).collect();
+        assert_eq!(uniprot_ids, vec!["P1", "P2"]);
+
+        // the match in the second protein starts after a separation character and ends at the
+        // termination character
+        assert_eq!(
+            searcher.search_matching_suffixes(b"C-KEA", usize::MAX, false, true),
+            SearchAllSuffixesResult::SearchResult(vec![6])
+        );
+
+        assert_eq!(searcher.protein_bounds_at(2), Some((0, 5)));
+        assert_eq!(searcher.protein_bounds_at(7), Some((6, 11)));
+        assert_eq!(searcher.protein_bounds_at(5), None);
+        assert_eq!(searcher.protein_bounds_at(11), None);
+    }
+
+    #[test]
+    fn test_search_matching_suffixes_max_result_bytes() {
+        let proteins = get_example_proteins();
+        let sa = SuffixArray::Original(vec![19, 10, 2, 13, 9, 8, 11, 5, 0, 12, 3, 15, 6, 1, 4, 17, 14, 16, 7, 18], 1);
+
+        let suffix_index_to_protein = SparseSuffixToProtein::new(&proteins.text);
+        let mut searcher = Searcher::new(sa, proteins, Box::new(suffix_index_to_protein));
+
+        // "A" occurs 5 times, but the budget only allows 2 suffixes
+        searcher.max_result_bytes = 2 * std::mem::size_of::<i64>();
+        match searcher.search_matching_suffixes(b"A", usize::MAX, false, false) {
+            SearchAllSuffixesResult::MaxMatches(matches) => assert_eq!(matches.len(), 2),
+            _ => panic!("the search should stop when the byte budget is reached")
+        }
+
+        // a budget larger than the result does not change the result
+        searcher.max_result_bytes = 100 * std::mem::size_of::<i64>();
+        assert_eq!(
+            searcher.search_matching_suffixes(b"A", usize::MAX, false, false),
+            SearchAllSuffixesResult::SearchResult(vec![0, 5, 8, 9, 11])
+        );
+    }
+
+    #[test]
+    fn test_search_matching_suffixes_sorted() {
+        let proteins = get_example_proteins();
+        let dense_sa =
+            SuffixArray::Original(vec![19, 10, 2, 13, 9, 8, 11, 5, 0, 3, 12, 15, 6, 1, 4, 17, 14, 16, 7, 18], 1);
+        let suffix_index_to_protein = SparseSuffixToProtein::new(&proteins.text);
+        let mut dense_searcher = Searcher::new(dense_sa, proteins, Box::new(suffix_index_to_protein));
+
+        let proteins = get_example_proteins();
+        let sparse_sa = SuffixArray::Original(vec![9, 0, 3, 12, 15, 6, 18], 3);
+        let suffix_index_to_protein = SparseSuffixToProtein::new(&proteins.text);
+        let mut sparse_searcher = Searcher::new(sparse_sa, proteins, Box::new(suffix_index_to_protein));
+
+        // without sorting, the order follows the suffix array
+        assert_eq!(
+            dense_searcher.search_matching_suffixes(b"AC", usize::MAX, false, false),
+            SearchAllSuffixesResult::SearchResult(vec![11, 5])
+        );
+
+        dense_searcher.sort_matches = true;
+        sparse_searcher.sort_matches = true;
+        for searcher in [&dense_searcher, &sparse_searcher] {
+            assert_eq!(
+                searcher.search_matching_suffixes(b"AC", usize::MAX, false, false),
+                SearchAllSuffixesResult::SearchResult(vec![5, 11])
+            );
+        }
+    }
+
+    #[test]
+    fn test_searcher_from_proteins() {
+        let dense_searcher = Searcher::from_proteins(get_example_proteins(), 1).unwrap();
+        assert_eq!(
+            dense_searcher.sa.iter().collect::<Vec<i64>>(),
+            vec![19, 10, 2, 13, 9, 8, 11, 5, 0, 12, 3, 15, 6, 1, 4, 17, 14, 16, 7, 18]
+        );
+
+        let sparse_searcher = Searcher::from_proteins(get_example_proteins(), 3).unwrap();
+        assert_eq!(sparse_searcher.sa.iter().collect::<Vec<i64>>(), vec![9, 0, 12, 3, 15, 6, 18]);
+
+        for searcher in [&dense_searcher, &sparse_searcher] {
+            assert_eq!(
+                searcher.search_matching_suffixes(b"VAA", usize::MAX, false, false),
+                SearchAllSuffixesResult::SearchResult(vec![7])
+            );
+            assert_eq!(
+                searcher.search_matching_suffixes(b"RIY", usize::MAX, true, false),
+                SearchAllSuffixesResult::SearchResult(vec![16])
+            );
+        }
+
+        assert!(Searcher::from_proteins(get_example_proteins(), 0).is_err());
+    }
+
+    #[test]
+    fn test_verify() {
+        let dense_searcher = Searcher::from_proteins(get_example_proteins(), 1).unwrap();
+        let sparse_searcher = Searcher::from_proteins(get_example_proteins(), 3).unwrap();
+        for searcher in [&dense_searcher, &sparse_searcher] {
+            assert_eq!(searcher.verify(usize::MAX), Ok(()));
+            assert_eq!(searcher.verify(4), Ok(()));
+            assert_eq!(searcher.verify(0), Ok(()));
+        }
+
+        let mut sa = dense_searcher.sa.iter().collect::<Vec<i64>>();
+        sa.swap(10, 11);
+        let proteins = get_example_proteins();
+        let suffix_index_to_protein = SparseSuffixToProtein::new(&proteins.text);
+        let shuffled_searcher =
+            Searcher::new(SuffixArray::Original(sa, 1), proteins, Box::new(suffix_index_to_protein));
+        assert_eq!(shuffled_searcher.verify(usize::MAX), Err(VerifyError::OutOfOrder { index: 10 }));
+
+        let mut sa = dense_searcher.sa.iter().collect::<Vec<i64>>();
+        sa[5] = 20;
+        let proteins = get_example_proteins();
+        let suffix_index_to_protein = SparseSuffixToProtein::new(&proteins.text);
+        let out_of_bounds_searcher =
+            Searcher::new(SuffixArray::Original(sa, 1), proteins, Box::new(suffix_index_to_protein));
+        assert_eq!(
+            out_of_bounds_searcher.verify(usize::MAX),
+            Err(VerifyError::SuffixOutOfBounds { index: 5, suffix: 20 })
+        );
+    }
+
+    #[test]
+    fn test_text_position_to_rank() {
+        let mut searcher = Searcher::from_proteins(get_example_proteins(), 1).unwrap();
+        assert_eq!(searcher.text_position_to_rank(0), None);
+
+        searcher.build_inverse().unwrap();
+
+        // compare with a brute-force computation of the inverse
+        let text_length = searcher.proteins.text.len();
+        for pos in 0..text_length {
+            let rank = (0..searcher.sa.len()).find(|&sa_index| searcher.sa.get(sa_index) == pos as i64);
+            assert_eq!(searcher.text_position_to_rank(pos), rank);
+        }
+        assert_eq!(searcher.text_position_to_rank(text_length), None);
+    }
+
+    #[test]
+    fn test_build_inverse_sparse() {
+        let mut searcher = Searcher::from_proteins(get_example_proteins(), 3).unwrap();
+        assert!(searcher.build_inverse().is_err());
+        assert_eq!(searcher.text_position_to_rank(0), None);
+    }
+
+    #[test]
+    fn test_search_sorted_batch() {
+        let dense_searcher = Searcher::from_proteins(get_example_proteins(), 1).unwrap();
+        let sparse_searcher = Searcher::from_proteins(get_example_proteins(), 3).unwrap();
+
+        // the sparse suffix array can only search peptides of at least 3 residues
+        let peptides: Vec<Vec<u8>> = [&b"KCR"[..], b"ACV", b"CLA", b"AAC", b"CIA", b"WWW", b"KCRLY", b"CLA", b"RLY$"]
+            .iter()
+            .map(|peptide| peptide.to_vec())
+            .collect();
+        let mut dense_peptides = peptides.clone();
+        dense_peptides.extend([b"".to_vec(), b"A".to_vec(), b"AC".to_vec()]);
+
+        for (searcher, peptides) in [(dense_searcher, dense_peptides), (sparse_searcher, peptides)] {
+            for equate_il in [false, true] {
+                let batch_results = searcher.search_sorted_batch(&peptides, equate_il);
+                assert_eq!(batch_results.len(), peptides.len());
+
+                for (peptide, batch_result) in peptides.iter().zip(batch_results) {
+                    assert_eq!(batch_result, searcher.search_matching_suffixes(peptide, usize::MAX, equate_il, false));
+                }
+            }
+        }
+    }
+
+    fn get_il_distinguished_searcher(sparseness_factor: u8) -> Searcher {
+        let proteins = get_example_proteins();
+
+        // sort the suffixes without translating the L's to I's
+        let text = proteins.text.decode_range(0, proteins.text.len());
+        let mut sa: Vec<i64> = (0..text.len() as i64).step_by(sparseness_factor as usize).collect();
+        sa.sort_unstable_by(|&a, &b| text[a as usize..].cmp(&text[b as usize..]));
+
+        let suffix_index_to_protein = SparseSuffixToProtein::new(&proteins.text);
+        let mut searcher =
+            Searcher::new(SuffixArray::Original(sa, sparseness_factor), proteins, Box::new(suffix_index_to_protein));
+        searcher.il_equated = false;
+        searcher
+    }
+
+    #[test]
+    fn test_search_il_distinguished() {
+        for searcher in [get_il_distinguished_searcher(1), get_il_distinguished_searcher(2)] {
+            assert_eq!(searcher.verify(usize::MAX), Ok(()));
+
+            for equate_il in [false, true] {
+                // I and L are always distinguished, since the suffix array distinguishes them
+                assert_eq!(
+                    searcher.search_matching_suffixes(b"CLA", usize::MAX, equate_il, false),
+                    SearchAllSuffixesResult::SearchResult(vec![3])
+                );
+                assert_eq!(
+                    searcher.search_matching_suffixes(b"RLY", usize::MAX, equate_il, false),
+                    SearchAllSuffixesResult::SearchResult(vec![16])
+                );
+                assert_eq!(
+                    searcher.search_matching_suffixes(b"CIA", usize::MAX, equate_il, false),
+                    SearchAllSuffixesResult::NoMatches
+                );
+                assert_eq!(
+                    searcher.search_matching_suffixes(b"AL", usize::MAX, equate_il, false),
+                    SearchAllSuffixesResult::NoMatches
+                );
+                assert_eq!(searcher.count_matching_suffixes(b"AC", usize::MAX, equate_il), 2);
+
+                let peptides: Vec<Vec<u8>> = [&b"RLY"[..], b"AI", b"CLA", b"KCR"].iter().map(|p| p.to_vec()).collect();
+                for (peptide, batch_result) in peptides.iter().zip(searcher.search_sorted_batch(&peptides, equate_il)) {
+                    assert_eq!(batch_result, searcher.search_matching_suffixes(peptide, usize::MAX, equate_il, false));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_resolve_suffix() {
+        let dense_searcher = Searcher::from_proteins(get_example_proteins(), 1).unwrap();
+        let sparse_searcher = Searcher::from_proteins(get_example_proteins(), 3).unwrap();
+
+        // the first suffix in the sparse suffix array starts at position 9
+        assert_eq!(sparse_searcher.resolve_suffix(0, 0), Some(9));
+        assert_eq!(sparse_searcher.resolve_suffix(0, 2), Some(7));
+        // the second suffix starts at position 0, so no match can start before it
+        assert_eq!(sparse_searcher.resolve_suffix(1, 1), None);
+
+        for peptide in [&b"AC"[..], b"CLA", b"KCRLY", b"VAA-A"] {
+            let mut dense_positions: Vec<i64> = match dense_searcher.search_bounds(peptide) {
+                BoundSearchResult::SearchResult((min_bound, max_bound)) => {
+                    (min_bound..max_bound).filter_map(|sa_index| dense_searcher.resolve_suffix(sa_index, 0)).collect()
+                }
+                BoundSearchResult::NoMatches => vec![]
+            };
+
+            // resolve all the suffixes matching the peptide without its first `skip` characters, and
+            // keep the ones of which the skipped characters match as well
+            let mut sparse_positions = vec![];
+            for skip in 0..3 {
+                if let BoundSearchResult::SearchResult((min_bound, max_bound)) =
+                    sparse_searcher.search_bounds(&peptide[skip..])
+                {
+                    sparse_positions.extend((min_bound..max_bound).filter_map(|sa_index| {
+                        sparse_searcher.resolve_suffix(sa_index, skip).filter(|&position| {
+                            sparse_searcher.proteins.text.range_equals(position as usize, &peptide[..skip])
+                        })
+                    }));
+                }
+            }
+
+            dense_positions.sort_unstable();
+            sparse_positions.sort_unstable();
+            assert!(!dense_positions.is_empty());
+            assert_eq!(sparse_positions, dense_positions);
+        }
+    }
+
+    #[test]
+    fn test_longest_matching_prefix() {
+        let dense_searcher = Searcher::from_proteins(get_example_proteins(), 1).unwrap();
+        let sparse_searcher = Searcher::from_proteins(get_example_proteins(), 3).unwrap();
+
+        for searcher in [&dense_searcher, &sparse_searcher] {
+            // only the first 4 residues match
+            assert_eq!(searcher.longest_matching_prefix(b"CLACW", false), 4);
+            assert_eq!(searcher.longest_matching_prefix(b"CIACW", true), 4);
+
+            // the full search string matches
+            assert_eq!(searcher.longest_matching_prefix(b"CLACVAA", false), 7);
+
+            assert_eq!(searcher.longest_matching_prefix(b"WWW", false), 0);
+        }
+
+        // prefixes shorter than the sparseness factor can not be searched
+        assert_eq!(dense_searcher.longest_matching_prefix(b"CIACW", false), 1);
+        assert_eq!(sparse_searcher.longest_matching_prefix(b"CIACW", false), 0);
+    }
 }
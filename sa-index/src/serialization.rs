@@ -0,0 +1,111 @@
+//! This module contains the serde implementations of the `SuffixArray`, which are used to make
+//! snapshots of small suffix arrays. The compressed variant is serialized as its packed data, so the
+//! values do not have to be unpacked.
+
+use bitarray::{BitArray, Binary};
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::SuffixArray;
+
+/// The serialized representation of a `SuffixArray`
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum SuffixArraySnapshot {
+    Original {
+        values: Vec<i64>,
+        sample_rate: u8
+    },
+    Compressed {
+        data: Vec<u64>,
+        len: usize,
+        bits_per_value: usize,
+        sample_rate: u8
+    }
+}
+
+/// Returns the amount of 64-bit words used to store `len` values of `bits_per_value` bits
+fn word_count(len: usize, bits_per_value: usize) -> usize {
+    (len * bits_per_value).div_ceil(64)
+}
+
+impl Serialize for SuffixArray {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let snapshot = match self {
+            SuffixArray::Original(values, sample_rate) => {
+                SuffixArraySnapshot::Original { values: values.clone(), sample_rate: *sample_rate }
+            }
+            SuffixArray::Compressed(values, sample_rate) => SuffixArraySnapshot::Compressed {
+                data: values.get_data_slice(0, word_count(values.len(), values.bits_per_value())).to_vec(),
+                len: values.len(),
+                bits_per_value: values.bits_per_value(),
+                sample_rate: *sample_rate
+            }
+        };
+
+        snapshot.serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for SuffixArray {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        match SuffixArraySnapshot::deserialize(deserializer)? {
+            SuffixArraySnapshot::Original { values, sample_rate } => Ok(SuffixArray::Original(values, sample_rate)),
+            SuffixArraySnapshot::Compressed { data, len, bits_per_value, sample_rate } => {
+                if bits_per_value == 0 || bits_per_value >= 64 {
+                    return Err(de::Error::custom(format!("Invalid amount of bits per value: {}", bits_per_value)));
+                }
+
+                let expected_words = word_count(len, bits_per_value);
+                if data.len() != expected_words {
+                    return Err(de::Error::invalid_length(data.len(), &expected_words.to_string().as_str()));
+                }
+
+                let bytes: Vec<u8> = data.iter().flat_map(|word| word.to_le_bytes()).collect();
+                let mut values = BitArray::with_capacity(len, bits_per_value);
+                values.read_binary_exact(bytes.as_slice(), expected_words).map_err(de::Error::custom)?;
+
+                Ok(SuffixArray::Compressed(values, sample_rate))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_round_trip_original() {
+        let sa = SuffixArray::Original(vec![5, 3, 1, 4, 2], 2);
+
+        let json = serde_json::to_string(&sa).unwrap();
+        assert_eq!(json, r#"{"original":{"values":[5,3,1,4,2],"sample_rate":2}}"#);
+
+        let deserialized: SuffixArray = serde_json::from_str(&json).unwrap();
+        assert!(!deserialized.is_compressed());
+        assert_eq!(deserialized.sample_rate(), 2);
+        assert_eq!(deserialized.iter().collect::<Vec<i64>>(), vec![5, 3, 1, 4, 2]);
+    }
+
+    #[test]
+    fn test_round_trip_compressed() {
+        let mut bitarray = BitArray::with_capacity(5, 40);
+        for (i, value) in [5, 3, 1, 4, 2].iter().enumerate() {
+            bitarray.set(i, *value);
+        }
+        let sa = SuffixArray::Compressed(bitarray, 3);
+
+        let json = serde_json::to_string(&sa).unwrap();
+        let deserialized: SuffixArray = serde_json::from_str(&json).unwrap();
+        assert!(deserialized.is_compressed());
+        assert_eq!(deserialized.sample_rate(), 3);
+        assert_eq!(deserialized.bits_per_value(), 40);
+        assert_eq!(deserialized.iter().collect::<Vec<i64>>(), vec![5, 3, 1, 4, 2]);
+    }
+
+    #[test]
+    fn test_deserialize_compressed_wrong_length() {
+        let json = r#"{"compressed":{"data":[1],"len":5,"bits_per_value":40,"sample_rate":1}}"#;
+        assert!(serde_json::from_str::<SuffixArray>(json).is_err());
+    }
+}
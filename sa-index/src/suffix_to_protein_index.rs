@@ -1,5 +1,5 @@
 use clap::ValueEnum;
-use sa_mappings::proteins::{SEPARATION_CHARACTER, TERMINATION_CHARACTER};
+use sa_mappings::proteins::Delimiters;
 use text_compression::ProteinText;
 
 use crate::Nullable;
@@ -49,7 +49,7 @@ impl SuffixToProteinIndex for SparseSuffixToProtein {
     fn suffix_to_protein(&self, suffix: i64) -> u32 {
         let protein_index = self.mapping.binary_search(&suffix).unwrap_or_else(|index| index - 1);
         // if the next value in the mapping is 1 larger than the current suffix, that means that the
-        // current suffix starts with a SEPARATION_CHARACTER or TERMINATION_CHARACTER
+        // current suffix starts with a separation or termination character
         // this means it does not belong to a protein
         if self.mapping[protein_index + 1] == suffix + 1 {
             return u32::NULL;
@@ -68,10 +68,23 @@ impl DenseSuffixToProtein {
     ///
     /// Returns a new DenseSuffixToProtein build over the provided text
     pub fn new(text: &ProteinText) -> Self {
+        Self::new_with_delimiters(text, &Delimiters::default())
+    }
+
+    /// Creates a new DenseSuffixToProtein mapping over a text that uses the given delimiters
+    ///
+    /// # Arguments
+    /// * `text` - The text over which we want to create the mapping
+    /// * `delimiters` - The characters that separate the proteins in the text and terminate it
+    ///
+    /// # Returns
+    ///
+    /// Returns a new DenseSuffixToProtein build over the provided text
+    pub fn new_with_delimiters(text: &ProteinText, delimiters: &Delimiters) -> Self {
         let mut current_protein_index: u32 = 0;
         let mut suffix_index_to_protein: Vec<u32> = vec![];
         for char in text.iter() {
-            if char == SEPARATION_CHARACTER || char == TERMINATION_CHARACTER {
+            if delimiters.contains(char) {
                 current_protein_index += 1;
                 suffix_index_to_protein.push(u32::NULL);
             } else {
@@ -94,9 +107,22 @@ impl SparseSuffixToProtein {
     ///
     /// Returns a new SparseSuffixToProtein build over the provided text
     pub fn new(text: &ProteinText) -> Self {
+        Self::new_with_delimiters(text, &Delimiters::default())
+    }
+
+    /// Creates a new SparseSuffixToProtein mapping over a text that uses the given delimiters
+    ///
+    /// # Arguments
+    /// * `text` - The text over which we want to create the mapping
+    /// * `delimiters` - The characters that separate the proteins in the text and terminate it
+    ///
+    /// # Returns
+    ///
+    /// Returns a new SparseSuffixToProtein build over the provided text
+    pub fn new_with_delimiters(text: &ProteinText, delimiters: &Delimiters) -> Self {
         let mut suffix_index_to_protein: Vec<i64> = vec![0];
         for (index, char) in text.iter().enumerate() {
-            if char == SEPARATION_CHARACTER || char == TERMINATION_CHARACTER {
+            if delimiters.contains(char) {
                 suffix_index_to_protein.push(index as i64 + 1);
             }
         }
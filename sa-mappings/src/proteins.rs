@@ -1,11 +1,22 @@
 //! This module contains the `Protein` and `Proteins` structs, which are used to represent proteins
 //! and collections of proteins, respectively.
 
-use std::{error::Error, fs::File, io::BufReader, ops::Index, str::from_utf8};
+use std::{
+    collections::{HashMap, HashSet},
+    error::Error,
+    fs::File,
+    io::{BufRead, BufReader, Write},
+    ops::Index,
+    str::{from_utf8, FromStr}
+};
 
 use bytelines::ByteLines;
 use fa_compression::algorithm1::{decode, encode};
-use text_compression::ProteinText;
+use flate2::bufread::MultiGzDecoder;
+use index_format::{read_header, write_header, FileKind, CUSTOM_DELIMITERS_FLAG};
+#[cfg(feature = "serde")]
+use serde::{de, ser, Deserialize, Deserializer, Serialize, Serializer};
+use text_compression::{dump_protein_text, load_compressed_text, ProteinText, PROTEIN_ALPHABET};
 
 /// The separation character used in the input string
 pub static SEPARATION_CHARACTER: u8 = b'-';
@@ -14,7 +25,131 @@ pub static SEPARATION_CHARACTER: u8 = b'-';
 /// This character should be smaller than the separation character
 pub static TERMINATION_CHARACTER: u8 = b'$';
 
+/// The characters that separate the proteins in the input string and terminate it. Sequences that
+/// contain the default characters (e.g. `-` in gapped alignments) can use other delimiters.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Delimiters {
+    /// The character between two proteins
+    pub separation_character: u8,
+
+    /// The character at the end of the input string, which should be smaller than the separation
+    /// character
+    pub termination_character: u8
+}
+
+impl Default for Delimiters {
+    fn default() -> Self {
+        Delimiters { separation_character: SEPARATION_CHARACTER, termination_character: TERMINATION_CHARACTER }
+    }
+}
+
+impl Delimiters {
+    /// Creates the delimiters and checks that they can be used in the input string
+    ///
+    /// # Arguments
+    /// * `separation_character` - The character between two proteins
+    /// * `termination_character` - The character at the end of the input string
+    ///
+    /// # Returns
+    ///
+    /// Returns a `Result` containing the `Delimiters`
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a delimiter is a letter or not an ascii character, or if the termination
+    /// character is not smaller than the separation character, which is required to sort the
+    /// suffixes of the last protein before those of the other proteins
+    pub fn new(separation_character: u8, termination_character: u8) -> Result<Self, String> {
+        for character in [separation_character, termination_character] {
+            if !character.is_ascii() || character.is_ascii_alphabetic() {
+                return Err(format!(
+                    "The delimiter {:#04x} should be an ascii character that is not a letter",
+                    character
+                ));
+            }
+        }
+
+        if termination_character >= separation_character {
+            return Err(format!(
+                "The termination character '{}' should be smaller than the separation character '{}'",
+                termination_character as char, separation_character as char
+            ));
+        }
+
+        Ok(Delimiters { separation_character, termination_character })
+    }
+
+    /// Returns the alphabet of a text that uses these delimiters: the characters of the default
+    /// protein alphabet that are not a delimiter, followed by the separation and termination
+    /// character. The default delimiters result in the default protein alphabet.
+    pub fn alphabet(&self) -> Vec<u8> {
+        let mut alphabet: Vec<u8> = PROTEIN_ALPHABET.iter().copied().filter(|&c| !self.contains(c)).collect();
+        alphabet.extend([self.separation_character, self.termination_character]);
+        alphabet
+    }
+
+    /// Checks if a character is one of the delimiters
+    pub fn contains(&self, character: u8) -> bool {
+        character == self.separation_character || character == self.termination_character
+    }
+}
+
+/// The amount of residues at the start of every sequence that are checked to be uppercase when the
+/// database is assumed to be uppercase
+const UPPERCASE_SAMPLE_SIZE: usize = 16;
+
+/// The magic bytes at the start of every gzip compressed file
+const GZIP_MAGIC_BYTES: [u8; 2] = [0x1f, 0x8b];
+
+/// What to do with a protein of which the accession already occurred earlier in the database file
+#[derive(Clone, Debug, PartialEq)]
+pub enum DuplicateAccessions {
+    /// Keep all the proteins, even if their accessions are duplicates
+    Keep,
+    /// Keep the first protein with an accession and skip the later duplicates
+    Skip,
+    /// Return an error when a duplicate accession is found
+    Fail
+}
+
+impl FromStr for DuplicateAccessions {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "keep" => Ok(DuplicateAccessions::Keep),
+            "skip" => Ok(DuplicateAccessions::Skip),
+            "fail" => Ok(DuplicateAccessions::Fail),
+            _ => Err(format!("Invalid value '{}', expected one of keep, skip or fail", s))
+        }
+    }
+}
+
+/// How the functional annotations are stored in the columns of a database file
+#[derive(Clone, Debug, PartialEq)]
+pub enum AnnotationFormat {
+    /// The annotations are text, such as `GO:0009279;IPR:IPR016364`, and are encoded when loaded
+    Text,
+    /// The annotations are already encoded, and their bytes are stored as they are
+    Encoded
+}
+
+impl FromStr for AnnotationFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "text" => Ok(AnnotationFormat::Text),
+            "encoded" => Ok(AnnotationFormat::Encoded),
+            _ => Err(format!("Invalid value '{}', expected one of text or encoded", s))
+        }
+    }
+}
+
 /// A struct that represents a protein and its linked information
+#[derive(Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Protein {
     /// The id of the protein
     pub uniprot_id: String,
@@ -26,13 +161,105 @@ pub struct Protein {
     pub functional_annotations: Vec<u8>
 }
 
+/// The concatenated proteins of a database file as a `Vec<u8>`, together with the proteins and the
+/// accessions of the skipped duplicate proteins
+pub type UncompressedProteins = (Vec<u8>, Vec<Protein>, Vec<String>);
+
 /// A struct that represents a collection of proteins
 pub struct Proteins {
     /// The input string containing all proteins
     pub text: ProteinText,
 
     /// The proteins in the input string
-    pub proteins: Vec<Protein>
+    pub proteins: Vec<Protein>,
+
+    /// Optional mapping from uniprot ids to the index of the protein, only present after calling
+    /// `build_accession_index`
+    pub accession_index: Option<HashMap<String, usize>>,
+
+    /// Optional sequences of the proteins in their original case, only present when the proteins
+    /// are loaded with `try_from_database_file_preserving_case`. These are only used for display,
+    /// the text is always uppercase since the suffix array is built over uppercase residues.
+    pub original_sequences: Option<Vec<String>>,
+
+    /// The characters that separate the proteins in the text and terminate it
+    pub delimiters: Delimiters,
+
+    /// The accessions of the proteins that were skipped while loading the database file, because
+    /// they already occurred and duplicate accessions are skipped
+    pub skipped_accessions: Vec<String>
+}
+
+/// The serialized representation of `Proteins`. The text is stored as a string and the accession
+/// index is left out, since it can be rebuilt after deserializing. The alphabet of the text and the
+/// delimiters are only stored if they are not the default ones.
+#[cfg(feature = "serde")]
+#[derive(Serialize, Deserialize)]
+struct ProteinsSnapshot<P, O> {
+    text: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    alphabet: Option<String>,
+    proteins: P,
+    #[serde(default)]
+    original_sequences: Option<O>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    delimiters: Option<Delimiters>
+}
+
+#[cfg(feature = "serde")]
+impl Serialize for Proteins {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let text = String::from_utf8(self.text.decode_range(0, self.text.len())).map_err(ser::Error::custom)?;
+        let alphabet = match self.text.alphabet() {
+            alphabet if alphabet == PROTEIN_ALPHABET => None,
+            alphabet => Some(String::from_utf8(alphabet.to_vec()).map_err(ser::Error::custom)?)
+        };
+
+        ProteinsSnapshot {
+            text,
+            alphabet,
+            proteins: &self.proteins,
+            original_sequences: self.original_sequences.as_ref(),
+            delimiters: Some(self.delimiters).filter(|delimiters| *delimiters != Delimiters::default())
+        }
+        .serialize(serializer)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> Deserialize<'de> for Proteins {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let snapshot = ProteinsSnapshot::<Vec<Protein>, Vec<String>>::deserialize(deserializer)?;
+        let delimiters = match snapshot.delimiters {
+            Some(Delimiters { separation_character, termination_character }) => {
+                Delimiters::new(separation_character, termination_character).map_err(de::Error::custom)?
+            }
+            None => Delimiters::default()
+        };
+
+        // Check the text against the alphabet, since building the text panics on unknown characters
+        let alphabet = snapshot.alphabet.as_deref().map_or(PROTEIN_ALPHABET, str::as_bytes);
+        if alphabet.is_empty() || alphabet.len() > 256 {
+            return Err(de::Error::custom("The alphabet should contain between 1 and 256 characters"));
+        }
+        if let Some(c) = snapshot.text.bytes().find(|c| !alphabet.contains(c)) {
+            return Err(de::Error::custom(format!("Input character '{}' not in alphabet", c as char)));
+        }
+
+        let text = match snapshot.alphabet {
+            Some(_) => ProteinText::from_string_with_alphabet(&snapshot.text, alphabet),
+            None => ProteinText::from_string(&snapshot.text)
+        };
+
+        Ok(Proteins {
+            text,
+            proteins: snapshot.proteins,
+            accession_index: None,
+            original_sequences: snapshot.original_sequences,
+            delimiters,
+            skipped_accessions: Vec::new()
+        })
+    }
 }
 
 impl Protein {
@@ -40,6 +267,38 @@ impl Protein {
     pub fn get_functional_annotations(&self) -> String {
         decode(&self.functional_annotations)
     }
+
+    /// Returns the EC numbers of the protein, such as `EC:1.1.1.1`
+    pub fn ec_number_list(&self) -> Vec<String> {
+        self.annotation_list("EC:")
+    }
+
+    /// Returns the GO terms of the protein, such as `GO:0009279`
+    pub fn go_term_list(&self) -> Vec<String> {
+        self.annotation_list("GO:")
+    }
+
+    /// Returns the InterPro entries of the protein, such as `IPR:IPR016364`
+    pub fn interpro_entry_list(&self) -> Vec<String> {
+        self.annotation_list("IPR:")
+    }
+
+    /// Returns the decoded functional annotations of the protein that start with `prefix`
+    ///
+    /// # Arguments
+    /// * `prefix` - The prefix of the annotation type
+    ///
+    /// # Returns
+    ///
+    /// Returns the annotations in the order they were encoded, or an empty vec if the protein has no
+    /// annotations of this type
+    fn annotation_list(&self, prefix: &str) -> Vec<String> {
+        self.get_functional_annotations()
+            .split(';')
+            .filter(|annotation| annotation.starts_with(prefix))
+            .map(|annotation| annotation.to_string())
+            .collect()
+    }
 }
 
 impl Proteins {
@@ -56,40 +315,309 @@ impl Proteins {
     ///
     /// Returns a `Box<dyn Error>` if an error occurred while reading the database file
     pub fn try_from_database_file(file: &str) -> Result<Self, Box<dyn Error>> {
+        Self::try_from_database_file_with_duplicates(file, &DuplicateAccessions::Keep)
+    }
+
+    /// Creates a new `Proteins` struct from a database file, handling proteins with duplicate
+    /// accessions as configured. Skipped proteins are also left out of the text, so the offsets of
+    /// the kept proteins match the text built by `try_from_database_file_uncompressed`.
+    ///
+    /// # Arguments
+    /// * `file` - The path to the database file
+    /// * `duplicates` - What to do with proteins of which the accession already occurred
+    ///
+    /// # Returns
+    ///
+    /// Returns a `Result` containing the `Proteins` struct
+    ///
+    /// # Errors
+    ///
+    /// Returns a `Box<dyn Error>` if an error occurred while reading the database file, or if a
+    /// duplicate accession was found and `duplicates` is `DuplicateAccessions::Fail`
+    pub fn try_from_database_file_with_duplicates(
+        file: &str,
+        duplicates: &DuplicateAccessions
+    ) -> Result<Self, Box<dyn Error>> {
+        Self::read_database_files(&[file], duplicates, &AnnotationFormat::Text, false, &Delimiters::default())
+    }
+
+    /// Creates a new `Proteins` struct from a database file, using the given characters to separate
+    /// the proteins and terminate the text instead of the default `-` and `$`. The text then uses
+    /// the alphabet of the delimiters, so the default delimiters can occur in the sequences.
+    ///
+    /// # Arguments
+    /// * `file` - The path to the database file
+    /// * `delimiters` - The characters that separate the proteins and terminate the text
+    ///
+    /// # Returns
+    ///
+    /// Returns a `Result` containing the `Proteins` struct
+    ///
+    /// # Errors
+    ///
+    /// Returns a `Box<dyn Error>` if an error occurred while reading the database file, or if a
+    /// sequence contains one of the delimiters
+    pub fn try_from_database_file_with_delimiters(file: &str, delimiters: &Delimiters) -> Result<Self, Box<dyn Error>> {
+        Self::read_database_files(&[file], &DuplicateAccessions::Keep, &AnnotationFormat::Text, false, delimiters)
+    }
+
+    /// Creates a new `Proteins` struct from a database file of which the annotation columns have the
+    /// given format. Encoded annotation columns are read as raw bytes, so they do not have to be
+    /// valid utf8.
+    ///
+    /// # Arguments
+    /// * `file` - The path to the database file
+    /// * `duplicates` - What to do with proteins of which the accession already occurred
+    /// * `annotation_format` - How the functional annotations are stored in the database file
+    ///
+    /// # Returns
+    ///
+    /// Returns a `Result` containing the `Proteins` struct
+    ///
+    /// # Errors
+    ///
+    /// Returns a `Box<dyn Error>` if an error occurred while reading the database file, if a
+    /// duplicate accession was found and `duplicates` is `DuplicateAccessions::Fail`, or if the
+    /// annotations are not valid utf8 and `annotation_format` is `AnnotationFormat::Text`
+    pub fn try_from_database_file_with_annotation_format(
+        file: &str,
+        duplicates: &DuplicateAccessions,
+        annotation_format: &AnnotationFormat
+    ) -> Result<Self, Box<dyn Error>> {
+        Self::read_database_files(&[file], duplicates, annotation_format, false, &Delimiters::default())
+    }
+
+    /// Creates a new `Proteins` struct from multiple database files, for a database that is split
+    /// over multiple files. The proteins of every file are appended in the given order, so the
+    /// result is the same as for a single file containing all the files concatenated.
+    ///
+    /// # Arguments
+    /// * `files` - The paths to the database files
+    ///
+    /// # Returns
+    ///
+    /// Returns a `Result` containing the `Proteins` struct
+    ///
+    /// # Errors
+    ///
+    /// Returns a `Box<dyn Error>` if an error occurred while reading one of the database files
+    pub fn try_from_database_files(files: &[&str]) -> Result<Self, Box<dyn Error>> {
+        Self::read_database_files(
+            files,
+            &DuplicateAccessions::Keep,
+            &AnnotationFormat::Text,
+            false,
+            &Delimiters::default()
+        )
+    }
+
+    /// Creates a new `Proteins` struct from a database file, and also stores the sequences of the
+    /// proteins in their original case, so lowercase masking of e.g. low-complexity regions is not
+    /// lost. The text used for indexing is still uppercased.
+    ///
+    /// # Arguments
+    /// * `file` - The path to the database file
+    /// * `duplicates` - What to do with proteins of which the accession already occurred
+    ///
+    /// # Returns
+    ///
+    /// Returns a `Result` containing the `Proteins` struct
+    ///
+    /// # Errors
+    ///
+    /// Returns a `Box<dyn Error>` if an error occurred while reading the database file, or if a
+    /// duplicate accession was found and `duplicates` is `DuplicateAccessions::Fail`
+    pub fn try_from_database_file_preserving_case(
+        file: &str,
+        duplicates: &DuplicateAccessions
+    ) -> Result<Self, Box<dyn Error>> {
+        Self::read_database_files(&[file], duplicates, &AnnotationFormat::Text, true, &Delimiters::default())
+    }
+
+    /// Reads the proteins from one or more database files, in the given order
+    ///
+    /// # Arguments
+    /// * `files` - The paths to the database files
+    /// * `duplicates` - What to do with proteins of which the accession already occurred
+    /// * `annotation_format` - How the functional annotations are stored in the database files
+    /// * `preserve_case` - If the sequences should also be stored in their original case
+    /// * `delimiters` - The characters that separate the proteins and terminate the text
+    ///
+    /// # Returns
+    ///
+    /// Returns a `Result` containing the `Proteins` struct
+    ///
+    /// # Errors
+    ///
+    /// Returns a `Box<dyn Error>` if an error occurred while reading one of the database files, if
+    /// a duplicate accession was found and `duplicates` is `DuplicateAccessions::Fail`, if the
+    /// annotations are not valid utf8 and `annotation_format` is `AnnotationFormat::Text`, or if a
+    /// sequence contains one of the delimiters
+    fn read_database_files(
+        files: &[&str],
+        duplicates: &DuplicateAccessions,
+        annotation_format: &AnnotationFormat,
+        preserve_case: bool,
+        delimiters: &Delimiters
+    ) -> Result<Self, Box<dyn Error>> {
         let mut input_string: String = String::new();
         let mut proteins: Vec<Protein> = Vec::new();
+        let mut original_sequences: Option<Vec<String>> = if preserve_case { Some(Vec::new()) } else { None };
+        let mut seen_accessions: HashSet<String> = HashSet::new();
+        let mut skipped_accessions: Vec<String> = Vec::new();
 
-        let file = File::open(file)?;
+        // Every sequence is followed by a separator, so the last protein of a file is also
+        // separated from the first protein of the next file
+        for file in files {
+            let reader = open_database_file(file)?;
 
-        // Read the lines as bytes, since the input string is not guaranteed to be utf8
-        // because of the encoded functional annotations
-        let mut lines = ByteLines::new(BufReader::new(file));
+            // Read the lines as bytes, since the input string is not guaranteed to be utf8
+            // because of the encoded functional annotations
+            let mut lines = ByteLines::new(reader);
 
-        while let Some(Ok(line)) = lines.next() {
-            let mut fields = line.split(|b| *b == b'\t');
+            while let Some(line) = lines.next() {
+                let line = line?;
+                let mut fields = line.split(|b| *b == b'\t');
 
-            // uniprot_id, taxon_id and sequence should always contain valid utf8
-            let uniprot_id = from_utf8(fields.next().unwrap())?;
-            let taxon_id = from_utf8(fields.next().unwrap())?.parse()?;
-            let sequence = from_utf8(fields.next().unwrap())?;
-            let functional_annotations: Vec<u8> = encode(from_utf8(fields.next().unwrap())?);
+                // uniprot_id, taxon_id and sequence should always contain valid utf8
+                let uniprot_id = from_utf8(fields.next().unwrap())?;
+                if !keep_accession(uniprot_id, duplicates, &mut seen_accessions, &mut skipped_accessions)? {
+                    continue;
+                }
 
-            input_string.push_str(&sequence.to_uppercase());
-            input_string.push(SEPARATION_CHARACTER.into());
+                let taxon_id = from_utf8(fields.next().unwrap())?.parse()?;
+                let sequence = from_utf8(fields.next().unwrap())?;
+                if sequence.bytes().any(|c| delimiters.contains(c)) {
+                    return Err(format!("The sequence of protein {} contains a delimiter", uniprot_id).into());
+                }
+
+                // encoded functional annotations are not guaranteed to be utf8, so they are stored
+                // as raw bytes
+                let annotations = fields.next().unwrap();
+                let functional_annotations: Vec<u8> = match annotation_format {
+                    AnnotationFormat::Text => encode(from_utf8(annotations)?),
+                    AnnotationFormat::Encoded => annotations.to_vec()
+                };
+
+                input_string.push_str(&sequence.to_uppercase());
+                input_string.push(delimiters.separation_character.into());
+
+                if let Some(original_sequences) = original_sequences.as_mut() {
+                    original_sequences.push(sequence.to_string());
+                }
 
-            proteins.push(Protein {
-                uniprot_id: uniprot_id.to_string(),
-                taxon_id,
-                functional_annotations
-            });
+                proteins.push(Protein {
+                    uniprot_id: uniprot_id.to_string(),
+                    taxon_id,
+                    functional_annotations
+                });
+            }
         }
 
         input_string.pop();
+        input_string.push(delimiters.termination_character.into());
+        proteins.shrink_to_fit();
+
+        let text = if *delimiters == Delimiters::default() {
+            ProteinText::from_string(&input_string)
+        } else {
+            ProteinText::from_string_with_alphabet(&input_string, &delimiters.alphabet())
+        };
+        Ok(Self {
+            text,
+            proteins,
+            accession_index: None,
+            original_sequences,
+            delimiters: *delimiters,
+            skipped_accessions
+        })
+    }
+
+    /// Creates a new `Proteins` struct from a FASTA file
+    ///
+    /// The accession of each protein is taken from the first word of its header (the middle part
+    /// if the header uses the UniProt `db|accession|name` format). Sequences may be wrapped over
+    /// multiple lines. FASTA files do not contain taxa or functional annotations, so the taxon id
+    /// of every protein is 0 and its functional annotations are empty.
+    ///
+    /// # Arguments
+    /// * `file` - The path to the FASTA file
+    ///
+    /// # Returns
+    ///
+    /// Returns a `Result` containing the `Proteins` struct
+    ///
+    /// # Errors
+    ///
+    /// Returns a `Box<dyn Error>` if an error occurred while reading the FASTA file
+    pub fn try_from_fasta_file(file: &str) -> Result<Self, Box<dyn Error>> {
+        let mut input_string: String = String::new();
+        let mut proteins: Vec<Protein> = Vec::new();
+
+        let reader = open_database_file(file)?;
+        let mut lines = ByteLines::new(reader);
+
+        while let Some(line) = lines.next() {
+            let line = from_utf8(line?)?.trim_end();
+
+            if let Some(header) = line.strip_prefix('>') {
+                if !proteins.is_empty() {
+                    input_string.push(SEPARATION_CHARACTER.into());
+                }
+
+                let identifier = header.split_whitespace().next().unwrap_or_default();
+                let uniprot_id = identifier.split('|').nth(1).unwrap_or(identifier);
+
+                proteins.push(Protein {
+                    uniprot_id: uniprot_id.to_string(),
+                    taxon_id: 0,
+                    functional_annotations: Vec::new()
+                });
+            } else if !line.is_empty() {
+                if proteins.is_empty() {
+                    return Err("FASTA file should start with a header line".into());
+                }
+
+                input_string.push_str(&line.to_uppercase());
+            }
+        }
+
         input_string.push(TERMINATION_CHARACTER.into());
         proteins.shrink_to_fit();
 
         let text = ProteinText::from_string(&input_string);
-        Ok(Self { text, proteins })
+        Ok(Self {
+            text,
+            proteins,
+            accession_index: None,
+            original_sequences: None,
+            delimiters: Delimiters::default(),
+            skipped_accessions: Vec::new()
+        })
+    }
+
+    /// Concatenates protein sequences into the text over which the index is built, in the same way
+    /// as the database file loaders: every sequence is uppercased, the sequences are separated by
+    /// the separation character and the text ends with the termination character.
+    ///
+    /// # Arguments
+    /// * `sequences` - The sequences of the proteins
+    ///
+    /// # Returns
+    ///
+    /// Returns the concatenated text as a `Vec<u8>`
+    pub fn concatenate(sequences: &[&str]) -> Vec<u8> {
+        let mut input_string: String = String::new();
+
+        for sequence in sequences {
+            input_string.push_str(&sequence.to_uppercase());
+            input_string.push(SEPARATION_CHARACTER.into());
+        }
+
+        input_string.pop();
+        input_string.push(TERMINATION_CHARACTER.into());
+
+        input_string.into_bytes()
     }
 
     /// Creates a `ProteinText` which represents all the proteins concatenated from the database file
@@ -107,13 +635,14 @@ impl Proteins {
     pub fn try_from_database_file_without_annotations(database_file: &str) -> Result<ProteinText, Box<dyn Error>> {
         let mut input_string: String = String::new();
 
-        let file = File::open(database_file)?;
+        let reader = open_database_file(database_file)?;
 
         // Read the lines as bytes, since the input string is not guaranteed to be utf8
         // because of the encoded functional annotations
-        let mut lines = ByteLines::new(BufReader::new(file));
+        let mut lines = ByteLines::new(reader);
 
-        while let Some(Ok(line)) = lines.next() {
+        while let Some(line) = lines.next() {
+            let line = line?;
             let mut fields = line.split(|b| *b == b'\t');
 
             // only get the taxon id and sequence from each line, we don't need the other parts
@@ -132,56 +661,427 @@ impl Proteins {
     ///
     /// # Arguments
     /// * `file` - The path to the database file
+    /// * `assume_uppercase` - If the sequences in the database file are already uppercase, in which
+    ///   case they are not converted. Only the first residues of every sequence are checked.
+    /// * `duplicates` - What to do with proteins of which the accession already occurred
     ///
     /// # Returns
     ///
-    /// Returns a `Result` containing the `Vec<u8>`
+    /// Returns a `Result` containing the `Vec<u8>` and the accessions of the skipped duplicate
+    /// proteins
     ///
     /// # Errors
     ///
-    /// Returns a `Box<dyn Error>` if an error occurred while reading the database file
-    pub fn try_from_database_file_uncompressed(database_file: &str) -> Result<Vec<u8>, Box<dyn Error>> {
+    /// Returns a `Box<dyn Error>` if an error occurred while reading the database file, if
+    /// `assume_uppercase` is set and a sequence contains lowercase residues, or if a duplicate
+    /// accession was found and `duplicates` is `DuplicateAccessions::Fail`
+    pub fn try_from_database_file_uncompressed(
+        database_file: &str,
+        assume_uppercase: bool,
+        duplicates: &DuplicateAccessions
+    ) -> Result<(Vec<u8>, Vec<String>), Box<dyn Error>> {
+        let (text, _, skipped_accessions) = Self::read_database_file_uncompressed(
+            database_file,
+            assume_uppercase,
+            duplicates,
+            &AnnotationFormat::Text,
+            false
+        )?;
+        Ok((text, skipped_accessions))
+    }
+
+    /// Creates a `vec<u8>` which represents all the proteins concatenated from the database file,
+    /// together with the proteins in the database file. The database file is only read once, so the
+    /// text and the proteins always match.
+    ///
+    /// # Arguments
+    /// * `database_file` - The path to the database file
+    /// * `assume_uppercase` - If the sequences in the database file are already uppercase, in which
+    ///   case they are not converted. Only the first residues of every sequence are checked.
+    /// * `duplicates` - What to do with proteins of which the accession already occurred
+    /// * `annotation_format` - How the functional annotations are stored in the database file
+    ///
+    /// # Returns
+    ///
+    /// Returns a `Result` containing the `Vec<u8>`, the proteins and the accessions of the skipped
+    /// duplicate proteins
+    ///
+    /// # Errors
+    ///
+    /// Returns a `Box<dyn Error>` if an error occurred while reading the database file, if
+    /// `assume_uppercase` is set and a sequence contains lowercase residues, if a duplicate
+    /// accession was found and `duplicates` is `DuplicateAccessions::Fail`, or if the annotations
+    /// are not valid utf8 and `annotation_format` is `AnnotationFormat::Text`
+    pub fn try_from_database_file_uncompressed_with_proteins(
+        database_file: &str,
+        assume_uppercase: bool,
+        duplicates: &DuplicateAccessions,
+        annotation_format: &AnnotationFormat
+    ) -> Result<UncompressedProteins, Box<dyn Error>> {
+        Self::read_database_file_uncompressed(database_file, assume_uppercase, duplicates, annotation_format, true)
+    }
+
+    /// Reads the concatenated proteins from the database file as a `vec<u8>`
+    ///
+    /// # Arguments
+    /// * `database_file` - The path to the database file
+    /// * `assume_uppercase` - If the sequences in the database file are already uppercase
+    /// * `duplicates` - What to do with proteins of which the accession already occurred
+    /// * `annotation_format` - How the functional annotations are stored in the database file, only
+    ///   used if the proteins are kept
+    /// * `keep_proteins` - If the proteins should also be returned, otherwise the returned vector of
+    ///   proteins is empty
+    ///
+    /// # Returns
+    ///
+    /// Returns a `Result` containing the `Vec<u8>`, the proteins and the accessions of the skipped
+    /// duplicate proteins
+    ///
+    /// # Errors
+    ///
+    /// Returns a `Box<dyn Error>` if an error occurred while reading the database file, if
+    /// `assume_uppercase` is set and a sequence contains lowercase residues, if a duplicate
+    /// accession was found and `duplicates` is `DuplicateAccessions::Fail`, or if the proteins are
+    /// kept and the annotations are not valid utf8 while `annotation_format` is
+    /// `AnnotationFormat::Text`
+    fn read_database_file_uncompressed(
+        database_file: &str,
+        assume_uppercase: bool,
+        duplicates: &DuplicateAccessions,
+        annotation_format: &AnnotationFormat,
+        keep_proteins: bool
+    ) -> Result<UncompressedProteins, Box<dyn Error>> {
         let mut input_string: String = String::new();
+        let mut proteins: Vec<Protein> = Vec::new();
+        let mut seen_accessions: HashSet<String> = HashSet::new();
+        let mut skipped_accessions: Vec<String> = Vec::new();
 
-        let file = File::open(database_file)?;
+        let reader = open_database_file(database_file)?;
 
         // Read the lines as bytes, since the input string is not guaranteed to be utf8
         // because of the encoded functional annotations
-        let mut lines = ByteLines::new(BufReader::new(file));
+        let mut lines = ByteLines::new(reader);
 
-        while let Some(Ok(line)) = lines.next() {
+        let mut line_number = 0;
+        while let Some(line) = lines.next() {
+            let line = line?;
+            line_number += 1;
             let mut fields = line.split(|b| *b == b'\t');
 
-            // only get the taxon id and sequence from each line, we don't need the other parts
-            let sequence = from_utf8(fields.nth(2).unwrap())?;
+            // only get the uniprot id and sequence from each line, unless the proteins are kept
+            let uniprot_id = from_utf8(fields.next().unwrap())?;
+            if !keep_accession(uniprot_id, duplicates, &mut seen_accessions, &mut skipped_accessions)? {
+                continue;
+            }
+            let taxon_id = fields.next().unwrap();
+            let sequence = from_utf8(fields.next().unwrap())?;
 
-            input_string.push_str(&sequence.to_uppercase());
+            if assume_uppercase {
+                if sequence.bytes().take(UPPERCASE_SAMPLE_SIZE).any(|c| c.is_ascii_lowercase()) {
+                    return Err(format!("The sequence on line {} is not uppercase", line_number).into());
+                }
+                input_string.push_str(sequence);
+            } else {
+                input_string.push_str(&sequence.to_uppercase());
+            }
             input_string.push(SEPARATION_CHARACTER.into());
+
+            if keep_proteins {
+                let annotations = fields.next().unwrap();
+                proteins.push(Protein {
+                    uniprot_id: uniprot_id.to_string(),
+                    taxon_id: from_utf8(taxon_id)?.parse()?,
+                    functional_annotations: match annotation_format {
+                        AnnotationFormat::Text => encode(from_utf8(annotations)?),
+                        AnnotationFormat::Encoded => annotations.to_vec()
+                    }
+                });
+            }
         }
 
         input_string.pop();
         input_string.push(TERMINATION_CHARACTER.into());
 
         input_string.shrink_to_fit();
-        Ok(input_string.into_bytes())
+        proteins.shrink_to_fit();
+        Ok((input_string.into_bytes(), proteins, skipped_accessions))
     }
-}
 
-impl Index<usize> for Proteins {
-    type Output = Protein;
+    /// Builds an index that maps the uniprot id of every protein to its position, which is used
+    /// by `get_by_accession`. This index is not built by default to avoid the extra memory usage.
+    pub fn build_accession_index(&mut self) {
+        let accession_index = self
+            .proteins
+            .iter()
+            .enumerate()
+            .map(|(index, protein)| (protein.uniprot_id.clone(), index))
+            .collect();
 
-    fn index(&self, index: usize) -> &Self::Output {
-        &self.proteins[index]
+        self.accession_index = Some(accession_index);
     }
-}
-
-#[cfg(test)]
-mod tests {
-    use std::{fs::File, io::Write, path::PathBuf};
 
-    use tempdir::TempDir;
+    /// Returns the protein with the given uniprot id
+    ///
+    /// # Arguments
+    /// * `id` - The uniprot id of the protein
+    ///
+    /// # Returns
+    ///
+    /// Returns the protein with the given uniprot id, or None if no such protein exists or if the
+    /// accession index was not built using `build_accession_index`
+    pub fn get_by_accession(&self, id: &str) -> Option<&Protein> {
+        let index = *self.accession_index.as_ref()?.get(id)?;
+        Some(&self.proteins[index])
+    }
 
-    use super::*;
+    /// Returns the sequence of the protein at the given index in its original case
+    ///
+    /// # Arguments
+    /// * `index` - The index of the protein
+    ///
+    /// # Returns
+    ///
+    /// Returns the original sequence of the protein, or None if the index is out of bounds or if the
+    /// proteins were not loaded with `try_from_database_file_preserving_case`
+    pub fn get_original_sequence(&self, index: usize) -> Option<&str> {
+        self.original_sequences.as_ref()?.get(index).map(String::as_str)
+    }
+
+    /// Returns the sequence of the protein at the given index, as it is stored in the text. The text
+    /// is scanned up to the protein, using the delimiters of the proteins.
+    ///
+    /// # Arguments
+    /// * `index` - The index of the protein
+    ///
+    /// # Returns
+    ///
+    /// Returns the (uppercase) sequence of the protein, or None if the index is out of bounds
+    pub fn get_sequence(&self, index: usize) -> Option<String> {
+        let mut protein_index = 0;
+        let mut sequence = String::new();
+        for character in self.text.iter() {
+            if self.delimiters.contains(character) {
+                if protein_index == index {
+                    return Some(sequence);
+                }
+                protein_index += 1;
+            } else if protein_index == index {
+                sequence.push(character.into());
+            }
+        }
+
+        None
+    }
+}
+
+/// Writes the proteins to a writer in a binary format, so they can be loaded without parsing the
+/// database file again. The compressed text is written last, since it is read until the end of the
+/// reader when loading.
+///
+/// # Arguments
+///
+/// * `proteins` - The proteins to write.
+/// * `writer` - The writer to which the proteins will be written.
+///
+/// # Errors
+///
+/// Returns an error if writing to the writer fails.
+pub fn dump_proteins(proteins: &Proteins, writer: &mut impl Write) -> Result<(), Box<dyn Error>> {
+    // Write the header to the writer, followed by the delimiters if they are not the default ones
+    if proteins.delimiters == Delimiters::default() {
+        write_header(FileKind::Proteins, 0, writer)?;
+    } else {
+        write_header(FileKind::Proteins, CUSTOM_DELIMITERS_FLAG, writer)?;
+        writer
+            .write_all(&[proteins.delimiters.separation_character, proteins.delimiters.termination_character])
+            .map_err(|_| "Could not write the delimiters to the writer")?;
+    }
+
+    // Write the amount of proteins to the writer
+    writer
+        .write_all(&(proteins.proteins.len() as u64).to_le_bytes())
+        .map_err(|_| "Could not write the amount of proteins to the writer")?;
+
+    // Write every protein to the writer
+    for protein in proteins.proteins.iter() {
+        write_bytes(protein.uniprot_id.as_bytes(), writer)
+            .map_err(|_| "Could not write the uniprot id to the writer")?;
+        writer
+            .write_all(&protein.taxon_id.to_le_bytes())
+            .map_err(|_| "Could not write the taxon id to the writer")?;
+        write_bytes(&protein.functional_annotations, writer)
+            .map_err(|_| "Could not write the functional annotations to the writer")?;
+    }
+
+    // Write the compressed text to the writer
+    dump_protein_text(&proteins.text, writer)
+}
+
+/// Loads the proteins from a reader containing the binary format written by `dump_proteins`.
+///
+/// # Arguments
+///
+/// * `reader` - The reader from which the proteins will be read.
+/// * `allow_legacy` - If files without headers, written before the headers were introduced, are
+///   accepted.
+///
+/// # Errors
+///
+/// Returns an error if reading from the reader fails, or if the reader does not contain proteins.
+pub fn load_proteins(reader: &mut impl BufRead, allow_legacy: bool) -> Result<Proteins, Box<dyn Error>> {
+    let flags = read_header(reader, FileKind::Proteins, allow_legacy)?;
+
+    // Read the separation and termination character (2 bytes), if they are not the default ones
+    let delimiters = if flags & CUSTOM_DELIMITERS_FLAG != 0 {
+        let mut delimiters_buffer = [0_u8; 2];
+        reader
+            .read_exact(&mut delimiters_buffer)
+            .map_err(|_| "Could not read the delimiters from the binary file")?;
+        Delimiters::new(delimiters_buffer[0], delimiters_buffer[1])?
+    } else {
+        Delimiters::default()
+    };
+
+    // Read the amount of proteins (8 bytes)
+    let mut size_buffer = [0_u8; 8];
+    reader
+        .read_exact(&mut size_buffer)
+        .map_err(|_| "Could not read the amount of proteins from the binary file")?;
+    let size = u64::from_le_bytes(size_buffer) as usize;
+
+    let mut proteins = Vec::with_capacity(size);
+    for _ in 0..size {
+        let uniprot_id = String::from_utf8(read_bytes(reader)?)?;
+
+        let mut taxon_id_buffer = [0_u8; 4];
+        reader
+            .read_exact(&mut taxon_id_buffer)
+            .map_err(|_| "Could not read the taxon id from the binary file")?;
+
+        proteins.push(Protein {
+            uniprot_id,
+            taxon_id: u32::from_le_bytes(taxon_id_buffer),
+            functional_annotations: read_bytes(reader)?
+        });
+    }
+
+    // The compressed text has its own header
+    read_header(reader, FileKind::Text, allow_legacy)?;
+
+    // Read the bits per value of the compressed text (1 byte)
+    let mut bits_per_value_buffer = [0_u8; 1];
+    reader
+        .read_exact(&mut bits_per_value_buffer)
+        .map_err(|_| "Could not read the flags from the binary file")?;
+
+    let text = load_compressed_text(reader, bits_per_value_buffer[0] as usize)?;
+
+    Ok(Proteins {
+        text,
+        proteins,
+        accession_index: None,
+        original_sequences: None,
+        delimiters,
+        skipped_accessions: Vec::new()
+    })
+}
+
+/// Writes the length of the bytes (4 bytes), followed by the bytes themselves
+fn write_bytes(bytes: &[u8], writer: &mut impl Write) -> std::io::Result<()> {
+    writer.write_all(&(bytes.len() as u32).to_le_bytes())?;
+    writer.write_all(bytes)
+}
+
+/// Reads bytes that were written by `write_bytes`
+fn read_bytes(reader: &mut impl BufRead) -> Result<Vec<u8>, Box<dyn Error>> {
+    let mut length_buffer = [0_u8; 4];
+    reader
+        .read_exact(&mut length_buffer)
+        .map_err(|_| "Could not read the length of a protein field from the binary file")?;
+
+    let mut bytes = vec![0_u8; u32::from_le_bytes(length_buffer) as usize];
+    reader.read_exact(&mut bytes).map_err(|_| "Could not read a protein field from the binary file")?;
+
+    Ok(bytes)
+}
+
+/// Checks if a protein should be kept, based on its accession and the accessions of the
+/// proteins that were already loaded
+///
+/// # Arguments
+/// * `accession` - The accession of the protein
+/// * `duplicates` - What to do with proteins of which the accession already occurred
+/// * `seen_accessions` - The accessions of the proteins that were already loaded, only used if
+///   duplicates are not kept
+/// * `skipped_accessions` - The accessions of the skipped proteins, to which the accession is added
+///   if the protein is skipped
+///
+/// # Returns
+///
+/// Returns true if the protein should be kept, false if it should be skipped
+///
+/// # Errors
+///
+/// Returns a `Box<dyn Error>` if the accession is a duplicate and `duplicates` is
+/// `DuplicateAccessions::Fail`
+fn keep_accession(
+    accession: &str,
+    duplicates: &DuplicateAccessions,
+    seen_accessions: &mut HashSet<String>,
+    skipped_accessions: &mut Vec<String>
+) -> Result<bool, Box<dyn Error>> {
+    if *duplicates == DuplicateAccessions::Keep || seen_accessions.insert(accession.to_string()) {
+        return Ok(true);
+    }
+
+    match duplicates {
+        DuplicateAccessions::Fail => Err(format!("Duplicate accession {} in the database file", accession).into()),
+        _ => {
+            skipped_accessions.push(accession.to_string());
+            Ok(false)
+        }
+    }
+}
+
+/// Opens a database file for reading. If the file is gzip compressed (it has a `.gz` extension or
+/// starts with the gzip magic bytes), the returned reader transparently decompresses it.
+///
+/// # Arguments
+/// * `file` - The path to the database file
+///
+/// # Returns
+///
+/// Returns a `Result` containing a buffered reader over the (decompressed) contents of the file
+///
+/// # Errors
+///
+/// Returns a `Box<dyn Error>` if the file could not be opened
+fn open_database_file(file: &str) -> Result<Box<dyn BufRead>, Box<dyn Error>> {
+    let mut reader = BufReader::new(File::open(file)?);
+
+    if file.ends_with(".gz") || reader.fill_buf()?.starts_with(&GZIP_MAGIC_BYTES) {
+        Ok(Box::new(BufReader::new(MultiGzDecoder::new(reader))))
+    } else {
+        Ok(Box::new(reader))
+    }
+}
+
+impl Index<usize> for Proteins {
+    type Output = Protein;
+
+    fn index(&self, index: usize) -> &Self::Output {
+        &self.proteins[index]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{fs::File, io::Write, path::PathBuf};
+
+    use flate2::{write::GzEncoder, Compression};
+    use tempdir::TempDir;
+
+    use super::*;
 
     fn create_database_file(tmp_dir: &TempDir) -> PathBuf {
         let database_file = tmp_dir.path().join("database.tsv");
@@ -202,6 +1102,31 @@ mod tests {
         database_file
     }
 
+    fn create_fasta_file(tmp_dir: &TempDir) -> PathBuf {
+        let fasta_file = tmp_dir.path().join("database.fasta");
+        let mut file = File::create(&fasta_file).unwrap();
+
+        file.write_all(b">sp|P12345|PROT1_HUMAN First protein\n").unwrap();
+        file.write_all(b"MLPGLALLLL\n").unwrap();
+        file.write_all(b"aawtaralev\n").unwrap();
+        file.write_all(b">P54321 Second protein\n").unwrap();
+        file.write_all(b"PTDGNAGLLAEPQIAM\n").unwrap();
+        file.write_all(b"FCGRLNMHMNVQNG\n").unwrap();
+
+        fasta_file
+    }
+
+    fn create_gzipped_database_file(tmp_dir: &TempDir, file_name: &str) -> PathBuf {
+        let database = std::fs::read(create_database_file(tmp_dir)).unwrap();
+
+        let gzipped_database_file = tmp_dir.path().join(file_name);
+        let mut encoder = GzEncoder::new(File::create(&gzipped_database_file).unwrap(), Compression::default());
+        encoder.write_all(&database).unwrap();
+        encoder.finish().unwrap();
+
+        gzipped_database_file
+    }
+
     #[test]
     fn test_new_protein() {
         let protein = Protein {
@@ -232,7 +1157,11 @@ mod tests {
                     taxon_id: 2,
                     functional_annotations: vec![0xD1, 0x11]
                 },
-            ]
+            ],
+            accession_index: None,
+            original_sequences: None,
+            delimiters: Delimiters::default(),
+            skipped_accessions: Vec::new()
         };
 
         assert_eq!(proteins.proteins.len(), 2);
@@ -244,6 +1173,94 @@ mod tests {
         assert_eq!(proteins[1].functional_annotations, vec![0xD1, 0x11]);
     }
 
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde_round_trip() {
+        let proteins = Proteins {
+            text: ProteinText::from_string("MLPG-PTDG$"),
+            proteins: vec![
+                Protein { uniprot_id: "P12345".to_string(), taxon_id: 1, functional_annotations: vec![0xD1, 0x11] },
+                Protein { uniprot_id: "P54321".to_string(), taxon_id: 2, functional_annotations: vec![] },
+            ],
+            accession_index: None,
+            original_sequences: None,
+            delimiters: Delimiters::default(),
+            skipped_accessions: Vec::new()
+        };
+
+        let json = serde_json::to_string(&proteins).unwrap();
+        let deserialized: Proteins = serde_json::from_str(&json).unwrap();
+
+        assert_eq!(deserialized.text.decode_range(0, deserialized.text.len()), b"MLPG-PTDG$".to_vec());
+        assert_eq!(deserialized.proteins.len(), 2);
+        assert_eq!(deserialized[0].uniprot_id, "P12345");
+        assert_eq!(deserialized[0].taxon_id, 1);
+        assert_eq!(deserialized[0].functional_annotations, vec![0xD1, 0x11]);
+        assert_eq!(deserialized[1].uniprot_id, "P54321");
+        assert_eq!(deserialized[1].taxon_id, 2);
+        assert!(deserialized[1].functional_annotations.is_empty());
+        assert!(deserialized.accession_index.is_none());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde_round_trip_custom_alphabet() {
+        let proteins = Proteins {
+            text: ProteinText::from_string_with_alphabet("ACGT-TTGA$", b"ACGT-$"),
+            proteins: vec![],
+            accession_index: None,
+            original_sequences: None,
+            delimiters: Delimiters::default(),
+            skipped_accessions: Vec::new()
+        };
+
+        let json = serde_json::to_string(&proteins).unwrap();
+        let deserialized: Proteins = serde_json::from_str(&json).unwrap();
+
+        assert_eq!(deserialized.text.alphabet(), b"ACGT-$");
+        assert_eq!(deserialized.text.bits_per_value(), 3);
+        assert_eq!(deserialized.text.decode_range(0, deserialized.text.len()), b"ACGT-TTGA$".to_vec());
+        assert_eq!(deserialized.delimiters, Delimiters::default());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde_round_trip_delimiters() {
+        let delimiters = Delimiters::new(b'*', b'#').unwrap();
+        let proteins = Proteins {
+            text: ProteinText::from_string_with_alphabet("MLPG-*PTDG#", &delimiters.alphabet()),
+            proteins: vec![],
+            accession_index: None,
+            original_sequences: None,
+            delimiters,
+            skipped_accessions: Vec::new()
+        };
+
+        let json = serde_json::to_string(&proteins).unwrap();
+        let deserialized: Proteins = serde_json::from_str(&json).unwrap();
+
+        assert_eq!(deserialized.delimiters, delimiters);
+        assert_eq!(deserialized.get_sequence(0), Some("MLPG-".to_string()));
+
+        // the delimiters are validated, the termination character # is larger than the separation character
+        let json = r#"{"text":"M#","proteins":[],"delimiters":{"separation_character":35,"termination_character":42}}"#;
+        assert!(serde_json::from_str::<Proteins>(json).is_err());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde_invalid_text() {
+        // J is not part of the default protein alphabet
+        let result = serde_json::from_str::<Proteins>(r#"{"text":"MJPG$","proteins":[]}"#);
+        assert_eq!(result.err().unwrap().to_string(), "Input character 'J' not in alphabet");
+
+        let result = serde_json::from_str::<Proteins>(r#"{"text":"ACGU$","alphabet":"ACGT-$","proteins":[]}"#);
+        assert_eq!(result.err().unwrap().to_string(), "Input character 'U' not in alphabet");
+
+        let result = serde_json::from_str::<Proteins>(r#"{"text":"","alphabet":"","proteins":[]}"#);
+        assert!(result.is_err());
+    }
+
     #[test]
     fn test_get_taxon() {
         // Create a temporary directory for this test
@@ -273,6 +1290,32 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_annotation_lists() {
+        let no_annotations = Protein { uniprot_id: "P12345".to_string(), taxon_id: 1, functional_annotations: vec![] };
+        assert!(no_annotations.ec_number_list().is_empty());
+        assert!(no_annotations.go_term_list().is_empty());
+        assert!(no_annotations.interpro_entry_list().is_empty());
+
+        let one_ec_number = Protein {
+            uniprot_id: "P12345".to_string(),
+            taxon_id: 1,
+            functional_annotations: encode("EC:1.1.1.-;GO:0009279")
+        };
+        assert_eq!(one_ec_number.ec_number_list(), vec!["EC:1.1.1.-"]);
+        assert_eq!(one_ec_number.go_term_list(), vec!["GO:0009279"]);
+        assert!(one_ec_number.interpro_entry_list().is_empty());
+
+        let multiple_ec_numbers = Protein {
+            uniprot_id: "P12345".to_string(),
+            taxon_id: 1,
+            functional_annotations: encode("EC:1.1.1.-;EC:2.7.11.1;GO:0009279;IPR:IPR016364;IPR:IPR008816")
+        };
+        assert_eq!(multiple_ec_numbers.ec_number_list(), vec!["EC:1.1.1.-", "EC:2.7.11.1"]);
+        assert_eq!(multiple_ec_numbers.go_term_list(), vec!["GO:0009279"]);
+        assert_eq!(multiple_ec_numbers.interpro_entry_list(), vec!["IPR:IPR016364", "IPR:IPR008816"]);
+    }
+
     #[test]
     fn test_get_concatenated_proteins() {
         // Create a temporary directory for this test
@@ -285,4 +1328,548 @@ mod tests {
         let expected = 'L' as u8;
         assert_eq!(proteins.get(4), expected);
     }
+
+    #[test]
+    fn test_concatenate() {
+        // Create a temporary directory for this test
+        let tmp_dir = TempDir::new("test_concatenate").unwrap();
+
+        let database_file = create_database_file(&tmp_dir);
+        let sequences = [
+            "MLPGLALLLLAAWTARALEV",
+            "PTDGNAGLLAEPQIAMFCGRLNMHMNVQNG",
+            "KWDSDPSGTKTCIDT",
+            "KEGILQYCQEVYPELQITNVVEANQPVTIQNWCKRGRKQCKTHPH"
+        ];
+        let concatenated = Proteins::concatenate(&sequences);
+
+        let (input_string, _) = Proteins::try_from_database_file_uncompressed(
+            database_file.to_str().unwrap(),
+            false,
+            &DuplicateAccessions::Keep
+        )
+        .unwrap();
+        assert_eq!(concatenated, input_string);
+
+        // the text without annotations ends with a separation character instead of a terminator
+        let text = Proteins::try_from_database_file_without_annotations(database_file.to_str().unwrap()).unwrap();
+        assert_eq!(text.len(), concatenated.len());
+        assert_eq!(text.decode_range(0, text.len() - 1), concatenated[..concatenated.len() - 1]);
+
+        assert_eq!(Proteins::concatenate(&["mkc", "Ac"]), b"MKC-AC$");
+        assert_eq!(Proteins::concatenate(&[]), b"$");
+    }
+
+    #[test]
+    fn test_try_from_fasta_file() {
+        // Create a temporary directory for this test
+        let tmp_dir = TempDir::new("test_fasta").unwrap();
+
+        let fasta_file = create_fasta_file(&tmp_dir);
+
+        let proteins = Proteins::try_from_fasta_file(fasta_file.to_str().unwrap()).unwrap();
+
+        assert_eq!(proteins.proteins.len(), 2);
+        assert_eq!(proteins[0].uniprot_id, "P12345");
+        assert_eq!(proteins[0].taxon_id, 0);
+        assert_eq!(proteins[0].get_functional_annotations(), "");
+        assert_eq!(proteins[1].uniprot_id, "P54321");
+
+        let expected = "MLPGLALLLLAAWTARALEV-PTDGNAGLLAEPQIAMFCGRLNMHMNVQNG$";
+        assert_eq!(proteins.text.len(), expected.len());
+        for (i, c) in expected.bytes().enumerate() {
+            assert_eq!(proteins.text.get(i), c);
+        }
+    }
+
+    #[test]
+    fn test_try_from_gzipped_database_file() {
+        // Create a temporary directory for this test
+        let tmp_dir = TempDir::new("test_gzip").unwrap();
+
+        let database_file = create_database_file(&tmp_dir);
+        // the second file has no .gz extension, so it has to be detected by its magic bytes
+        for file_name in ["database.tsv.gz", "database.gzipped"] {
+            let gzipped_database_file = create_gzipped_database_file(&tmp_dir, file_name);
+
+            let proteins = Proteins::try_from_database_file(database_file.to_str().unwrap()).unwrap();
+            let gzipped_proteins = Proteins::try_from_database_file(gzipped_database_file.to_str().unwrap()).unwrap();
+
+            assert_eq!(gzipped_proteins.proteins, proteins.proteins);
+            assert_eq!(gzipped_proteins.text.len(), proteins.text.len());
+            for i in 0..proteins.text.len() {
+                assert_eq!(gzipped_proteins.text.get(i), proteins.text.get(i));
+            }
+
+            let text = Proteins::try_from_database_file_without_annotations(database_file.to_str().unwrap()).unwrap();
+            let gzipped_text =
+                Proteins::try_from_database_file_without_annotations(gzipped_database_file.to_str().unwrap()).unwrap();
+
+            assert_eq!(gzipped_text.len(), text.len());
+            for i in 0..text.len() {
+                assert_eq!(gzipped_text.get(i), text.get(i));
+            }
+        }
+    }
+
+    #[test]
+    fn test_try_from_truncated_gzipped_database_file() {
+        // Create a temporary directory for this test
+        let tmp_dir = TempDir::new("test_truncated_gzip").unwrap();
+
+        // a partially downloaded file, of which the end of the compressed stream is missing
+        let gzipped_database_file = create_gzipped_database_file(&tmp_dir, "database.tsv.gz");
+        let gzipped_database = std::fs::read(&gzipped_database_file).unwrap();
+        std::fs::write(&gzipped_database_file, &gzipped_database[..gzipped_database.len() / 2]).unwrap();
+        let gzipped_database_file = gzipped_database_file.to_str().unwrap();
+
+        assert!(Proteins::try_from_database_file(gzipped_database_file).is_err());
+        assert!(Proteins::try_from_database_file_without_annotations(gzipped_database_file).is_err());
+        assert!(Proteins::try_from_database_file_uncompressed(
+            gzipped_database_file,
+            false,
+            &DuplicateAccessions::Keep
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_try_from_database_file_uncompressed_assume_uppercase() {
+        // Create a temporary directory for this test
+        let tmp_dir = TempDir::new("test_assume_uppercase").unwrap();
+
+        let database_file = create_database_file(&tmp_dir);
+
+        let (input_string, _) = Proteins::try_from_database_file_uncompressed(
+            database_file.to_str().unwrap(),
+            false,
+            &DuplicateAccessions::Keep
+        )
+        .unwrap();
+        let (uppercase_input_string, _) = Proteins::try_from_database_file_uncompressed(
+            database_file.to_str().unwrap(),
+            true,
+            &DuplicateAccessions::Keep
+        )
+        .unwrap();
+
+        assert_eq!(uppercase_input_string, input_string);
+    }
+
+    #[test]
+    fn test_try_from_database_file_uncompressed_assume_uppercase_lowercase() {
+        // Create a temporary directory for this test
+        let tmp_dir = TempDir::new("test_assume_uppercase_lowercase").unwrap();
+
+        let database_file = tmp_dir.path().join("database.tsv");
+        let mut file = File::create(&database_file).unwrap();
+        file.write_all(b"P12345\t1\tMLPGLALLLLAAWTARALEV\tGO:0009279\n").unwrap();
+        file.write_all(b"P54321\t2\tptdgnagllaepqiam\tGO:0009279\n").unwrap();
+
+        let (input_string, _) = Proteins::try_from_database_file_uncompressed(
+            database_file.to_str().unwrap(),
+            false,
+            &DuplicateAccessions::Keep
+        )
+        .unwrap();
+        assert_eq!(input_string, b"MLPGLALLLLAAWTARALEV-PTDGNAGLLAEPQIAM$".to_vec());
+
+        let err = Proteins::try_from_database_file_uncompressed(
+            database_file.to_str().unwrap(),
+            true,
+            &DuplicateAccessions::Keep
+        )
+        .unwrap_err();
+        assert_eq!(err.to_string(), "The sequence on line 2 is not uppercase");
+    }
+
+    #[test]
+    fn test_try_from_database_file_uncompressed_with_proteins() {
+        // Create a temporary directory for this test
+        let tmp_dir = TempDir::new("test_uncompressed_with_proteins").unwrap();
+
+        let database_file = create_database_file(&tmp_dir);
+
+        let (input_string, uncompressed_proteins, _) = Proteins::try_from_database_file_uncompressed_with_proteins(
+            database_file.to_str().unwrap(),
+            true,
+            &DuplicateAccessions::Keep,
+            &AnnotationFormat::Text
+        )
+        .unwrap();
+        let proteins = Proteins::try_from_database_file(database_file.to_str().unwrap()).unwrap();
+
+        assert_eq!(input_string, proteins.text.decode_range(0, proteins.text.len()));
+        assert_eq!(uncompressed_proteins, proteins.proteins);
+    }
+
+    fn create_duplicate_database_file(tmp_dir: &TempDir) -> PathBuf {
+        let database_file = tmp_dir.path().join("database.tsv");
+        let mut file = File::create(&database_file).unwrap();
+
+        file.write_all(b"P12345\t1\tMLPG\tGO:0009279\n").unwrap();
+        file.write_all(b"P54321\t2\tPTDG\tGO:0009279\n").unwrap();
+        file.write_all(b"P12345\t3\tKWDS\tGO:0009279\n").unwrap();
+        file.write_all(b"P67890\t6\tCQEV\tGO:0009279\n").unwrap();
+
+        database_file
+    }
+
+    #[test]
+    fn test_try_from_database_file_encoded_annotations() {
+        // Create a temporary directory for this test
+        let tmp_dir = TempDir::new("test_encoded_annotations").unwrap();
+
+        // the second annotation column is encoded, but happens to be valid utf8
+        let utf8_annotations = encode("EC:1.1.1.1;GO:0000001");
+        assert!(from_utf8(&utf8_annotations).is_ok());
+
+        let database_file = tmp_dir.path().join("database.tsv");
+        let mut file = File::create(&database_file).unwrap();
+        file.write_all(b"P12345\t1\tMLPGLALLLL\t\xfe\x00\x83\xa7\n").unwrap();
+        file.write_all(&[b"P54321\t2\tPTDGNAGLLA\t".as_slice(), &utf8_annotations, b"\n"].concat()).unwrap();
+
+        let proteins = Proteins::try_from_database_file_with_annotation_format(
+            database_file.to_str().unwrap(),
+            &DuplicateAccessions::Keep,
+            &AnnotationFormat::Encoded
+        )
+        .unwrap();
+
+        assert_eq!(proteins.proteins.len(), 2);
+        assert_eq!(proteins[0].uniprot_id, "P12345");
+        assert_eq!(proteins[0].functional_annotations, vec![0xfe, 0x00, 0x83, 0xa7]);
+        assert_eq!(proteins[1].functional_annotations, utf8_annotations);
+        assert_eq!(proteins[1].get_functional_annotations(), "EC:1.1.1.1;GO:0000001");
+
+        // the proteins that are written by the builder are not encoded again
+        let (_, uncompressed_proteins, _) = Proteins::try_from_database_file_uncompressed_with_proteins(
+            database_file.to_str().unwrap(),
+            false,
+            &DuplicateAccessions::Keep,
+            &AnnotationFormat::Encoded
+        )
+        .unwrap();
+        assert_eq!(uncompressed_proteins[0].functional_annotations, vec![0xfe, 0x00, 0x83, 0xa7]);
+        assert_eq!(uncompressed_proteins[1].functional_annotations, utf8_annotations);
+
+        // text annotations should be valid utf8
+        assert!(Proteins::try_from_database_file(database_file.to_str().unwrap()).is_err());
+        assert!(Proteins::try_from_database_file_uncompressed_with_proteins(
+            database_file.to_str().unwrap(),
+            false,
+            &DuplicateAccessions::Keep,
+            &AnnotationFormat::Text
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_annotation_format_from_str() {
+        assert_eq!("text".parse::<AnnotationFormat>(), Ok(AnnotationFormat::Text));
+        assert_eq!("encoded".parse::<AnnotationFormat>(), Ok(AnnotationFormat::Encoded));
+        assert!("other".parse::<AnnotationFormat>().is_err());
+    }
+
+    #[test]
+    fn test_try_from_database_file_skip_duplicates() {
+        // Create a temporary directory for this test
+        let tmp_dir = TempDir::new("test_skip_duplicates").unwrap();
+
+        let database_file = create_duplicate_database_file(&tmp_dir);
+
+        let mut proteins = Proteins::try_from_database_file_with_duplicates(
+            database_file.to_str().unwrap(),
+            &DuplicateAccessions::Skip
+        )
+        .unwrap();
+        let (input_string, skipped_accessions) = Proteins::try_from_database_file_uncompressed(
+            database_file.to_str().unwrap(),
+            false,
+            &DuplicateAccessions::Skip
+        )
+        .unwrap();
+
+        // the second protein with accession P12345 is skipped
+        let expected = "MLPG-PTDG-CQEV$";
+        assert_eq!(input_string, expected.as_bytes());
+        assert_eq!(skipped_accessions, vec!["P12345"]);
+        assert_eq!(proteins.proteins.len(), 3);
+        assert_eq!(proteins.skipped_accessions, vec!["P12345"]);
+        assert_eq!(proteins.text.len(), expected.len());
+        for (i, c) in expected.bytes().enumerate() {
+            assert_eq!(proteins.text.get(i), c);
+        }
+
+        proteins.build_accession_index();
+        assert_eq!(proteins.get_by_accession("P12345").unwrap().taxon_id, 1);
+        assert_eq!(proteins.get_by_accession("P67890").unwrap().taxon_id, 6);
+        assert_eq!(proteins[2].uniprot_id, "P67890");
+    }
+
+    #[test]
+    fn test_try_from_database_file_fail_duplicates() {
+        // Create a temporary directory for this test
+        let tmp_dir = TempDir::new("test_fail_duplicates").unwrap();
+
+        let database_file = create_duplicate_database_file(&tmp_dir);
+
+        let err = Proteins::try_from_database_file_with_duplicates(
+            database_file.to_str().unwrap(),
+            &DuplicateAccessions::Fail
+        )
+        .err()
+        .unwrap();
+        assert_eq!(err.to_string(), "Duplicate accession P12345 in the database file");
+
+        // duplicates are kept by default
+        let proteins = Proteins::try_from_database_file(database_file.to_str().unwrap()).unwrap();
+        assert_eq!(proteins.proteins.len(), 4);
+        assert!(proteins.skipped_accessions.is_empty());
+    }
+
+    #[test]
+    fn test_duplicate_accessions_from_str() {
+        assert_eq!("keep".parse::<DuplicateAccessions>(), Ok(DuplicateAccessions::Keep));
+        assert_eq!("skip".parse::<DuplicateAccessions>(), Ok(DuplicateAccessions::Skip));
+        assert_eq!("fail".parse::<DuplicateAccessions>(), Ok(DuplicateAccessions::Fail));
+        assert!("other".parse::<DuplicateAccessions>().is_err());
+    }
+
+    #[test]
+    fn test_get_by_accession() {
+        // Create a temporary directory for this test
+        let tmp_dir = TempDir::new("test_get_by_accession").unwrap();
+
+        let database_file = create_database_file(&tmp_dir);
+
+        let mut proteins = Proteins::try_from_database_file(database_file.to_str().unwrap()).unwrap();
+
+        // the index is only available after building it
+        assert!(proteins.get_by_accession("P54321").is_none());
+
+        proteins.build_accession_index();
+
+        assert_eq!(proteins.get_by_accession("P54321").unwrap().taxon_id, 2);
+        assert_eq!(proteins.get_by_accession("P13579").unwrap().taxon_id, 17);
+        assert_eq!(proteins.get_by_accession("P12345").unwrap().taxon_id, 1);
+        assert!(proteins.get_by_accession("Q99999").is_none());
+    }
+
+    #[test]
+    fn test_try_from_database_files() {
+        let tmp_dir = TempDir::new("test_try_from_database_files").unwrap();
+        let database_file = create_database_file(&tmp_dir);
+        let content = std::fs::read_to_string(&database_file).unwrap();
+
+        // split the database file in two shards
+        let lines: Vec<&str> = content.lines().collect();
+        let first_shard = tmp_dir.path().join("shard1.tsv");
+        let second_shard = tmp_dir.path().join("shard2.tsv");
+        std::fs::write(&first_shard, lines[..2].join("\n") + "\n").unwrap();
+        std::fs::write(&second_shard, lines[2..].join("\n") + "\n").unwrap();
+
+        let proteins = Proteins::try_from_database_file(database_file.to_str().unwrap()).unwrap();
+        let shards = [first_shard.to_str().unwrap(), second_shard.to_str().unwrap()];
+        let sharded_proteins = Proteins::try_from_database_files(&shards).unwrap();
+
+        assert!(sharded_proteins.text == proteins.text);
+        assert_eq!(sharded_proteins.proteins, proteins.proteins);
+
+        // the last protein of the first shard is separated from the first protein of the second
+        let text = sharded_proteins.text.decode_range(0, sharded_proteins.text.len());
+        assert_eq!(text.iter().filter(|&&c| c == SEPARATION_CHARACTER).count(), 3);
+        assert!(text.windows(17).any(|window| window == b"MHMNVQNG-KWDSDPSG".as_slice()));
+    }
+
+    #[test]
+    fn test_try_from_database_file_preserving_case() {
+        let tmp_dir = TempDir::new("test_try_from_database_file_preserving_case").unwrap();
+        let database_file = tmp_dir.path().join("database.tsv");
+        let mut file = File::create(&database_file).unwrap();
+        file.write_all("P12345\t1\tMLPGlallLAAW\tGO:0009279\n".as_bytes()).unwrap();
+        file.write_all("P54321\t2\tPTDGNA\tGO:0009279\n".as_bytes()).unwrap();
+
+        let proteins = Proteins::try_from_database_file_preserving_case(
+            database_file.to_str().unwrap(),
+            &DuplicateAccessions::Keep
+        )
+        .unwrap();
+
+        assert_eq!(proteins.get_original_sequence(0), Some("MLPGlallLAAW"));
+        assert_eq!(proteins.get_original_sequence(1), Some("PTDGNA"));
+        assert_eq!(proteins.get_original_sequence(2), None);
+        assert_eq!(proteins.text.decode_range(0, proteins.text.len()), b"MLPGLALLLAAW-PTDGNA$".to_vec());
+
+        let proteins = Proteins::try_from_database_file(database_file.to_str().unwrap()).unwrap();
+        assert_eq!(proteins.get_original_sequence(0), None);
+    }
+
+    #[test]
+    fn test_dump_and_load_proteins() {
+        // Create a temporary directory for this test
+        let tmp_dir = TempDir::new("test_dump_and_load_proteins").unwrap();
+
+        let database_file = create_database_file(&tmp_dir);
+        let proteins = Proteins::try_from_database_file(database_file.to_str().unwrap()).unwrap();
+
+        let mut buffer = Vec::new();
+        dump_proteins(&proteins, &mut buffer).unwrap();
+
+        let loaded_proteins = load_proteins(&mut &buffer[..], false).unwrap();
+
+        assert_eq!(loaded_proteins.proteins, proteins.proteins);
+        assert_eq!(loaded_proteins.text.len(), proteins.text.len());
+        for i in 0..proteins.text.len() {
+            assert_eq!(loaded_proteins.text.get(i), proteins.text.get(i));
+        }
+    }
+
+    #[test]
+    fn test_dump_and_load_proteins_with_alphabet() {
+        let proteins = Proteins {
+            text: ProteinText::from_string_with_alphabet("ACGT-TGCA$", b"ACGT-$"),
+            proteins: vec![
+                Protein { uniprot_id: "P12345".to_string(), taxon_id: 1, functional_annotations: vec![] },
+                Protein { uniprot_id: "P54321".to_string(), taxon_id: 2, functional_annotations: vec![] },
+            ],
+            accession_index: None,
+            original_sequences: None,
+            delimiters: Delimiters::default(),
+            skipped_accessions: Vec::new()
+        };
+
+        let mut buffer = Vec::new();
+        dump_proteins(&proteins, &mut buffer).unwrap();
+
+        let loaded_proteins = load_proteins(&mut &buffer[..], false).unwrap();
+        assert_eq!(loaded_proteins.text.alphabet(), b"ACGT-$");
+        assert_eq!(loaded_proteins.text.decode_range(0, loaded_proteins.text.len()), b"ACGT-TGCA$".to_vec());
+    }
+
+    #[test]
+    fn test_load_proteins_truncated() {
+        let tmp_dir = TempDir::new("test_load_proteins_truncated").unwrap();
+
+        let database_file = create_database_file(&tmp_dir);
+        let proteins = Proteins::try_from_database_file(database_file.to_str().unwrap()).unwrap();
+
+        let mut buffer = Vec::new();
+        dump_proteins(&proteins, &mut buffer).unwrap();
+        buffer.truncate(20);
+
+        assert!(load_proteins(&mut &buffer[..], false).is_err());
+    }
+
+    #[test]
+    fn test_load_proteins_wrong_kind() {
+        let mut buffer = Vec::new();
+        dump_protein_text(&ProteinText::from_string("MLPG$"), &mut buffer).unwrap();
+
+        let err = load_proteins(&mut &buffer[..], true).err().unwrap();
+        assert_eq!(err.to_string(), "Unexpected file format: found text, expected proteins");
+    }
+
+    fn create_gapped_database_file(tmp_dir: &TempDir) -> PathBuf {
+        let database_file = tmp_dir.path().join("database.tsv");
+        let mut file = File::create(&database_file).unwrap();
+
+        file.write_all(b"P12345	1	MLPG--ALLL	GO:0009279
+").unwrap();
+        file.write_all(b"P54321	2	PT-dgna	IPR:IPR016364
+").unwrap();
+
+        database_file
+    }
+
+    #[test]
+    fn test_delimiters_new() {
+        assert_eq!(Delimiters::new(b'-', b'$'), Ok(Delimiters::default()));
+        assert_eq!(
+            Delimiters::new(b'*', b'#'),
+            Ok(Delimiters { separation_character: b'*', termination_character: b'#' })
+        );
+
+        // the termination character should be smaller than the separation character
+        assert_eq!(
+            Delimiters::new(b'#', b'*'),
+            Err("The termination character '*' should be smaller than the separation character '#'".to_string())
+        );
+        assert!(Delimiters::new(b'*', b'*').is_err());
+
+        // residues can not be used as delimiters
+        assert!(Delimiters::new(b'X', b'$').is_err());
+        assert!(Delimiters::new(b'-', 0xff).is_err());
+    }
+
+    #[test]
+    fn test_delimiters_alphabet() {
+        assert_eq!(Delimiters::default().alphabet(), PROTEIN_ALPHABET.to_vec());
+
+        let alphabet = Delimiters::new(b'*', b'#').unwrap().alphabet();
+        assert_eq!(alphabet, b"ABCDEFGHIKLMNOPQRSTUVWXYZ-$*#".to_vec());
+        assert_eq!(ProteinText::bits_for_alphabet(alphabet.len()), 5);
+    }
+
+    #[test]
+    fn test_try_from_database_file_with_delimiters() {
+        // Create a temporary directory for this test
+        let tmp_dir = TempDir::new("test_delimiters").unwrap();
+
+        let database_file = create_gapped_database_file(&tmp_dir);
+        let delimiters = Delimiters::new(b'*', b'#').unwrap();
+        let proteins =
+            Proteins::try_from_database_file_with_delimiters(database_file.to_str().unwrap(), &delimiters).unwrap();
+
+        assert_eq!(proteins.delimiters, delimiters);
+        assert_eq!(proteins.text.decode_range(0, proteins.text.len()), b"MLPG--ALLL*PT-DGNA#".to_vec());
+        assert_eq!(proteins.get_sequence(0), Some("MLPG--ALLL".to_string()));
+        assert_eq!(proteins.get_sequence(1), Some("PT-DGNA".to_string()));
+        assert_eq!(proteins.get_sequence(2), None);
+
+        // the default separation character occurs in the sequences
+        let err = Proteins::try_from_database_file(database_file.to_str().unwrap()).err().unwrap();
+        assert_eq!(err.to_string(), "The sequence of protein P12345 contains a delimiter");
+    }
+
+    #[test]
+    fn test_get_sequence() {
+        // Create a temporary directory for this test
+        let tmp_dir = TempDir::new("test_get_sequence").unwrap();
+
+        let database_file = create_database_file(&tmp_dir);
+        let proteins = Proteins::try_from_database_file(database_file.to_str().unwrap()).unwrap();
+
+        assert_eq!(proteins.get_sequence(0), Some("MLPGLALLLLAAWTARALEV".to_string()));
+        assert_eq!(proteins.get_sequence(3), Some("KEGILQYCQEVYPELQITNVVEANQPVTIQNWCKRGRKQCKTHPH".to_string()));
+        assert_eq!(proteins.get_sequence(4), None);
+    }
+
+    #[test]
+    fn test_dump_and_load_proteins_with_delimiters() {
+        // Create a temporary directory for this test
+        let tmp_dir = TempDir::new("test_dump_and_load_delimiters").unwrap();
+
+        let database_file = create_gapped_database_file(&tmp_dir);
+        let delimiters = Delimiters::new(b'*', b'#').unwrap();
+        let proteins =
+            Proteins::try_from_database_file_with_delimiters(database_file.to_str().unwrap(), &delimiters).unwrap();
+
+        let mut buffer = Vec::new();
+        dump_proteins(&proteins, &mut buffer).unwrap();
+        assert_eq!(buffer[5], CUSTOM_DELIMITERS_FLAG);
+        assert_eq!(&buffer[6..8], b"*#");
+
+        let loaded_proteins = load_proteins(&mut &buffer[..], false).unwrap();
+        assert_eq!(loaded_proteins.delimiters, delimiters);
+        assert_eq!(loaded_proteins.get_sequence(1), Some("PT-DGNA".to_string()));
+
+        // proteins with the default delimiters do not store them
+        let database_file = create_database_file(&tmp_dir);
+        let proteins = Proteins::try_from_database_file(database_file.to_str().unwrap()).unwrap();
+        let mut buffer = Vec::new();
+        dump_proteins(&proteins, &mut buffer).unwrap();
+        assert_eq!(buffer[5], 0);
+        assert_eq!(load_proteins(&mut &buffer[..], false).unwrap().delimiters, Delimiters::default());
+    }
 }
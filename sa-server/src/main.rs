@@ -1,36 +1,239 @@
 use std::{
+    collections::HashMap,
     error::Error,
     fs::File,
-    io::{BufReader, Read},
-    sync::Arc
+    future::{Future, IntoFuture},
+    io::{BufRead, BufReader},
+    path::Path as FilePath,
+    sync::{
+        atomic::{AtomicU64, AtomicUsize, Ordering},
+        Arc, OnceLock
+    },
+    time::Instant
 };
 
 use axum::{
-    extract::{DefaultBodyLimit, State},
-    http::StatusCode,
-    routing::post,
+    extract::{DefaultBodyLimit, Path, Request, State},
+    http::{header, StatusCode},
+    middleware::{self, Next},
+    response::Response,
+    routing::{get, post},
     Json, Router
 };
 use clap::Parser;
+use index_format::{read_header, FileKind, EQUATE_IL_FLAG};
 use sa_compression::load_compressed_suffix_array;
 use sa_index::{
     binary::load_suffix_array,
-    peptide_search::{search_all_peptides, SearchResult},
+    peptide_search::{search_all_peptides_batch_with_config, BatchSearchResponse, SearchConfig},
     sa_searcher::SparseSearcher,
     SuffixArray
 };
-use sa_mappings::proteins::Proteins;
+use sa_mappings::proteins::{load_proteins, AnnotationFormat, DuplicateAccessions, Proteins};
 use serde::Deserialize;
+use tower_http::{compression::CompressionLayer, decompression::RequestDecompressionLayer};
 
 /// Enum that represents all possible commandline arguments
 #[derive(Parser, Debug)]
 pub struct Arguments {
     /// File with the proteins used to build the suffix tree. All the proteins are expected to be
-    /// concatenated using a `#`.
-    #[arg(short, long)]
-    database_file: String,
-    #[arg(short, long)]
-    index_file: String
+    /// concatenated using a `#`. Can be repeated to serve multiple indexes, in the same order as
+    /// the index files.
+    #[arg(short, long, required = true)]
+    database_file: Vec<String>,
+    /// File with the suffix array. Can be repeated to serve multiple indexes.
+    #[arg(short, long, required = true)]
+    index_file: Vec<String>,
+    /// The names of the indexes, used to search an index with `/search/{name}`. Defaults to the
+    /// names of the index files without their extension. `/search` uses the first index.
+    #[arg(long)]
+    index_name: Vec<String>,
+    /// If the database file contains the binary proteins written by the builder with
+    /// `--write-proteins`, instead of the tab-separated database
+    #[arg(long, default_value_t = false)]
+    binary_database: bool,
+    /// If I and L should be equated for requests that do not specify `equate_il`
+    #[arg(long, default_value_t = false)]
+    default_equate_il: bool,
+    /// The host address on which the server listens
+    #[arg(long, default_value = "0.0.0.0")]
+    host: String,
+    /// The port on which the server listens
+    #[arg(short, long, default_value_t = 3000)]
+    port: u16,
+    /// What to do with proteins of which the accession already occurred in the database file: keep,
+    /// skip or fail. This should be the same value that was used to build the index.
+    #[arg(long, default_value = "keep")]
+    duplicate_accessions: DuplicateAccessions,
+    /// How the functional annotations are stored in the tab-separated database: text, or encoded
+    /// if the annotation columns already contain the encoded bytes
+    #[arg(long, default_value = "text")]
+    annotation_format: AnnotationFormat,
+    /// The maximum amount of memory in bytes used by the matching suffixes of a single peptide,
+    /// which protects the server from running out of memory when a high cutoff is requested
+    #[arg(long)]
+    max_result_bytes: Option<usize>,
+    /// If index files without a header, written by an older version of the builder, are accepted
+    #[arg(long, default_value_t = false)]
+    allow_legacy_index: bool
+}
+
+impl Arguments {
+    /// Returns the address on which the server should listen
+    ///
+    /// # Returns
+    ///
+    /// Returns the address as `host:port`
+    fn bind_address(&self) -> String {
+        format!("{}:{}", self.host, self.port)
+    }
+
+    /// Returns the names of the indexes served by the server
+    ///
+    /// # Returns
+    ///
+    /// Returns the configured index names, or the names of the index files without their extension
+    /// if no names are configured
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the amount of database files, index files and names differ, or if two
+    /// indexes have the same name
+    fn index_names(&self) -> Result<Vec<String>, String> {
+        if self.database_file.len() != self.index_file.len() {
+            return Err(format!(
+                "Expected as many database files as index files, but found {} database files and {} index files",
+                self.database_file.len(),
+                self.index_file.len()
+            ));
+        }
+
+        let names: Vec<String> = if self.index_name.is_empty() {
+            self.index_file
+                .iter()
+                .map(|file| FilePath::new(file).file_stem().unwrap_or_default().to_string_lossy().to_string())
+                .collect()
+        } else if self.index_name.len() == self.index_file.len() {
+            self.index_name.clone()
+        } else {
+            return Err(format!(
+                "Expected as many index names as index files, but found {} index names and {} index files",
+                self.index_name.len(),
+                self.index_file.len()
+            ));
+        };
+
+        for (i, name) in names.iter().enumerate() {
+            if names[..i].contains(name) {
+                return Err(format!("The index name {} is used for multiple indexes", name));
+            }
+        }
+
+        Ok(names)
+    }
+}
+
+/// Struct containing the state shared by all the endpoints
+///
+/// # Arguments
+/// * `searchers` - The searcher objects used to search the peptides by index name, only set once
+///   all the indexes are loaded
+/// * `default_index` - The name of the index searched by `/search`
+/// * `default_equate_il` - The value used for `equate_il` when it is missing from a request
+/// * `in_flight_requests` - The amount of requests that are currently being handled
+/// * `metrics` - The counters exposed by the `/metrics` endpoint
+struct AppState {
+    searchers: OnceLock<HashMap<String, SparseSearcher>>,
+    default_index: String,
+    default_equate_il: bool,
+    in_flight_requests: AtomicUsize,
+    metrics: Metrics
+}
+
+/// Struct containing the counters of the searches handled by the server, which are exposed in the
+/// Prometheus text format by the `/metrics` endpoint
+///
+/// # Arguments
+/// * `searches` - The amount of handled search requests
+/// * `peptides` - The amount of peptides in the handled search requests
+/// * `cutoff_hits` - The amount of peptides of which the matches were limited by the cutoff
+/// * `search_duration_micros` - The total time spent searching, in microseconds
+#[derive(Default)]
+struct Metrics {
+    searches: AtomicU64,
+    peptides: AtomicU64,
+    cutoff_hits: AtomicU64,
+    search_duration_micros: AtomicU64
+}
+
+impl Metrics {
+    /// Formats the counters in the Prometheus text format
+    ///
+    /// # Returns
+    ///
+    /// Returns the counters with their help text and type
+    fn to_prometheus(&self) -> String {
+        let counters = [
+            ("unipept_index_searches_total", "The amount of handled search requests", &self.searches),
+            ("unipept_index_peptides_total", "The amount of searched peptides", &self.peptides),
+            (
+                "unipept_index_cutoff_hits_total",
+                "The amount of peptides of which the matches were limited by the cutoff",
+                &self.cutoff_hits
+            )
+        ];
+
+        let mut output = String::new();
+        for (name, help, counter) in counters {
+            output.push_str(&format!("# HELP {} {}\n", name, help));
+            output.push_str(&format!("# TYPE {} counter\n", name));
+            output.push_str(&format!("{} {}\n", name, counter.load(Ordering::Relaxed)));
+        }
+
+        let search_duration = self.search_duration_micros.load(Ordering::Relaxed) as f64 / 1_000_000.0;
+        output.push_str("# HELP unipept_index_search_duration_seconds_total The total time spent searching\n");
+        output.push_str("# TYPE unipept_index_search_duration_seconds_total counter\n");
+        output.push_str(&format!("unipept_index_search_duration_seconds_total {}\n", search_duration));
+
+        output
+    }
+}
+
+impl AppState {
+    /// Creates the state of a server of which the indexes are not loaded yet
+    ///
+    /// # Arguments
+    /// * `default_index` - The name of the index searched by `/search`
+    /// * `default_equate_il` - The value used for `equate_il` when it is missing from a request
+    ///
+    /// # Returns
+    ///
+    /// Returns the state shared by all the endpoints
+    fn new(default_index: String, default_equate_il: bool) -> Self {
+        AppState {
+            searchers: OnceLock::new(),
+            default_index,
+            default_equate_il,
+            in_flight_requests: AtomicUsize::new(0),
+            metrics: Metrics::default()
+        }
+    }
+}
+
+/// Counts a request as in flight for as long as it is alive, also if the request is cancelled
+struct InFlightGuard<'a>(&'a AtomicUsize);
+
+impl<'a> InFlightGuard<'a> {
+    fn new(in_flight_requests: &'a AtomicUsize) -> Self {
+        in_flight_requests.fetch_add(1, Ordering::SeqCst);
+        InFlightGuard(in_flight_requests)
+    }
+}
+
+impl Drop for InFlightGuard<'_> {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::SeqCst);
+    }
 }
 
 /// Function used by serde to place a default value in the cutoff field of the input
@@ -49,18 +252,38 @@ fn default_true() -> bool {
 /// # Arguments
 /// * `peptides` - List of peptides we want to process
 /// * `cutoff` - The maximum amount of matches to process, default value 10000
-/// * `equate_il` - True if we want to equalize I and L during search
+/// * `equate_il` - True if we want to equalize I and L during search, the server default is used if
+///   missing
 /// * `clean_taxa` - True if we only want to use proteins marked as "valid"
+/// * `include_positions` - True if we want the positions of the peptide within each matching protein
+/// * `include_suffixes` - True if we want the positions of the matching suffixes in the text
 #[derive(Debug, Deserialize)]
 struct InputData {
     peptides: Vec<String>,
     #[serde(default = "default_cutoff")] // default value is 10000
     cutoff: usize,
-    #[serde(default = "bool::default")]
-    // default value is false // TODO: maybe default should be true?
-    equate_il: bool,
+    #[serde(default)] // default value is configured by the server
+    equate_il: Option<bool>,
+    #[serde(default = "bool::default")] // default false
+    tryptic: bool,
     #[serde(default = "bool::default")] // default false
-    tryptic: bool
+    include_positions: bool,
+    #[serde(default = "bool::default")] // default false
+    include_suffixes: bool
+}
+
+impl InputData {
+    /// Returns if I and L should be equated for this request
+    ///
+    /// # Arguments
+    /// * `default_equate_il` - The value used if the request did not specify `equate_il`
+    ///
+    /// # Returns
+    ///
+    /// Returns the `equate_il` value of the request, or `default_equate_il` if it is missing
+    fn equate_il(&self, default_equate_il: bool) -> bool {
+        self.equate_il.unwrap_or(default_equate_il)
+    }
 }
 
 #[tokio::main]
@@ -72,25 +295,185 @@ async fn main() {
     }
 }
 
-/// Endpoint executed for peptide matching, without any analysis
+/// Endpoint used to check if the server is running
+///
+/// # Returns
+///
+/// Returns 200 as soon as the server accepts requests, also while the index is still loading
+async fn health() -> StatusCode {
+    StatusCode::OK
+}
+
+/// Endpoint used to check if the server is ready to handle search requests
+///
+/// # Arguments
+/// * `state(state)` - The searcher object and the defaults provided by the server
+///
+/// # Returns
+///
+/// Returns 200 if the indexes are loaded, 503 if the indexes are still loading
+async fn ready(State(state): State<Arc<AppState>>) -> StatusCode {
+    match state.searchers.get() {
+        Some(_) => StatusCode::OK,
+        None => StatusCode::SERVICE_UNAVAILABLE
+    }
+}
+
+/// Endpoint executed for peptide matching in the default index, without any analysis
 ///
 /// # Arguments
-/// * `state(searcher)` - The searcher object provided by the server
+/// * `state(state)` - The searcher object and the defaults provided by the server
 /// * `data` - InputData object provided by the user with the peptides to be searched and the config
 ///
 /// # Returns
 ///
-/// Returns the search results from the index as a JSON
+/// Returns the search results from the index, together with the used search parameters, as a JSON.
+/// Returns 503 if the index is still loading.
 async fn search(
-    State(searcher): State<Arc<SparseSearcher>>,
+    State(state): State<Arc<AppState>>,
+    data: Json<InputData>
+) -> Result<Json<BatchSearchResponse>, StatusCode> {
+    let index_name = state.default_index.clone();
+    search_index(State(state), Path(index_name), data).await
+}
+
+/// Endpoint executed for peptide matching in the index with the given name, without any analysis
+///
+/// # Arguments
+/// * `state(state)` - The searcher object and the defaults provided by the server
+/// * `index_name` - The name of the index that is searched
+/// * `data` - InputData object provided by the user with the peptides to be searched and the config
+///
+/// # Returns
+///
+/// Returns the search results from the index, together with the used search parameters, as a JSON.
+/// Returns 503 if the indexes are still loading, 404 if there is no index with the given name, and
+/// 400 if I and L should be equated but the index was built without equating them.
+async fn search_index(
+    State(state): State<Arc<AppState>>,
+    Path(index_name): Path<String>,
     data: Json<InputData>
-) -> Result<Json<Vec<SearchResult>>, StatusCode> {
-    let search_result = search_all_peptides(&searcher, &data.peptides, data.cutoff, data.equate_il, data.tryptic);
+) -> Result<Json<BatchSearchResponse>, StatusCode> {
+    let searchers = state.searchers.get().ok_or(StatusCode::SERVICE_UNAVAILABLE)?;
+    let searcher = searchers.get(&index_name).ok_or(StatusCode::NOT_FOUND)?;
+
+    let start = Instant::now();
+    let config = SearchConfig {
+        cutoff: data.cutoff,
+        equate_il: data.equate_il(state.default_equate_il),
+        tryptic: data.tryptic,
+        include_positions: data.include_positions,
+        include_suffixes: data.include_suffixes,
+        ..SearchConfig::default()
+    };
+    if config.equate_il && !searcher.il_equated {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+    let search_result = search_all_peptides_batch_with_config(searcher, &data.peptides, &config);
+
+    let metrics = &state.metrics;
+    metrics.searches.fetch_add(1, Ordering::Relaxed);
+    metrics.peptides.fetch_add(data.peptides.len() as u64, Ordering::Relaxed);
+    let cutoff_hits = search_result.results.iter().filter(|result| result.cutoff_used).count();
+    metrics.cutoff_hits.fetch_add(cutoff_hits as u64, Ordering::Relaxed);
+    metrics.search_duration_micros.fetch_add(start.elapsed().as_micros() as u64, Ordering::Relaxed);
 
     Ok(Json(search_result))
 }
 
-/// Starts the server with the provided commandline arguments
+/// Endpoint exposing the counters of the handled searches in the Prometheus text format
+///
+/// # Arguments
+/// * `state(state)` - The state containing the counters
+///
+/// # Returns
+///
+/// Returns the counters as plain text
+async fn metrics(State(state): State<Arc<AppState>>) -> ([(header::HeaderName, &'static str); 1], String) {
+    ([(header::CONTENT_TYPE, "text/plain; version=0.0.4")], state.metrics.to_prometheus())
+}
+
+/// Middleware that keeps track of the amount of requests that are being handled, which is reported
+/// when the server shuts down
+///
+/// # Arguments
+/// * `state(state)` - The state containing the counter of the requests in flight
+/// * `request` - The request that is handled
+/// * `next` - The rest of the middleware stack and the endpoint
+///
+/// # Returns
+///
+/// Returns the response of the endpoint
+async fn track_in_flight(State(state): State<Arc<AppState>>, request: Request, next: Next) -> Response {
+    let _guard = InFlightGuard::new(&state.in_flight_requests);
+    next.run(request).await
+}
+
+/// Waits until the process receives Ctrl-C or, on unix, SIGTERM
+async fn termination_signal() {
+    let ctrl_c = async {
+        tokio::signal::ctrl_c().await.expect("Could not listen for Ctrl-C");
+    };
+
+    #[cfg(unix)]
+    let terminate = async {
+        tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate())
+            .expect("Could not listen for SIGTERM")
+            .recv()
+            .await;
+    };
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        _ = ctrl_c => {},
+        _ = terminate => {}
+    }
+}
+
+/// Waits for the signal to shut down the server, and reports the amount of requests that are still
+/// being handled. The server stops accepting connections and finishes these requests once this
+/// future resolves.
+///
+/// # Arguments
+/// * `signal` - The future that resolves when the server should shut down
+/// * `state` - The state containing the counter of the requests in flight
+async fn shutdown_signal(signal: impl Future<Output = ()>, state: Arc<AppState>) {
+    signal.await;
+
+    eprintln!();
+    eprintln!(
+        "🛑 Shutting down, waiting for {} requests in flight...",
+        state.in_flight_requests.load(Ordering::SeqCst)
+    );
+}
+
+/// Builds the router with all the endpoints of the server. Gzip compressed request bodies are
+/// decompressed before the body limit is checked, and responses are compressed if the client
+/// accepts it.
+///
+/// # Arguments
+/// * `state` - The state shared by all the endpoints
+///
+/// # Returns
+///
+/// Returns the router of the server
+fn app(state: Arc<AppState>) -> Router {
+    Router::new()
+        .route("/health", get(health))
+        .route("/ready", get(ready))
+        .route("/metrics", get(metrics))
+        .route("/search", post(search))
+        .route("/search/:index_name", post(search_index))
+        .layer(middleware::from_fn_with_state(state.clone(), track_in_flight))
+        .layer(DefaultBodyLimit::max(5 * 10_usize.pow(6)))
+        .layer(RequestDecompressionLayer::new())
+        .layer(CompressionLayer::new())
+        .with_state(state)
+}
+
+/// Starts the server with the provided commandline arguments. The server already accepts requests
+/// while the index is loading, so its readiness can be checked using the `/ready` endpoint.
 ///
 /// # Arguments
 /// * `args` - The provided commandline arguments
@@ -103,45 +486,163 @@ async fn search(
 ///
 /// Returns any error occurring during the startup or uptime of the server
 async fn start_server(args: Arguments) -> Result<(), Box<dyn Error>> {
-    let Arguments { database_file, index_file } = args;
+    let bind_address = args.bind_address();
+    let index_names = args.index_names()?;
+    let Arguments {
+        database_file,
+        index_file,
+        binary_database,
+        default_equate_il,
+        duplicate_accessions,
+        annotation_format,
+        max_result_bytes,
+        allow_legacy_index,
+        ..
+    } = args;
+
+    let state = Arc::new(AppState::new(index_names[0].clone(), default_equate_il));
+
+    let listener = tokio::net::TcpListener::bind(&bind_address).await?;
+    let server = tokio::spawn(
+        axum::serve(listener, app(state.clone()))
+            .with_graceful_shutdown(shutdown_signal(termination_signal(), state.clone()))
+            .into_future()
+    );
+
+    let searchers = tokio::task::spawn_blocking(move || {
+        let mut searchers = HashMap::new();
+        for ((name, database_file), index_file) in index_names.into_iter().zip(database_file).zip(index_file) {
+            eprintln!();
+            eprintln!("📦 Loading index {}...", name);
+            let mut searcher = load_searcher(
+                &database_file,
+                &index_file,
+                binary_database,
+                &duplicate_accessions,
+                &annotation_format,
+                allow_legacy_index
+            )
+            .map_err(|err| err.to_string())?;
+            if let Some(max_result_bytes) = max_result_bytes {
+                searcher.max_result_bytes = max_result_bytes;
+            }
+            searcher.index_id = index_id(&name, &searcher);
+            if default_equate_il && !searcher.il_equated {
+                return Err(format!(
+                    "The index {} was built without equating I and L, so I and L can not be equated by default",
+                    name
+                ));
+            }
+            searchers.insert(name, searcher);
+        }
+        Ok::<_, String>(searchers)
+    })
+    .await??;
+    state.searchers.set(searchers).map_err(|_| "The searchers were already loaded")?;
 
+    eprintln!();
+    eprintln!("🚀 Server is ready...");
+    server.await??;
+
+    Ok(())
+}
+
+/// Builds the identifier of an index that is reported with its search results. Besides the name of
+/// the index, it contains the amount of proteins and the size and sample rate of the suffix array,
+/// so the identifier changes when the index is rebuilt over another database.
+///
+/// # Arguments
+/// * `name` - The name of the index
+/// * `searcher` - The searcher of the index
+///
+/// # Returns
+///
+/// Returns the identifier of the index
+fn index_id(name: &str, searcher: &SparseSearcher) -> String {
+    format!("{}-{}-{}-{}", name, searcher.proteins.proteins.len(), searcher.sa.len(), searcher.sa.sample_rate())
+}
+
+/// Loads the suffix array and the proteins, and builds the searcher
+///
+/// # Arguments
+/// * `database_file` - The file with the proteins
+/// * `index_file` - The file with the suffix array
+/// * `binary_database` - If the database file contains the binary proteins instead of the
+///   tab-separated database
+/// * `duplicate_accessions` - What to do with proteins of which the accession already occurred in
+///   the tab-separated database
+/// * `annotation_format` - How the functional annotations are stored in the tab-separated database
+/// * `allow_legacy_index` - If index files without a header are accepted
+///
+/// # Returns
+///
+/// Returns the searcher object used to search the peptides, which only equates I and L if the suffix
+/// array was built with I and L equated
+///
+/// # Errors
+///
+/// Returns any error occurring while loading the suffix array or the proteins
+fn load_searcher(
+    database_file: &str,
+    index_file: &str,
+    binary_database: bool,
+    duplicate_accessions: &DuplicateAccessions,
+    annotation_format: &AnnotationFormat,
+    allow_legacy_index: bool
+) -> Result<SparseSearcher, Box<dyn Error>> {
     eprintln!();
     eprintln!("📋 Started loading the suffix array...");
-    let suffix_array = load_suffix_array_file(&index_file)?;
+    let (suffix_array, il_equated) = load_suffix_array_file(index_file, allow_legacy_index)?;
     eprintln!("✅ Successfully loaded the suffix array!");
     eprintln!("\tAmount of items: {}", suffix_array.len());
     eprintln!("\tAmount of bits per item: {}", suffix_array.bits_per_value());
     eprintln!("\tSample rate: {}", suffix_array.sample_rate());
+    eprintln!("\tI and L equated: {}", il_equated);
 
     eprintln!();
     eprintln!("📋 Started loading the proteins...");
-    let proteins = Proteins::try_from_database_file(&database_file)?;
+    let proteins = if binary_database {
+        load_proteins(&mut BufReader::new(File::open(database_file)?), allow_legacy_index)?
+    } else {
+        Proteins::try_from_database_file_with_annotation_format(database_file, duplicate_accessions, annotation_format)?
+    };
     eprintln!("✅ Successfully loaded the proteins!");
+    for accession in &proteins.skipped_accessions {
+        eprintln!("\tSkipped duplicate accession {}", accession);
+    }
 
-    let searcher = Arc::new(SparseSearcher::new(suffix_array, proteins));
-
-    // build our application with a route
-    let app = Router::new()
-        .route("/search", post(search))
-        .layer(DefaultBodyLimit::max(5 * 10_usize.pow(6)))
-        .with_state(searcher);
-
-    let listener = tokio::net::TcpListener::bind("0.0.0.0:3000").await?;
-
-    eprintln!();
-    eprintln!("🚀 Server is ready...");
-    axum::serve(listener, app).await?;
-
-    Ok(())
+    let mut searcher = SparseSearcher::new(suffix_array, proteins);
+    searcher.il_equated = il_equated;
+    Ok(searcher)
 }
 
-fn load_suffix_array_file(file: &str) -> Result<SuffixArray, Box<dyn Error>> {
+fn load_suffix_array_file(file: &str, allow_legacy: bool) -> Result<(SuffixArray, bool), Box<dyn Error>> {
     // Open the suffix array file
     let mut sa_file = File::open(file)?;
 
     // Create a buffer reader for the file
     let mut reader = BufReader::new(&mut sa_file);
 
+    read_suffix_array(&mut reader, allow_legacy)
+}
+
+/// Reads a suffix array, which can be compressed or not, from a reader
+///
+/// # Arguments
+/// * `reader` - The reader containing the suffix array
+/// * `allow_legacy` - If suffix arrays without a header are accepted
+///
+/// # Returns
+///
+/// Returns the suffix array, together with true if it was built with every L in the text translated
+/// to an I
+///
+/// # Errors
+///
+/// Returns an error if the reader does not contain a suffix array, or if reading it failed
+fn read_suffix_array(reader: &mut impl BufRead, allow_legacy: bool) -> Result<(SuffixArray, bool), Box<dyn Error>> {
+    let flags = read_header(reader, FileKind::SuffixArray, allow_legacy)?;
+
     // Read the bits per value from the binary file (1 byte)
     let mut bits_per_value_buffer = [0_u8; 1];
     reader
@@ -149,9 +650,379 @@ fn load_suffix_array_file(file: &str) -> Result<SuffixArray, Box<dyn Error>> {
         .map_err(|_| "Could not read the flags from the binary file")?;
     let bits_per_value = bits_per_value_buffer[0];
 
-    if bits_per_value == 64 {
-        load_suffix_array(&mut reader)
+    let suffix_array = if bits_per_value == 64 {
+        load_suffix_array(reader)?
     } else {
-        load_compressed_suffix_array(&mut reader, bits_per_value as usize)
+        load_compressed_suffix_array(reader, bits_per_value as usize)?
+    };
+
+    Ok((suffix_array, flags & EQUATE_IL_FLAG != 0))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{Read, Write};
+
+    use axum::{body::Body, http::header};
+    use index_format::MAGIC;
+    use flate2::{read::GzDecoder, write::GzEncoder, Compression};
+    use sa_index::binary::dump_suffix_array;
+    use sa_mappings::proteins::{Delimiters, Protein};
+    use text_compression::{dump_protein_text, ProteinText};
+    use tower::ServiceExt;
+
+    use super::*;
+
+    fn create_searcher(uniprot_id: &str) -> SparseSearcher {
+        let proteins = Proteins {
+            text: ProteinText::from_string("AC$"),
+            proteins: vec![Protein {
+                uniprot_id: uniprot_id.to_string(),
+                taxon_id: 1,
+                functional_annotations: vec![]
+            }],
+            accession_index: None,
+            original_sequences: None,
+            delimiters: Delimiters::default(),
+            skipped_accessions: Vec::new()
+        };
+        let suffix_array = SuffixArray::Original(vec![2, 0, 1], 1);
+        SparseSearcher::new(suffix_array, proteins)
+    }
+
+    fn create_state(loaded: bool) -> Arc<AppState> {
+        let state = Arc::new(AppState::new("index".to_string(), false));
+
+        if loaded {
+            let searchers = HashMap::from([("index".to_string(), create_searcher("P12345"))]);
+            state.searchers.set(searchers).ok().unwrap();
+        }
+
+        state
+    }
+
+    async fn get_status(state: Arc<AppState>, uri: &str) -> StatusCode {
+        let request = axum::http::Request::get(uri).body(Body::empty()).unwrap();
+        app(state).oneshot(request).await.unwrap().status()
+    }
+
+    #[test]
+    fn test_bind_address_default() {
+        let args = Arguments::parse_from(["sa-server", "-d", "proteins.tsv", "-i", "index.bin"]);
+        assert_eq!(args.bind_address(), "0.0.0.0:3000");
+    }
+
+    #[test]
+    fn test_bind_address_configured() {
+        let args = Arguments::parse_from([
+            "sa-server",
+            "-d",
+            "proteins.tsv",
+            "-i",
+            "index.bin",
+            "--host",
+            "127.0.0.1",
+            "--port",
+            "8080"
+        ]);
+        assert_eq!(args.bind_address(), "127.0.0.1:8080");
+    }
+
+    fn gzip(data: &[u8]) -> Vec<u8> {
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(data).unwrap();
+        encoder.finish().unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_search_gzip() {
+        let request = axum::http::Request::post("/search")
+            .header(header::CONTENT_TYPE, "application/json")
+            .header(header::CONTENT_ENCODING, "gzip")
+            .header(header::ACCEPT_ENCODING, "gzip")
+            .body(Body::from(gzip(br#"{"peptides": ["AC"]}"#)))
+            .unwrap();
+        let response = app(create_state(true)).oneshot(request).await.unwrap();
+
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.headers()[header::CONTENT_ENCODING], "gzip");
+
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let mut json = String::new();
+        GzDecoder::new(&body[..]).read_to_string(&mut json).unwrap();
+        let json: serde_json::Value = serde_json::from_str(&json).unwrap();
+
+        assert_eq!(json["results"][0]["sequence"], "AC");
+        assert_eq!(json["results"][0]["proteins"][0]["uniprot_accession"], "P12345");
+    }
+
+    #[tokio::test]
+    async fn test_search_include_suffixes() {
+        for (body, expected_suffixes) in [
+            (r#"{"peptides": ["AC"], "include_suffixes": true}"#, serde_json::json!([0])),
+            (r#"{"peptides": ["AC"]}"#, serde_json::Value::Null)
+        ] {
+            let request = axum::http::Request::post("/search")
+                .header(header::CONTENT_TYPE, "application/json")
+                .body(Body::from(body))
+                .unwrap();
+            let response = app(create_state(true)).oneshot(request).await.unwrap();
+            assert_eq!(response.status(), StatusCode::OK);
+
+            let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+            let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+            assert_eq!(json["results"][0]["suffixes"], expected_suffixes);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_search_gzip_body_limit() {
+        // the compressed body is small, but the decompressed body exceeds the body limit
+        let peptides = vec!["A".repeat(100); 60_000];
+        let body = serde_json::to_vec(&serde_json::json!({ "peptides": peptides })).unwrap();
+        let request = axum::http::Request::post("/search")
+            .header(header::CONTENT_TYPE, "application/json")
+            .header(header::CONTENT_ENCODING, "gzip")
+            .body(Body::from(gzip(&body)))
+            .unwrap();
+        let response = app(create_state(true)).oneshot(request).await.unwrap();
+
+        assert_eq!(response.status(), StatusCode::PAYLOAD_TOO_LARGE);
+    }
+
+    #[test]
+    fn test_read_suffix_array() {
+        let mut buffer = Vec::new();
+        dump_suffix_array(&vec![2, 0, 1], 1, true, &mut buffer).unwrap();
+
+        let (suffix_array, il_equated) = read_suffix_array(&mut buffer.as_slice(), false).unwrap();
+        assert_eq!(suffix_array.iter().collect::<Vec<i64>>(), vec![2, 0, 1]);
+        assert!(il_equated);
+
+        // suffix arrays without a header are only accepted if legacy files are allowed, and they
+        // were always built with I and L equated
+        let legacy_buffer = &buffer[MAGIC.len() + 2..];
+        assert!(read_suffix_array(&mut &legacy_buffer[..], false).is_err());
+        let (suffix_array, il_equated) = read_suffix_array(&mut &legacy_buffer[..], true).unwrap();
+        assert_eq!(suffix_array.iter().collect::<Vec<i64>>(), vec![2, 0, 1]);
+        assert!(il_equated);
+    }
+
+    #[test]
+    fn test_read_suffix_array_il_distinguished() {
+        let mut buffer = Vec::new();
+        dump_suffix_array(&vec![2, 0, 1], 1, false, &mut buffer).unwrap();
+
+        let (_, il_equated) = read_suffix_array(&mut buffer.as_slice(), false).unwrap();
+        assert!(!il_equated);
+    }
+
+    #[test]
+    fn test_read_zstd_compressed_suffix_array() {
+        let mut buffer = Vec::new();
+        sa_compression::dump_compressed_suffix_array(vec![2, 0, 1], 1, false, 2, Some(3), &mut buffer).unwrap();
+
+        let (suffix_array, il_equated) = read_suffix_array(&mut buffer.as_slice(), false).unwrap();
+        assert_eq!(suffix_array.iter().collect::<Vec<i64>>(), vec![2, 0, 1]);
+        assert!(!il_equated);
+    }
+
+    #[test]
+    fn test_read_suffix_array_wrong_kind() {
+        let mut buffer = Vec::new();
+        dump_protein_text(&ProteinText::from_string("AC$"), &mut buffer).unwrap();
+
+        let err = read_suffix_array(&mut buffer.as_slice(), true).err().unwrap();
+        assert_eq!(err.to_string(), "Unexpected file format: found text, expected suffix array");
+    }
+
+    #[tokio::test]
+    async fn test_health() {
+        assert_eq!(get_status(create_state(false), "/health").await, StatusCode::OK);
+        assert_eq!(get_status(create_state(true), "/health").await, StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn test_ready() {
+        assert_eq!(get_status(create_state(false), "/ready").await, StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(get_status(create_state(true), "/ready").await, StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn test_search_ready() {
+        let search_request = || {
+            axum::http::Request::post("/search")
+                .header(header::CONTENT_TYPE, "application/json")
+                .body(Body::from(r#"{"peptides": ["AC"]}"#))
+                .unwrap()
+        };
+
+        let response = app(create_state(false)).oneshot(search_request()).await.unwrap();
+        assert_eq!(response.status(), StatusCode::SERVICE_UNAVAILABLE);
+
+        let response = app(create_state(true)).oneshot(search_request()).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn test_search_il_distinguished_index() {
+        let mut searcher = create_searcher("P12345");
+        searcher.il_equated = false;
+        let state = Arc::new(AppState::new("index".to_string(), false));
+        state.searchers.set(HashMap::from([("index".to_string(), searcher)])).ok().unwrap();
+
+        let search_request = |body: &'static str| {
+            axum::http::Request::post("/search")
+                .header(header::CONTENT_TYPE, "application/json")
+                .body(Body::from(body))
+                .unwrap()
+        };
+
+        let response = app(state.clone()).oneshot(search_request(r#"{"peptides": ["AC"]}"#)).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        // I and L can not be equated in an index that distinguishes them
+        let request = search_request(r#"{"peptides": ["AC"], "equate_il": true}"#);
+        let response = app(state).oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[test]
+    fn test_index_names() {
+        let args = Arguments::parse_from(["sa-server", "-d", "proteins.tsv", "-i", "data/index.bin"]);
+        assert_eq!(args.index_names().unwrap(), vec!["index"]);
+
+        let args = Arguments::parse_from([
+            "sa-server",
+            "-d",
+            "human.tsv",
+            "-i",
+            "human.bin",
+            "-d",
+            "mouse.tsv",
+            "-i",
+            "mouse.bin",
+            "--index-name",
+            "human",
+            "--index-name",
+            "mouse"
+        ]);
+        assert_eq!(args.index_names().unwrap(), vec!["human", "mouse"]);
+
+        let args = Arguments::parse_from(["sa-server", "-d", "a.tsv", "-i", "a.bin", "-i", "b.bin"]);
+        assert!(args.index_names().is_err());
+
+        let args = Arguments::parse_from(["sa-server", "-d", "a.tsv", "-i", "x/a.bin", "-d", "b.tsv", "-i", "y/a.bin"]);
+        assert!(args.index_names().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_search_index_routing() {
+        let state = Arc::new(AppState::new("human".to_string(), false));
+        let searchers = HashMap::from([
+            ("human".to_string(), create_searcher("P12345")),
+            ("mouse".to_string(), create_searcher("Q67890")),
+        ]);
+        state.searchers.set(searchers).ok().unwrap();
+
+        let search = |uri: &str| {
+            let request = axum::http::Request::post(uri)
+                .header(header::CONTENT_TYPE, "application/json")
+                .body(Body::from(r#"{"peptides": ["AC"]}"#))
+                .unwrap();
+            app(state.clone()).oneshot(request)
+        };
+        let accession = |body: axum::body::Bytes| {
+            let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+            json["results"][0]["proteins"][0]["uniprot_accession"].as_str().unwrap().to_string()
+        };
+
+        for (uri, expected) in [("/search", "P12345"), ("/search/human", "P12345"), ("/search/mouse", "Q67890")] {
+            let response = search(uri).await.unwrap();
+            assert_eq!(response.status(), StatusCode::OK);
+            let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+            assert_eq!(accession(body), expected);
+        }
+
+        assert_eq!(search("/search/rat").await.unwrap().status(), StatusCode::NOT_FOUND);
+    }
+
+    #[test]
+    fn test_index_id() {
+        let searcher = create_searcher("P12345");
+        assert_eq!(index_id("human", &searcher), "human-1-3-1");
+        assert_ne!(index_id("human", &searcher), index_id("mouse", &searcher));
+    }
+
+    #[tokio::test]
+    async fn test_shutdown_signal() {
+        let state = create_state(true);
+        let _guard = InFlightGuard::new(&state.in_flight_requests);
+        assert_eq!(state.in_flight_requests.load(Ordering::SeqCst), 1);
+
+        let (sender, receiver) = tokio::sync::oneshot::channel::<()>();
+        let shutdown = tokio::spawn(shutdown_signal(
+            async {
+                receiver.await.ok();
+            },
+            state.clone()
+        ));
+
+        // the shutdown only starts when the signal fires
+        tokio::task::yield_now().await;
+        assert!(!shutdown.is_finished());
+
+        sender.send(()).unwrap();
+        tokio::time::timeout(std::time::Duration::from_secs(5), shutdown).await.unwrap().unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_in_flight_requests() {
+        let state = create_state(true);
+        assert_eq!(get_status(state.clone(), "/health").await, StatusCode::OK);
+
+        // the request is no longer in flight once it is handled
+        assert_eq!(state.in_flight_requests.load(Ordering::SeqCst), 0);
+    }
+
+    #[tokio::test]
+    async fn test_metrics() {
+        let state = create_state(true);
+
+        let request = axum::http::Request::post("/search")
+            .header(header::CONTENT_TYPE, "application/json")
+            .body(Body::from(r#"{"peptides": ["AC", "C", "W"], "cutoff": 1}"#))
+            .unwrap();
+        assert_eq!(app(state.clone()).oneshot(request).await.unwrap().status(), StatusCode::OK);
+
+        let request = axum::http::Request::get("/metrics").body(Body::empty()).unwrap();
+        let response = app(state).oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert!(response.headers()[header::CONTENT_TYPE].to_str().unwrap().starts_with("text/plain"));
+
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let metrics = String::from_utf8(body.to_vec()).unwrap();
+        assert!(metrics.contains("# TYPE unipept_index_searches_total counter\n"));
+        assert!(metrics.contains("\nunipept_index_searches_total 1\n"));
+        assert!(metrics.contains("\nunipept_index_peptides_total 3\n"));
+        // "AC" and "C" both reach the cutoff of 1 match, "W" has no matches
+        assert!(metrics.contains("\nunipept_index_cutoff_hits_total 2\n"));
+        assert!(metrics.contains("\nunipept_index_search_duration_seconds_total "));
+    }
+
+    #[test]
+    fn test_equate_il_server_default() {
+        let data: InputData = serde_json::from_str(r#"{"peptides": ["AAL"]}"#).unwrap();
+        assert!(data.equate_il(true));
+        assert!(!data.equate_il(false));
+    }
+
+    #[test]
+    fn test_equate_il_request_override() {
+        let data: InputData = serde_json::from_str(r#"{"peptides": ["AAL"], "equate_il": false}"#).unwrap();
+        assert!(!data.equate_il(true));
+
+        let data: InputData = serde_json::from_str(r#"{"peptides": ["AAL"], "equate_il": true}"#).unwrap();
+        assert!(data.equate_il(false));
     }
 }
@@ -0,0 +1,63 @@
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use text_compression::ProteinText;
+
+/// The amino acids used to generate the synthetic text.
+const AMINO_ACIDS: &[u8] = b"ACDEFGHIKLMNPQRSTVWY";
+
+/// The length of the synthetic text.
+const TEXT_LENGTH: usize = 1 << 16;
+
+/// The length of the peptides of which the I and L positions are checked.
+const PEPTIDE_LENGTH: usize = 30;
+
+/// Generate a text with a high density of I's and L's, together with the start positions of the
+/// peptides that are checked. A simple linear congruential generator is used, so every run checks
+/// the same peptides.
+fn generate_text() -> (ProteinText, String, Vec<usize>) {
+    let mut seed: u64 = 42;
+    let mut next_random = move || {
+        seed = seed.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+        (seed >> 33) as usize
+    };
+
+    let input_string: String = (0..TEXT_LENGTH)
+        .map(|_| if next_random() % 3 == 0 { b'L' } else { AMINO_ACIDS[next_random() % AMINO_ACIDS.len()] } as char)
+        .collect();
+    let starts = (0..1000).map(|_| next_random() % (TEXT_LENGTH - PEPTIDE_LENGTH)).collect();
+
+    (ProteinText::from_string(&input_string), input_string, starts)
+}
+
+fn criterion_benchmark(c: &mut Criterion) {
+    let (text, input_string, starts) = generate_text();
+    let peptides: Vec<(usize, Vec<usize>, &[u8])> = starts
+        .iter()
+        .map(|&start| {
+            let peptide = &input_string.as_bytes()[start..start + PEPTIDE_LENGTH];
+            let il_locations = (0..PEPTIDE_LENGTH).filter(|&i| peptide[i] == b'I' || peptide[i] == b'L').collect();
+            (start, il_locations, peptide)
+        })
+        .collect();
+
+    let mut group = c.benchmark_group("check_il_locations");
+    group.bench_function("per_location", |b| {
+        b.iter(|| {
+            for (start, il_locations, peptide) in &peptides {
+                let text_slice = text.slice(*start, start + PEPTIDE_LENGTH);
+                black_box(text_slice.check_il_locations(0, il_locations, peptide));
+            }
+        })
+    });
+    group.bench_function("batched", |b| {
+        b.iter(|| {
+            for (start, il_locations, peptide) in &peptides {
+                let text_slice = text.slice(*start, start + PEPTIDE_LENGTH);
+                black_box(text_slice.check_il_locations_batched(0, il_locations, peptide));
+            }
+        })
+    });
+    group.finish();
+}
+
+criterion_group!(benches, criterion_benchmark);
+criterion_main!(benches);
@@ -1,12 +1,28 @@
 use std::{
+    cmp::min,
     collections::HashMap,
     error::Error,
     io::{BufRead, Write}
 };
 
 use bitarray::{data_to_writer, Binary, BitArray};
+use index_format::{write_header, FileKind, IndexError};
 
-/// Structure representing the proteins, stored in a bit array using 5 bits per amino acid.
+/// The maximum distance between the first and last I or L position for which
+/// `ProteinTextSlice::check_il_locations_batched` decodes the characters in a single pass.
+const IL_WINDOW_SIZE: usize = 64;
+
+/// The characters of the default protein alphabet, in the order of their 5 bit numbers.
+pub static PROTEIN_ALPHABET: &[u8] = b"ABCDEFGHIKLMNOPQRSTUVWXYZ-$";
+
+/// Flag that is set in the bits per value byte if the text uses a custom alphabet, which is then
+/// written after this byte. The amount of bits per value is at most 8, so this bit is never set in
+/// files with the default alphabet.
+pub const CUSTOM_ALPHABET_FLAG: u8 = 0x80;
+
+/// Structure representing the proteins, stored in a bit array using 5 bits per amino acid, or
+/// fewer bits if the text is built over a smaller alphabet.
+#[derive(Clone, PartialEq)]
 pub struct ProteinText {
     /// Bit array holding the sequence of amino acids
     bit_array: BitArray,
@@ -23,9 +39,22 @@ impl ProteinText {
     ///
     /// Returns the hashmap
     fn create_char_to_5bit_hashmap() -> HashMap<u8, u8> {
+        ProteinText::create_char_to_code_hashmap(PROTEIN_ALPHABET)
+    }
+
+    /// Creates the hashmap storing the mappings between the characters of an alphabet and their
+    /// position in the alphabet.
+    ///
+    /// # Arguments
+    /// * `alphabet` - The characters of the alphabet.
+    ///
+    /// # Returns
+    ///
+    /// Returns the hashmap
+    fn create_char_to_code_hashmap(alphabet: &[u8]) -> HashMap<u8, u8> {
         let mut hashmap = HashMap::<u8, u8>::new();
-        for (i, c) in "ABCDEFGHIKLMNOPQRSTUVWXYZ-$".chars().enumerate() {
-            hashmap.insert(c as u8, i as u8);
+        for (i, &c) in alphabet.iter().enumerate() {
+            hashmap.insert(c, i as u8);
         }
 
         hashmap
@@ -37,11 +66,19 @@ impl ProteinText {
     ///
     /// Returns the vector
     fn create_bit5_to_char() -> Vec<u8> {
-        let mut vec = Vec::<u8>::new();
-        for c in "ABCDEFGHIKLMNOPQRSTUVWXYZ-$".chars() {
-            vec.push(c as u8);
-        }
-        vec
+        PROTEIN_ALPHABET.to_vec()
+    }
+
+    /// Calculates the amount of bits needed to store every character of an alphabet.
+    ///
+    /// # Arguments
+    /// * `alphabet_len` - The amount of characters in the alphabet.
+    ///
+    /// # Returns
+    ///
+    /// The amount of bits needed, which is `ceil(log2(alphabet_len))` with a minimum of 1.
+    pub fn bits_for_alphabet(alphabet_len: usize) -> usize {
+        (usize::BITS - alphabet_len.saturating_sub(1).leading_zeros()).max(1) as usize
     }
 
     /// Creates the compressed text from a string.
@@ -66,6 +103,35 @@ impl ProteinText {
         Self { bit_array, char_to_5bit, bit5_to_char }
     }
 
+    /// Creates the compressed text from a string, using a custom alphabet. Every character is
+    /// stored using `ceil(log2(alphabet.len()))` bits, so a small alphabet (e.g. a reduced
+    /// physicochemical alphabet) needs fewer bits than the 5 bits of the default protein alphabet.
+    ///
+    /// # Arguments
+    /// * `input_string` - The text (proteins) in string format
+    /// * `alphabet` - The distinct characters that can occur in the text, at most 256
+    ///
+    /// # Returns
+    ///
+    /// An instance of `ProteinText`
+    pub fn from_string_with_alphabet(input_string: &str, alphabet: &[u8]) -> ProteinText {
+        assert!(alphabet.len() <= 256, "The alphabet can contain at most 256 characters");
+
+        let char_to_5bit = ProteinText::create_char_to_code_hashmap(alphabet);
+        let bit5_to_char = alphabet.to_vec();
+
+        let bits_per_value = ProteinText::bits_for_alphabet(alphabet.len());
+        let mut bit_array = BitArray::with_capacity(input_string.len(), bits_per_value);
+        for (i, c) in input_string.bytes().enumerate() {
+            let code: u8 = *char_to_5bit
+                .get(&c)
+                .unwrap_or_else(|| panic!("Input character '{}' not in alphabet", c as char));
+            bit_array.set(i, code as u64);
+        }
+
+        Self { bit_array, char_to_5bit, bit5_to_char }
+    }
+
     /// Creates the compressed text from a vector.
     ///
     /// # Arguments
@@ -88,6 +154,80 @@ impl ProteinText {
         Self { bit_array, char_to_5bit, bit5_to_char }
     }
 
+    /// Creates the compressed text from a reader, without keeping the full text in memory as a string.
+    /// The characters are read in chunks and stored in a bit array that grows when needed. Line
+    /// breaks and other whitespace in the input are ignored.
+    ///
+    /// # Arguments
+    /// * `reader` - The reader containing the text (proteins), already concatenated with the
+    ///   separation and termination characters.
+    ///
+    /// # Returns
+    ///
+    /// An instance of `ProteinText`
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if reading from the reader fails or if a character is not in the alphabet.
+    pub fn from_reader(mut reader: impl BufRead) -> Result<ProteinText, Box<dyn Error>> {
+        let char_to_5bit = ProteinText::create_char_to_5bit_hashmap();
+        let bit5_to_char = ProteinText::create_bit5_to_char();
+
+        let mut bit_array = BitArray::with_capacity(8 * 1024, 5);
+        let mut text_length = 0;
+
+        loop {
+            let buffer = reader.fill_buf()?;
+            if buffer.is_empty() {
+                break;
+            }
+
+            for &c in buffer {
+                if c.is_ascii_whitespace() {
+                    continue;
+                }
+
+                let char_5bit: u8 =
+                    *char_to_5bit.get(&c).ok_or_else(|| format!("Input character '{}' not in alphabet", c as char))?;
+
+                // Double the capacity of the bit array if it is full
+                if text_length == bit_array.len() {
+                    bit_array = ProteinText::copy_bit_array(&bit_array, 2 * bit_array.len());
+                }
+
+                bit_array.set(text_length, char_5bit as u64);
+                text_length += 1;
+            }
+
+            let bytes_read = buffer.len();
+            reader.consume(bytes_read);
+        }
+
+        // Shrink the bit array to the actual length of the text
+        let bit_array = ProteinText::copy_bit_array(&bit_array, text_length);
+
+        Ok(Self { bit_array, char_to_5bit, bit5_to_char })
+    }
+
+    /// Copies the values of a bit array into a new bit array with a given length.
+    ///
+    /// # Arguments
+    /// * `bit_array` - The bit array to copy the values from.
+    /// * `length` - The length of the new bit array.
+    ///
+    /// # Returns
+    ///
+    /// A new bit array containing the first `length` values of `bit_array`, padded with zeros if
+    /// `bit_array` is shorter.
+    fn copy_bit_array(bit_array: &BitArray, length: usize) -> BitArray {
+        let mut copy = BitArray::with_capacity(length, bit_array.bits_per_value());
+        for i in 0..min(length, bit_array.len()) {
+            copy.set(i, bit_array.get(i));
+        }
+
+        copy
+    }
+
     /// Creates the compressed text from a bit array.
     ///
     /// # Arguments
@@ -102,6 +242,22 @@ impl ProteinText {
         Self { bit_array, char_to_5bit, bit5_to_char }
     }
 
+    /// Creates the compressed text from a bit array, using a custom alphabet.
+    ///
+    /// # Arguments
+    /// * `bit_array` - The text (proteins) in a bit array, storing the position of every character
+    ///   in the alphabet.
+    /// * `alphabet` - The characters of the alphabet, in the order of the numbers they are stored as
+    ///
+    /// # Returns
+    ///
+    /// An instance of `ProteinText`
+    pub fn new_with_alphabet(bit_array: BitArray, alphabet: &[u8]) -> ProteinText {
+        let char_to_5bit = ProteinText::create_char_to_code_hashmap(alphabet);
+        let bit5_to_char = alphabet.to_vec();
+        Self { bit_array, char_to_5bit, bit5_to_char }
+    }
+
     /// Creates an instance of `ProteinText` with a given capacity.
     ///
     /// # Arguments
@@ -140,6 +296,24 @@ impl ProteinText {
         self.bit_array.set(index, char_5bit as u64);
     }
 
+    /// Queries the amount of bits used to store a single character.
+    ///
+    /// # Returns
+    ///
+    /// the amount of bits per character
+    pub fn bits_per_value(&self) -> usize {
+        self.bit_array.bits_per_value()
+    }
+
+    /// Queries the alphabet of the text.
+    ///
+    /// # Returns
+    ///
+    /// the characters of the alphabet, in the order of the numbers they are stored as
+    pub fn alphabet(&self) -> &[u8] {
+        &self.bit5_to_char
+    }
+
     /// Queries the length of the text.
     ///
     /// # Returns
@@ -180,6 +354,131 @@ impl ProteinText {
     pub fn slice(&self, start: usize, end: usize) -> ProteinTextSlice {
         ProteinTextSlice::new(self, start, end)
     }
+
+    /// Decodes the characters in the range `[start, end)` of the text.
+    ///
+    /// # Arguments
+    /// * `start` - The index of the first character to decode.
+    /// * `end` - The index after the last character to decode.
+    ///
+    /// # Returns
+    ///
+    /// the decoded characters as a `Vec<u8>`.
+    pub fn decode_range(&self, start: usize, end: usize) -> Vec<u8> {
+        let mut out = Vec::with_capacity(end - start);
+        self.decode_range_into(start, end, &mut out);
+        out
+    }
+
+    /// Decodes the characters in the range `[start, end)` of the text into the given buffer. The
+    /// buffer is cleared first, so it can be reused for multiple ranges without reallocating.
+    ///
+    /// # Arguments
+    /// * `start` - The index of the first character to decode.
+    /// * `end` - The index after the last character to decode.
+    /// * `out` - The buffer to fill with the decoded characters.
+    pub fn decode_range_into(&self, start: usize, end: usize, out: &mut Vec<u8>) {
+        out.clear();
+        out.extend((start..end).map(|index| self.get(index)));
+    }
+
+    /// Checks if the characters starting at `start` are equal to the given characters, comparing
+    /// multiple characters at once.
+    ///
+    /// # Arguments
+    /// * `start` - The index of the first character to compare.
+    /// * `values` - The characters to compare with.
+    ///
+    /// # Returns
+    ///
+    /// true if all the characters are equal, false otherwise.
+    pub fn range_equals(&self, start: usize, values: &[u8]) -> bool {
+        let mut values_5bit = Vec::with_capacity(values.len());
+        for value in values {
+            match self.char_to_5bit.get(value) {
+                Some(&value_5bit) => values_5bit.push(value_5bit as u64),
+                None => return false
+            }
+        }
+
+        self.bit_array.range_equals(start, &values_5bit)
+    }
+
+    /// Creates a copy of the text in which every L is replaced by an I.
+    ///
+    /// # Returns
+    ///
+    /// A new `ProteinText` in which I and L are equated.
+    pub fn equate_il(&self) -> ProteinText {
+        let mut text = self.clone();
+        text.equate_il_in_place();
+        text
+    }
+
+    /// Replaces every L in the text by an I, by rewriting the 5 bit codes in the existing bit array.
+    /// The text is left unchanged if its alphabet does not contain both I and L.
+    pub fn equate_il_in_place(&mut self) {
+        let (Some(&l_5bit), Some(&i_5bit)) = (self.char_to_5bit.get(&b'L'), self.char_to_5bit.get(&b'I')) else {
+            return;
+        };
+        let (l_5bit, i_5bit) = (l_5bit as u64, i_5bit as u64);
+        for index in 0..self.len() {
+            if self.bit_array.get(index) == l_5bit {
+                self.bit_array.set(index, i_5bit);
+            }
+        }
+    }
+
+    /// Splits the text into a slice per protein, using `-` as the separator between proteins. The
+    /// `$` that terminates the text is not part of the last slice.
+    ///
+    /// # Returns
+    ///
+    /// An iterator over the `ProteinTextSlice`s of the proteins in the text.
+    pub fn split_on_separator(&self) -> impl Iterator<Item = ProteinTextSlice<'_>> + '_ {
+        let mut start = 0;
+        std::iter::from_fn(move || {
+            if start >= self.len() {
+                return None;
+            }
+
+            let mut end = start;
+            while end < self.len() && self.get(end) != b'-' && self.get(end) != b'$' {
+                end += 1;
+            }
+
+            let slice = self.slice(start, end);
+            start = if end < self.len() && self.get(end) == b'-' { end + 1 } else { self.len() };
+            Some(slice)
+        })
+    }
+
+    /// Counts the number of proteins in the text, by counting the characters that end a protein.
+    ///
+    /// # Arguments
+    /// * `separator` - The character that separates two proteins.
+    /// * `terminator` - The character that terminates the text.
+    ///
+    /// # Returns
+    ///
+    /// The number of proteins in the text. A trailing protein that is not followed by a separator
+    /// or terminator is also counted.
+    pub fn protein_count(&self, separator: u8, terminator: u8) -> usize {
+        let mut count = 0;
+        let mut last_character = separator;
+        for character in self.iter() {
+            if character == separator || character == terminator {
+                count += 1;
+            }
+            last_character = character;
+        }
+
+        if last_character != separator && last_character != terminator {
+            count += 1;
+        }
+
+        count
+    }
 }
 
 /// Structure representing a slice of a `ProteinText`.
@@ -244,17 +543,38 @@ impl<'a> ProteinTextSlice<'a> {
     /// True if the slice is equal to the given array, false otherwise.
     #[inline]
     pub fn equals_slice(&self, other: &[u8], equate_il: bool) -> bool {
+        self.common_prefix_len(other, equate_il) == other.len()
+    }
+
+    /// Computes how many leading characters of the slice and a given array of `u8` are equal.
+    /// I and L can be equated.
+    ///
+    /// # Arguments
+    /// * `other` - the array of `u8` to compare the slice with.
+    /// * `equate_il` - true if I and L need to be equated, false otherwise.
+    ///
+    /// # Returns
+    ///
+    /// The length of the common prefix of the slice and the given array, which is also the position
+    /// of the first mismatch.
+    #[inline]
+    pub fn common_prefix_len(&self, other: &[u8], equate_il: bool) -> usize {
         if equate_il {
-            other.iter().zip(self.iter()).all(|(&search_character, text_character)| {
-                search_character == text_character
-                    || (search_character == b'I' && text_character == b'L')
-                    || (search_character == b'L' && text_character == b'I')
-            })
+            other
+                .iter()
+                .zip(self.iter())
+                .take_while(|&(&search_character, text_character)| {
+                    search_character == text_character
+                        || (search_character == b'I' && text_character == b'L')
+                        || (search_character == b'L' && text_character == b'I')
+                })
+                .count()
         } else {
             other
                 .iter()
                 .zip(self.iter())
-                .all(|(&search_character, text_character)| search_character == text_character)
+                .take_while(|&(&search_character, text_character)| search_character == text_character)
+                .count()
         }
     }
 
@@ -278,6 +598,57 @@ impl<'a> ProteinTextSlice<'a> {
         true
     }
 
+    /// Check if the slice and a given array of `u8` are equal on the I and L positions, with the
+    /// same result as `check_il_locations`. If all the I and L positions lie within a window of
+    /// `IL_WINDOW_SIZE` characters, the blocks of the bit array containing that window are copied
+    /// once into a buffer on the stack, and the characters are decoded from that buffer.
+    ///
+    /// # Arguments
+    /// * `skip` - The amount of positions this slice skipped, this has an influence on the I and L positions.
+    /// * `il_locations` - The positions where I and L occur.
+    /// * `search_string` -  An array of `u8` to compare the slice with.
+    ///
+    /// # Returns
+    ///
+    /// True if the slice and `search_string` have the same contents on the I and L positions, false otherwise.
+    pub fn check_il_locations_batched(&self, skip: usize, il_locations: &[usize], search_string: &[u8]) -> bool {
+        let (Some(&first), Some(&last)) = (il_locations.iter().min(), il_locations.iter().max()) else {
+            return true;
+        };
+
+        let window_start = first - skip;
+        let window_length = last - first + 1;
+        if window_length > IL_WINDOW_SIZE {
+            return self.check_il_locations(skip, il_locations, search_string);
+        }
+
+        // Copy the blocks of the bit array that contain the window, and decode the characters from
+        // that copy
+        let bit_array = &self.text.bit_array;
+        let bits_per_value = bit_array.bits_per_value();
+        let window_start_bit = (self.start + window_start) * bits_per_value;
+        let first_block = window_start_bit / 64;
+        let last_block = ((self.start + window_start + window_length) * bits_per_value - 1) / 64;
+
+        let mut blocks = [0_u64; IL_WINDOW_SIZE];
+        let block_count = last_block - first_block + 1;
+        blocks[..block_count].copy_from_slice(bit_array.get_data_slice(first_block, last_block + 1));
+
+        il_locations.iter().all(|&il_location| {
+            let index = il_location - skip;
+            let bit = (self.start + index) * bits_per_value - first_block * 64;
+            let (block, offset) = (bit / 64, bit % 64);
+
+            let code = if offset + bits_per_value <= 64 {
+                blocks[block] >> (64 - offset - bits_per_value)
+            } else {
+                let end_offset = offset + bits_per_value - 64;
+                blocks[block] << end_offset | blocks[block + 1] >> (64 - end_offset)
+            } & bit_array.mask();
+            search_string[index] == self.text.bit5_to_char[code as usize]
+        })
+    }
+
     /// Get an iterator over the slice.
     ///
     /// # Returns
@@ -349,6 +720,9 @@ impl<'a> Iterator for ProteinTextIterator<'a> {
 pub fn dump_compressed_text(text: Vec<u8>, writer: &mut impl Write) -> Result<(), Box<dyn Error>> {
     let bits_per_value = 5;
 
+    // Write the header to the writer
+    write_header(FileKind::Text, 0, writer)?;
+
     // Write the flags to the writer
     // 00000001 indicates that the text is compressed
     writer
@@ -368,31 +742,98 @@ pub fn dump_compressed_text(text: Vec<u8>, writer: &mut impl Write) -> Result<()
     Ok(())
 }
 
+/// Writes a `ProteinText` to a writer, in the same format as `dump_compressed_text`. If the text
+/// uses a custom alphabet, the `CUSTOM_ALPHABET_FLAG` is set in the bits per value byte and the
+/// alphabet is written after it, so `load_compressed_text` can restore it.
+///
+/// # Arguments
+///
+/// * `text` - The text to be written.
+/// * `writer` - The writer to which the compressed text will be written.
+///
+/// # Errors
+///
+/// Returns an error if writing to the writer fails.
+pub fn dump_protein_text(text: &ProteinText, writer: &mut impl Write) -> Result<(), Box<dyn Error>> {
+    // Write the header to the writer
+    write_header(FileKind::Text, 0, writer)?;
+
+    // Write the flags to the writer
+    let custom_alphabet = text.alphabet() != PROTEIN_ALPHABET;
+    let bits_per_value = text.bits_per_value() as u8;
+    writer
+        .write_all(&[if custom_alphabet { bits_per_value | CUSTOM_ALPHABET_FLAG } else { bits_per_value }])
+        .map_err(|_| "Could not write the required bits to the writer")?;
+
+    // Write the length of the alphabet (2 bytes), followed by the alphabet itself
+    if custom_alphabet {
+        writer
+            .write_all(&(text.alphabet().len() as u16).to_le_bytes())
+            .and_then(|_| writer.write_all(text.alphabet()))
+            .map_err(|_| "Could not write the alphabet to the writer")?;
+    }
+
+    // Write the size of the text to the writer
+    writer
+        .write_all(&(text.len() as u64).to_le_bytes())
+        .map_err(|_| "Could not write the size of the text to the writer")?;
+
+    // The text is already compressed, so the bit array can be written directly
+    text.bit_array
+        .write_binary(writer)
+        .map_err(|_| "Could not write the compressed text to the writer")?;
+
+    Ok(())
+}
+
 /// Load the compressed text from a reader.
 ///
 /// # Arguments
 ///
 /// * `reader` - The reader from which the compressed text will be read.
+/// * `bits_per_value` - The number of bits used to represent each character in the compressed
+///   text, as read from the file. If the `CUSTOM_ALPHABET_FLAG` is set, the alphabet is read first.
 ///
 /// # Errors
 ///
-/// Returns an error if reading from the reader fails.
-pub fn load_compressed_text(reader: &mut impl BufRead) -> Result<ProteinText, Box<dyn Error>> {
-    let bits_per_value: usize = 5;
+/// Returns an `IndexError` if reading from the reader fails, or if the bits per value do not match
+/// the alphabet of the text.
+pub fn load_compressed_text(reader: &mut impl BufRead, bits_per_value: usize) -> Result<ProteinText, IndexError> {
+    let alphabet = if bits_per_value & CUSTOM_ALPHABET_FLAG as usize != 0 {
+        // Read the length of the alphabet (2 bytes), followed by the alphabet itself
+        let mut alphabet_length_buffer = [0_u8; 2];
+        reader
+            .read_exact(&mut alphabet_length_buffer)
+            .map_err(|err| IndexError::read("the alphabet of the text", err))?;
+        let mut alphabet = vec![0_u8; u16::from_le_bytes(alphabet_length_buffer) as usize];
+        reader
+            .read_exact(&mut alphabet)
+            .map_err(|err| IndexError::read("the alphabet of the text", err))?;
+        alphabet
+    } else {
+        PROTEIN_ALPHABET.to_vec()
+    };
+
+    let bits_per_value = bits_per_value & !(CUSTOM_ALPHABET_FLAG as usize);
+    let valid_alphabet = !alphabet.is_empty() && alphabet.len() <= 256;
+    if !valid_alphabet || bits_per_value != ProteinText::bits_for_alphabet(alphabet.len()) {
+        return Err(IndexError::Unsupported { part: "the bits per value of the text" });
+    }
+
     // Read the size of the text from the binary file (8 bytes)
     let mut size_buffer = [0_u8; 8];
     reader
         .read_exact(&mut size_buffer)
-        .map_err(|_| "Could not read the size of the text from the binary file")?;
+        .map_err(|err| IndexError::read("the size of the text", err))?;
     let size = u64::from_le_bytes(size_buffer) as usize;
 
     // Read the compressed text from the binary file
     let mut compressed_text = BitArray::with_capacity(size, bits_per_value);
     compressed_text
         .read_binary(reader)
-        .map_err(|_| "Could not read the compressed text from the binary file")?;
+        .map_err(|err| IndexError::read("the compressed text", err))?;
 
-    Ok(ProteinText::new(compressed_text))
+    Ok(ProteinText::new_with_alphabet(compressed_text, &alphabet))
 }
 
 #[cfg(test)]
@@ -465,6 +906,46 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_bits_for_alphabet() {
+        assert_eq!(ProteinText::bits_for_alphabet(1), 1);
+        assert_eq!(ProteinText::bits_for_alphabet(2), 1);
+        assert_eq!(ProteinText::bits_for_alphabet(15), 4);
+        assert_eq!(ProteinText::bits_for_alphabet(16), 4);
+        assert_eq!(ProteinText::bits_for_alphabet(17), 5);
+        assert_eq!(ProteinText::bits_for_alphabet(PROTEIN_ALPHABET.len()), 5);
+    }
+
+    #[test]
+    fn test_build_from_string_with_alphabet() {
+        let alphabet = b"ACDEGHIKMNPRST$";
+        let input = "MAKCDEGHINPRST$";
+        let mut text = ProteinText::from_string_with_alphabet(input, alphabet);
+
+        assert_eq!(text.bits_per_value(), 4);
+        assert_eq!(text.alphabet(), alphabet);
+        assert_eq!(text.len(), input.len());
+        assert_eq!(text.iter().collect::<Vec<u8>>(), input.as_bytes());
+        assert!(text.range_equals(1, b"AKC"));
+
+        text.set(0, b'T');
+        assert_eq!(text.get(0), b'T');
+        assert_eq!(text.decode_range(0, 3), b"TAK");
+    }
+
+    #[test]
+    fn test_build_from_string_with_default_alphabet() {
+        let text = ProteinText::from_string_with_alphabet("ACACA-CAC$", PROTEIN_ALPHABET);
+        assert_eq!(text.bits_per_value(), 5);
+        assert!(text == ProteinText::from_string("ACACA-CAC$"));
+    }
+
+    #[test]
+    #[should_panic(expected = "Input character 'F' not in alphabet")]
+    fn test_build_from_string_with_alphabet_invalid_character() {
+        ProteinText::from_string_with_alphabet("ACF$", b"AC$");
+    }
+
     #[test]
     fn test_build_from_vec() {
         let vec = vec![b'A', b'C', b'A', b'C', b'A', b'-', b'C', b'A', b'C', b'$'];
@@ -475,6 +956,39 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_build_from_reader() {
+        let input_string = "ACACA-CAC$";
+        let reader = std::io::Cursor::new(input_string);
+
+        let text = ProteinText::from_reader(reader).unwrap();
+
+        assert_eq!(text.len(), input_string.len());
+        for (i, c) in input_string.chars().enumerate() {
+            assert_eq!(c as u8, text.get(i));
+        }
+    }
+
+    #[test]
+    fn test_build_from_reader_multiple_chunks() {
+        let input_string = format!("{}-{}$\n", "MLPGLALLLLAAWTARALEV".repeat(500), "PTDGNAGLLAEPQIAMFCGR".repeat(500));
+        let reader = std::io::BufReader::with_capacity(64, input_string.as_bytes());
+
+        let text = ProteinText::from_reader(reader).unwrap();
+
+        assert_eq!(text.len(), input_string.len() - 1);
+        for (i, c) in input_string.trim_end().chars().enumerate() {
+            assert_eq!(c as u8, text.get(i));
+        }
+    }
+
+    #[test]
+    fn test_build_from_reader_invalid_character() {
+        let reader = std::io::Cursor::new("ACA#A$");
+
+        assert!(ProteinText::from_reader(reader).is_err());
+    }
+
     #[test]
     fn test_build_from_bitarray() {
         let input_string = "ACACA-CAC$";
@@ -535,6 +1049,37 @@ mod tests {
         assert!(text_slice.equals_slice(&eq_slice_il_true, true));
     }
 
+    #[test]
+    fn test_common_prefix_len_full_match() {
+        let input_string = "ACICA-CAC$";
+        let text = ProteinText::from_string(input_string);
+        let text_slice = text.slice(1, 5);
+
+        assert_eq!(text_slice.common_prefix_len(b"CICA", false), 4);
+        assert_eq!(text_slice.common_prefix_len(b"CICA", true), 4);
+    }
+
+    #[test]
+    fn test_common_prefix_len_prefix_match() {
+        let input_string = "ACICA-CAC$";
+        let text = ProteinText::from_string(input_string);
+        let text_slice = text.slice(1, 5);
+
+        assert_eq!(text_slice.common_prefix_len(b"CIAA", false), 2);
+        assert_eq!(text_slice.common_prefix_len(b"AICA", false), 0);
+        assert!(!text_slice.equals_slice(b"CIAA", false));
+    }
+
+    #[test]
+    fn test_common_prefix_len_il_equated() {
+        let input_string = "ACICA-CAC$";
+        let text = ProteinText::from_string(input_string);
+        let text_slice = text.slice(1, 5);
+
+        assert_eq!(text_slice.common_prefix_len(b"CLCA", true), 4);
+        assert_eq!(text_slice.common_prefix_len(b"CLCA", false), 1);
+    }
+
     #[test]
     fn test_check_il_locations() {
         let input_string = "ACILA-CAC$";
@@ -548,6 +1093,56 @@ mod tests {
         assert!(!text_slice.check_il_locations(0, &il_locations, &il_false));
     }
 
+    #[test]
+    fn test_check_il_locations_batched() {
+        let input_string = "ACILA-CAC$";
+        let text = ProteinText::from_string(input_string);
+        let text_slice = text.slice(1, 5);
+        let il_locations = [1, 2];
+        let il_true = [b'C', b'I', b'L', b'A'];
+        let il_false = [b'C', b'I', b'C', b'A'];
+
+        assert!(text_slice.check_il_locations_batched(0, &il_locations, &il_true));
+        assert!(!text_slice.check_il_locations_batched(0, &il_locations, &il_false));
+        assert!(text_slice.check_il_locations_batched(0, &[], &il_false));
+    }
+
+    #[test]
+    fn test_check_il_locations_batched_randomized() {
+        // simple linear congruential generator, so the test is deterministic
+        let mut seed: u64 = 42;
+        let mut next_random = move || {
+            seed = seed.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+            (seed >> 33) as usize
+        };
+
+        let alphabet = b"ACIL";
+        let input_string: String = (0..2000).map(|_| alphabet[next_random() % alphabet.len()] as char).collect();
+        let text = ProteinText::from_string(&input_string);
+
+        for _ in 0..2000 {
+            // long search strings also test the fallback for I and L positions far apart
+            let length = 1 + next_random() % 150;
+            let start = next_random() % (text.len() - length);
+            let skip = next_random() % length;
+            let text_slice = text.slice(start + skip, start + length);
+
+            let mut search_string: Vec<u8> = input_string.as_bytes()[start..start + length].to_vec();
+            let il_locations: Vec<usize> =
+                (skip..length).filter(|&i| search_string[i] == b'I' || search_string[i] == b'L').collect();
+            if !il_locations.is_empty() && next_random() % 2 == 0 {
+                let changed_location = il_locations[next_random() % il_locations.len()];
+                search_string[changed_location] = if search_string[changed_location] == b'I' { b'L' } else { b'I' };
+            }
+
+            let search_string = &search_string[skip..];
+            assert_eq!(
+                text_slice.check_il_locations_batched(skip, &il_locations, search_string),
+                text_slice.check_il_locations(skip, &il_locations, search_string)
+            );
+        }
+    }
+
     #[test]
     fn test_dump_compressed_text() {
         let text: Vec<u8> = vec![1, 2, 3, 4, 5, 6, 7, 8, 9, 10];
@@ -556,17 +1151,42 @@ mod tests {
         dump_compressed_text(text, &mut writer).unwrap();
 
         assert_eq!(writer, vec![
-            // bits per value
+            // header
+            b'U', b'P', b'I', b'1', 2, 0, // bits per value
+            5, // size of the text
+            10, 0, 0, 0, 0, 0, 0, 0, // compressed text
+            0, 128, 74, 232, 152, 66, 134, 8
+        ]);
+    }
+
+    #[test]
+    fn test_dump_protein_text() {
+        let text = ProteinText::from_string("BCDEFGHIKL");
+
+        let mut writer = vec![];
+        dump_protein_text(&text, &mut writer).unwrap();
+
+        assert_eq!(writer, vec![
+            // header
+            b'U', b'P', b'I', b'1', 2, 0, // bits per value
             5, // size of the text
             10, 0, 0, 0, 0, 0, 0, 0, // compressed text
             0, 128, 74, 232, 152, 66, 134, 8
         ]);
     }
 
+    #[test]
+    #[should_panic(expected = "Could not write the header to the writer")]
+    fn test_dump_compressed_text_fail_header() {
+        let mut writer = FailingWriter { valid_write_count: 0 };
+
+        dump_compressed_text(vec![], &mut writer).unwrap();
+    }
+
     #[test]
     #[should_panic(expected = "Could not write the required bits to the writer")]
     fn test_dump_compressed_text_fail_required_bits() {
-        let mut writer = FailingWriter { valid_write_count: 0 };
+        let mut writer = FailingWriter { valid_write_count: 6 };
 
         dump_compressed_text(vec![], &mut writer).unwrap();
     }
@@ -574,7 +1194,7 @@ mod tests {
     #[test]
     #[should_panic(expected = "Could not write the size of the text to the writer")]
     fn test_dump_compressed_text_fail_size() {
-        let mut writer = FailingWriter { valid_write_count: 1 };
+        let mut writer = FailingWriter { valid_write_count: 7 };
 
         dump_compressed_text(vec![], &mut writer).unwrap();
     }
@@ -582,7 +1202,7 @@ mod tests {
     #[test]
     #[should_panic(expected = "Could not write the compressed text to the writer")]
     fn test_dump_compressed_text_fail_compressed_text() {
-        let mut writer = FailingWriter { valid_write_count: 3 };
+        let mut writer = FailingWriter { valid_write_count: 9 };
 
         dump_compressed_text(vec![1], &mut writer).unwrap();
     }
@@ -596,7 +1216,7 @@ mod tests {
         ];
 
         let mut reader = std::io::BufReader::new(&data[..]);
-        let compressed_text = load_compressed_text(&mut reader).unwrap();
+        let compressed_text = load_compressed_text(&mut reader, 5).unwrap();
 
         for (i, c) in "BCDEFGHIKL".chars().enumerate() {
             assert_eq!(compressed_text.get(i), c as u8);
@@ -604,19 +1224,158 @@ mod tests {
     }
 
     #[test]
-    #[should_panic(expected = "Could not read the size of the text from the binary file")]
+    fn test_dump_and_load_protein_text_with_alphabet() {
+        let alphabet = b"ACGT-$";
+        let text = ProteinText::from_string_with_alphabet("ACGGT-TTAC-CA$", alphabet);
+
+        let mut writer = vec![];
+        dump_protein_text(&text, &mut writer).unwrap();
+        assert_eq!(writer[6], 3 | CUSTOM_ALPHABET_FLAG);
+        assert_eq!(&writer[7..15], &[6, 0, b'A', b'C', b'G', b'T', b'-', b'$']);
+
+        // skip the header of 6 bytes and the bits per value
+        let mut reader = std::io::BufReader::new(&writer[7..]);
+        let loaded_text = load_compressed_text(&mut reader, writer[6] as usize).unwrap();
+
+        assert_eq!(loaded_text.bits_per_value(), 3);
+        assert_eq!(loaded_text.alphabet(), alphabet);
+        assert_eq!(loaded_text.decode_range(0, loaded_text.len()), b"ACGGT-TTAC-CA$".to_vec());
+    }
+
+    #[test]
+    fn test_load_compressed_text_unsupported_bits_per_value() {
+        let data = vec![
+            // size of the text
+            1, 0, 0, 0, 0, 0, 0, 0, // compressed text
+            0, 0, 0, 0, 0, 0, 0, 0,
+        ];
+
+        // a width other than 5 bits needs a custom alphabet
+        let mut reader = std::io::BufReader::new(&data[..]);
+        let err = load_compressed_text(&mut reader, 3).err().unwrap();
+        assert!(matches!(err, IndexError::Unsupported { part: "the bits per value of the text" }));
+
+        // the alphabet of 2 characters needs only 1 bit
+        let mut alphabet_data = vec![2, 0, b'A', b'$'];
+        alphabet_data.extend_from_slice(&data);
+        let mut reader = std::io::BufReader::new(&alphabet_data[..]);
+        let err = load_compressed_text(&mut reader, 5 | CUSTOM_ALPHABET_FLAG as usize).err().unwrap();
+        assert!(matches!(err, IndexError::Unsupported { part: "the bits per value of the text" }));
+    }
+
+    #[test]
     fn test_load_compressed_text_fail_size() {
         let mut reader = FailingReader { valid_read_count: 0 };
 
-        load_compressed_text(&mut reader).unwrap();
+        let err = load_compressed_text(&mut reader, 5).err().unwrap();
+        assert!(matches!(err, IndexError::Io { part: "the size of the text", .. }));
     }
 
     #[test]
-    #[should_panic(expected = "Could not read the compressed text from the binary file")]
     fn test_load_compressed_text_fail_compressed_text() {
         let mut reader = FailingReader { valid_read_count: 2 };
 
-        load_compressed_text(&mut reader).unwrap();
+        let err = load_compressed_text(&mut reader, 5).err().unwrap();
+        assert!(matches!(err, IndexError::Io { part: "the compressed text", .. }));
+    }
+
+    #[test]
+    fn test_decode_range() {
+        let text = ProteinText::from_string("ACACA-CAC$");
+
+        assert_eq!(text.decode_range(1, 7), b"CACA-C".to_vec());
+        assert_eq!(text.decode_range(0, 10), b"ACACA-CAC$".to_vec());
+        assert_eq!(text.decode_range(3, 3), Vec::<u8>::new());
+    }
+
+    #[test]
+    fn test_decode_range_into_reused_buffer() {
+        let text = ProteinText::from_string("ACACA-CAC$");
+        let mut buffer = Vec::new();
+
+        text.decode_range_into(0, 6, &mut buffer);
+        assert_eq!(buffer, b"ACACA-".to_vec());
+
+        // the previous contents of the buffer are discarded
+        text.decode_range_into(7, 10, &mut buffer);
+        assert_eq!(buffer, b"AC$".to_vec());
+    }
+
+    #[test]
+    fn test_range_equals() {
+        let text = ProteinText::from_string("ACACA-CAC$");
+
+        assert!(text.range_equals(1, b"CACA-C"));
+        assert!(text.range_equals(0, b"ACACA-CAC$"));
+        assert!(!text.range_equals(1, b"CACA-A"));
+        assert!(!text.range_equals(8, b"C$A"));
+        // characters outside of the alphabet never match
+        assert!(!text.range_equals(0, b"a"));
+    }
+
+    #[test]
+    fn test_equate_il_without_il_in_alphabet() {
+        let mut text = ProteinText::from_string_with_alphabet("ACGT$", b"ACGT$");
+        text.equate_il_in_place();
+        assert_eq!(text.decode_range(0, text.len()), b"ACGT$".to_vec());
+
+        let text = ProteinText::from_string_with_alphabet("ALC$", b"ACL$");
+        assert_eq!(text.equate_il().decode_range(0, text.len()), b"ALC$".to_vec());
+    }
+
+    #[test]
+    fn test_equate_il() {
+        let text = ProteinText::from_string("LIKLAL-CL$");
+        let equated = text.equate_il();
+
+        assert_eq!(equated.decode_range(0, equated.len()), b"IIKIAI-CI$".to_vec());
+        // the original text is unchanged
+        assert_eq!(text.decode_range(0, text.len()), b"LIKLAL-CL$".to_vec());
+    }
+
+    #[test]
+    fn test_equate_il_in_place() {
+        let mut text = ProteinText::from_string("LIKLAL-CL$");
+        let equated = text.equate_il();
+        text.equate_il_in_place();
+
+        assert_eq!(text.decode_range(0, text.len()), equated.decode_range(0, equated.len()));
+        assert_eq!(text.decode_range(0, text.len()), b"IIKIAI-CI$".to_vec());
+    }
+
+    #[test]
+    fn test_protein_text_eq() {
+        let text = ProteinText::from_string("ACDE-FGHI$");
+        assert!(text == text.clone());
+        assert!(text == ProteinText::from_vec(b"ACDE-FGHI$"));
+        assert!(text != ProteinText::from_string("ACDE-FGHL$"));
+        assert!(text != ProteinText::from_string("ACDE-FGH$"));
+
+        // the equated text only equals a text that was built with I's
+        assert!(ProteinText::from_string("LIK$").equate_il() == ProteinText::from_string("IIK$"));
+    }
+
+    #[test]
+    fn test_split_on_separator() {
+        let text = ProteinText::from_string("ABC-DEF-GHI$");
+        let proteins: Vec<Vec<u8>> = text.split_on_separator().map(|slice| slice.iter().collect()).collect();
+        assert_eq!(proteins, vec![b"ABC".to_vec(), b"DEF".to_vec(), b"GHI".to_vec()]);
+
+        let text = ProteinText::from_string("");
+        assert_eq!(text.split_on_separator().count(), 0);
+    }
+
+    #[test]
+    fn test_protein_count() {
+        let text = ProteinText::from_string("AAA-BBB-CC$");
+        assert_eq!(text.protein_count(b'-', b'$'), 3);
+
+        // the last protein is not terminated
+        let text = ProteinText::from_string("AAA-BBB-CC");
+        assert_eq!(text.protein_count(b'-', b'$'), 3);
+
+        let text = ProteinText::from_string("");
+        assert_eq!(text.protein_count(b'-', b'$'), 0);
     }
 
     #[test]